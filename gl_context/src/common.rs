@@ -90,6 +90,14 @@ impl GLContextBuilder {
         self
     }
 
+    /// Sets the number of bits for the alpha channel of the window's framebuffer.
+    /// Alpha bits are needed to draw transparent content to a window built with `transparent(true)`.
+    /// On Web the canvas is opaque unless this is above 0.
+    pub fn alpha_bits(&mut self, bits: u8) -> &mut Self {
+        self.gl_attributes.alpha_bits = bits;
+        self
+    }
+
     /// Sets if the context should use the sRGB color space.
     /// This has no effect on Web.
     pub fn srgb(&mut self, srgb: bool) -> &mut Self {
@@ -1,5 +1,5 @@
 use crate::common::*;
-use objc::runtime::{Object, YES};
+use objc::runtime::{Object, BOOL, YES};
 use objc::*;
 use std::ffi::c_void;
 use std::io::Error;
@@ -128,6 +128,13 @@ impl GLContextTrait for GLContext {
                 msg_send![self.gl_context, performSelectorOnMainThread:sel!(setView:) withObject:window_view waitUntilDone:YES]
             };
 
+            // Transparent windows need a transparent surface for their content to show through.
+            let opaque: BOOL = unsafe { msg_send![ns_window, isOpaque] };
+            let surface_opacity: i32 = if opaque == YES { 1 } else { 0 };
+            let () = unsafe {
+                msg_send![self.gl_context, setValues:&surface_opacity forParameter:NSOpenGLCPSurfaceOpacity]
+            };
+
             self.set_vsync(self.vsync).unwrap();
             self.ns_window = Some(ns_window);
         } else {
@@ -240,6 +247,7 @@ impl Drop for GLContext {
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum NSOpenGLContextParameter {
     NSOpenGLCPSwapInterval = 222,
+    NSOpenGLCPSurfaceOpacity = 236,
 }
 
 #[repr(u64)]
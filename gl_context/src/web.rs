@@ -14,7 +14,8 @@ impl GLContextBuilder {
 
         // These should be configurable
         let mut context_attributes = web_sys::WebGlContextAttributes::new();
-        context_attributes.alpha(false); // Disable the canvas background transparency.
+        // The canvas background is only transparent if alpha bits are requested.
+        context_attributes.alpha(self.gl_attributes.alpha_bits > 0);
 
         let context = match self.gl_attributes.webgl_version {
             WebGLVersion::One => {
@@ -69,12 +70,13 @@ impl GLContext {
     pub fn new() -> GLContextBuilder {
         GLContextBuilder {
             gl_attributes: GLContextAttributes {
-                // None of these attributes other than webgl_version are used.
+                // None of these attributes other than webgl_version and alpha_bits are used.
+                // The canvas is opaque by default.
                 major_version: 3,
                 minor_version: 3,
                 msaa_samples: 1,
                 color_bits: 24,
-                alpha_bits: 8,
+                alpha_bits: 0,
                 depth_bits: 24,
                 stencil_bits: 8,
                 srgb: true,
@@ -0,0 +1,13 @@
+/// Identifies the application to the OS, which uses it to group the application's windows
+/// and to find its icon and desktop entry.
+/// Empty fields are left to the platform's defaults.
+#[derive(Debug, Clone, Default)]
+pub struct AppInfo {
+    /// A unique reverse domain name identifier like "com.example.Viewer".
+    /// On Linux this should match the name of the application's `.desktop` file.
+    pub id: String,
+    /// The name of the application as shown to users.
+    /// Only used on MacOS, where it sets the process name.
+    /// Linux and Windows take the name from the `.desktop` file or executable instead.
+    pub name: String,
+}
@@ -0,0 +1,40 @@
+use std::time::{Duration, Instant};
+
+/// How the event loop waits once it has processed all events and draw requests.
+/// Every iteration of the event loop sends `Event::EventsCleared`, so a program can do
+/// periodic work there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ControlFlow {
+    /// Wait until there is an event or a redraw request.
+    #[default]
+    Wait,
+    /// Run the event loop again immediately without waiting.
+    Poll,
+    /// Wait until there is an event, a redraw request, or the `Instant` is reached.
+    /// Once the `Instant` has passed this behaves like `Poll` until the control flow is changed.
+    ///
+    /// On Web the event loop is woken with `setTimeout`.
+    /// Note that `Instant::now()` panics on wasm32-unknown-unknown.
+    WaitUntil(Instant),
+}
+
+impl ControlFlow {
+    /// Returns how long the event loop should wait for an event,
+    /// or `None` if it should wait until there is one.
+    /// `timeout` further limits the wait, like the timeout passed to `pump_events`.
+    /// This is used by the platform backends.
+    #[doc(hidden)]
+    pub fn wait_duration(&self, timeout: Option<Duration>) -> Option<Duration> {
+        let wait = match self {
+            ControlFlow::Wait => None,
+            ControlFlow::Poll => Some(Duration::from_secs(0)),
+            ControlFlow::WaitUntil(instant) => {
+                Some(instant.saturating_duration_since(Instant::now()))
+            }
+        };
+        match (wait, timeout) {
+            (Some(wait), Some(timeout)) => Some(wait.min(timeout)),
+            (wait, timeout) => wait.or(timeout),
+        }
+    }
+}
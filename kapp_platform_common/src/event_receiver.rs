@@ -18,6 +18,12 @@ pub fn set_callback(callback: Box<dyn FnMut(Event)>) {
     });
 }
 
+/// Drops the user callback, and anything it owns, like `Window`s.
+/// Events sent afterwards are ignored.
+pub fn clear_callback() {
+    set_callback(Box::new(|_| {}));
+}
+
 /// Sends an event to the user callback
 pub fn send_event(event: Event) {
     // try_with because events may be sent during destruction, which should be ignored.
@@ -1,5 +1,10 @@
 use crate::keys::Key;
+use crate::FileDialogId;
+use crate::Modifiers;
+use crate::UserEvent;
 use crate::WindowId;
+use std::ops::Range;
+use std::path::PathBuf;
 use std::time::Duration;
 
 /// Input and system events.
@@ -30,51 +35,133 @@ pub enum Event {
     /// This event should be used for text entry instead of `KeyDown`.
     /// Combinations of key presses will produce characters not reported by
     /// `KeyDown`
+    ///
+    /// On SDL, Windows and MacOS this is only sent while text input is started
+    /// for the window with `start_text_input`.
     CharacterReceived {
         character: char,
+        window_id: WindowId,
     },
     /// The composition status of the IME (Input Method Editor).
     /// This event is fired when a keystroke causes the composition status to change.
     /// For proper IME support, use this data as an "intermediate" visualization of
     /// user input, prepended to the caret position.
+    ///
+    /// `cursor`, `selection`, and `segments` are byte offsets into `composition`.
+    /// `selection` is usually the clause currently being converted.
     IMEComposition {
         composition: String,
+        cursor: Option<usize>,
+        selection: Option<Range<usize>>,
+        segments: Vec<CompositionSegment>,
+        window_id: WindowId,
     },
     /// IME composition has ended.
     /// The "intermediate" visualization should be cleared.
-    IMEEndComposition,
+    IMEEndComposition {
+        window_id: WindowId,
+    },
     // ------------------- Input Events ---------------------
     /// A key is pressed.
+    ///
+    /// `key` is the key's meaning in the current keyboard layout and should be used for shortcuts.
+    /// Keys that produce a character without a `Key` variant report their physical key.
+    ///
+    /// `physical_key` is the key's position on the keyboard, named after the key
+    /// in that position on a US keyboard. Use it for layout independent controls like WASD.
+    ///
+    /// `scancode` is the platform specific code for the physical key. It is 0 on web.
+    ///
+    /// `text` is the printable text the key press produces with the current layout and modifiers.
+    /// On SDL it is only filled while text input is started for the window with `start_text_input`,
+    /// otherwise it is `None`. Windows and MacOS fill it either way.
     /// For text input use the `CharacterReceived` event instead.
     KeyDown {
         key: Key,
+        physical_key: Key,
+        scancode: u32,
+        text: Option<String>,
+        window_id: WindowId,
+        modifiers: Modifiers,
         timestamp: Duration,
     },
     /// A key is released.
     KeyUp {
         key: Key,
+        physical_key: Key,
+        scancode: u32,
+        window_id: WindowId,
+        modifiers: Modifiers,
         timestamp: Duration,
     },
     /// A repeat of a held key.
+    /// `text` is filled the same way as for `KeyDown`.
     KeyRepeat {
         key: Key,
+        physical_key: Key,
+        scancode: u32,
+        text: Option<String>,
+        window_id: WindowId,
+        modifiers: Modifiers,
         timestamp: Duration,
     },
+    /// The held keyboard modifiers have changed.
+    /// This is also sent if the modifiers changed while the application did not have focus.
+    ModifiersChanged {
+        modifiers: Modifiers,
+    },
+    /// The keyboard layout has changed, so labels returned by `key_label` may have changed.
+    /// Not sent on web.
+    KeyboardLayoutChanged,
     /// The pointer position has changed.
     /// Reports physical coordinates in relation to the pointer's window
+    ///
+    /// `pointer_id` distinguishes simultaneous pointers like multiple touches.
+    /// The mouse always has a `pointer_id` of 0 and other pointers never do.
     PointerMoved {
         x: f64,
         y: f64,
         source: PointerSource,
+        pointer_id: u64,
+        window_id: WindowId,
+        modifiers: Modifiers,
         timestamp: Duration,
     },
+    /// The pointer has entered a window.
+    /// Reports physical coordinates in relation to the window.
+    PointerEntered {
+        window_id: WindowId,
+        x: f64,
+        y: f64,
+    },
+    /// The pointer has left a window.
+    PointerLeft {
+        window_id: WindowId,
+    },
     /// How much the mouse has moved regardless of the pointer position.
     /// This event will be sent even if the mouse position is locked.
     MouseMotion {
         delta_x: f64,
         delta_y: f64,
+        window_id: WindowId,
+        timestamp: Duration,
+    },
+    /// Unaccelerated mouse movement, suitable for camera controls.
+    /// Only sent after raw mouse motion is enabled with `Application::set_raw_mouse_motion`.
+    /// On SDL this is only sent while the pointer is locked.
+    /// Not sent on MacOS or web.
+    RawMouseMotion {
+        delta_x: f64,
+        delta_y: f64,
+        window_id: WindowId,
         timestamp: Duration,
     },
+    /// The OS has released a pointer locked or confined to a window,
+    /// usually because the window lost focus.
+    /// The lock or confinement must be requested again to restore it.
+    PointerLockLost {
+        window_id: WindowId,
+    },
     /// A pointer, mouse, touch or, or stylus has been pressed down.
     /// Note that this is sent by multiple web events, not just web's "pointerdown" event.
     PointerDown {
@@ -82,6 +169,9 @@ pub enum Event {
         y: f64,
         source: PointerSource,
         button: PointerButton,
+        pointer_id: u64,
+        window_id: WindowId,
+        modifiers: Modifiers,
         timestamp: Duration,
     },
     /// Reports physical coordinates in relation to the pointer's window
@@ -90,6 +180,9 @@ pub enum Event {
         y: f64,
         source: PointerSource,
         button: PointerButton,
+        pointer_id: u64,
+        window_id: WindowId,
+        modifiers: Modifiers,
         timestamp: Duration,
     },
     /// Occurs when pressing a mouse button twice in quick succession.
@@ -99,6 +192,7 @@ pub enum Event {
         x: f64,
         y: f64,
         button: PointerButton,
+        window_id: WindowId,
         timestamp: Duration,
     },
     /// Occurs when pressing a mouse button twice in quick succession.
@@ -109,6 +203,7 @@ pub enum Event {
         x: f64,
         y: f64,
         button: PointerButton,
+        window_id: WindowId,
         timestamp: Duration,
     },
     /// Occurs when pressing a mouse button twice in quick succession.
@@ -118,23 +213,52 @@ pub enum Event {
         x: f64,
         y: f64,
         button: PointerButton,
+        window_id: WindowId,
         timestamp: Duration,
     },
-    /// If delta_x is set it horizontal scrolling from something like a trackpad.
-    /// Momentum may be added to this value
+    /// A mouse wheel or touchpad scroll.
+    /// An `x` delta is horizontal scrolling from something like a trackpad.
+    ///
+    /// `phase` is only known for touchpads on MacOS, elsewhere it is `ScrollPhase::Unknown`.
+    ///
+    /// The SDL backend, used on Linux, is built against SDL 2.0.14, which only reports
+    /// whole lines, so touchpads and high resolution wheels scroll in whole-line steps.
+    /// Fractional deltas need SDL 2.0.18's `preciseX` and `preciseY`.
     ///
     /// Note that on web this doesn't correspond to "scroll" events and instead
     /// corresponds to "wheel". Web "scroll" events can be triggered by moving the scrollbar
     Scroll {
-        delta_x: f64,
-        delta_y: f64,
+        delta: ScrollDelta,
+        phase: ScrollPhase,
+        window_id: WindowId,
+        modifiers: Modifiers,
+        timestamp: Duration,
+    },
+    /// A touchpad or multi-finger touch gesture has begun.
+    /// `GestureUpdate` events for the same `GestureKind` follow until a `GestureEnd` is sent.
+    ///
+    /// On MacOS pinch and rotate gestures are sent.
+    /// On SDL pinch, rotate, and pan gestures are produced from multi-finger touchscreen input.
+    /// Each kind begins once it has changed enough on its own, so a pan isn't also a pinch.
+    /// SDL 2.0.14 doesn't report touchpad gestures, so Linux touchpads produce no gesture events.
+    /// On Web only `GestureUpdate` is sent for pinches, which are detected from
+    /// "wheel" events with the ctrl modifier set.
+    GestureBegin {
+        kind: GestureKind,
+        window_id: WindowId,
+        timestamp: Duration,
+    },
+    /// The values in `gesture` are the change since the previous update.
+    GestureUpdate {
+        gesture: Gesture,
         window_id: WindowId,
         timestamp: Duration,
     },
-    /// A number corresponding to a pinch gesture.
-    /// Presently only sent on MacOS.
-    PinchGesture {
-        delta: f64,
+    /// `cancelled` is set if the OS reports the gesture was cancelled instead of completed.
+    GestureEnd {
+        kind: GestureKind,
+        cancelled: bool,
+        window_id: WindowId,
         timestamp: Duration,
     },
     // ------------------- Window Events  ---------------------
@@ -191,12 +315,30 @@ pub enum Event {
         window_id: WindowId,
     },
     // ------------------- Application Events  ---------------------
+    /// A file dialog opened with `open_file_dialog` or `save_file_dialog` was closed.
+    /// `paths` is empty if the dialog was cancelled or couldn't be shown.
+    FileDialogResult {
+        id: FileDialogId,
+        paths: Vec<PathBuf>,
+    },
     /// The application is going to quit immediately after this event is processed.
     /// Perform any final cleanup that's necessary. The quit cannot be cancelled.
+    ///
+    /// This is always the last event. Afterwards the event callback is dropped,
+    /// which closes any windows it owns, and `EventLoop::run` returns.
+    /// It's not sent on Web, where the event loop never stops.
     Quit,
     /// A quit is requested, but it is up to the program to call quit().
+    /// Like `WindowCloseRequested` nothing happens unless the program accepts the request.
     QuitRequested,
+    /// A value sent with an event loop proxy, possibly from another thread.
+    /// Values are received in the order they were sent.
+    User(UserEvent),
     /// When the event loop sends its last event
+    ///
+    /// Sent once per iteration of the event loop, before draws.
+    /// With `ControlFlow::Poll` or `ControlFlow::WaitUntil` this is also sent when the
+    /// event loop wakes without any other events.
     EventsCleared,
 }
 
@@ -218,3 +360,105 @@ pub enum PointerButton {
     Extra2,
     Unknown,
 }
+
+/// A clause of an IME composition and how it should be drawn.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct CompositionSegment {
+    /// Byte range of the clause within the composition.
+    pub range: Range<usize>,
+    pub kind: CompositionSegmentKind,
+}
+
+impl CompositionSegment {
+    /// Segments for IMEs that only report the clause being converted as the selection.
+    /// The selected clause is the target and the rest of the composition is converted.
+    /// Without a selection the whole composition is unconverted.
+    pub fn from_selection(length: usize, selection: &Option<Range<usize>>) -> Vec<Self> {
+        let mut segments = Vec::new();
+        let mut push = |range: Range<usize>, kind| {
+            if !range.is_empty() {
+                segments.push(CompositionSegment { range, kind })
+            }
+        };
+        match selection {
+            Some(selection) => {
+                push(0..selection.start, CompositionSegmentKind::Converted);
+                push(selection.clone(), CompositionSegmentKind::Target);
+                push(selection.end..length, CompositionSegmentKind::Converted);
+            }
+            None => push(0..length, CompositionSegmentKind::Unconverted),
+        }
+        segments
+    }
+}
+
+#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
+pub enum CompositionSegmentKind {
+    /// Text that has not been converted yet. Usually drawn with a thin underline.
+    Unconverted,
+    /// Text that has been converted. Usually drawn with a thin underline.
+    Converted,
+    /// The clause currently being converted. Usually drawn with a thick underline.
+    Target,
+}
+
+/// The amount scrolled by a `Scroll` event.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum ScrollDelta {
+    /// Lines or rows to scroll, as reported by most mouse wheels.
+    /// High resolution wheels may report fractions of a line, except on SDL
+    /// where only whole lines are reported.
+    Lines { x: f64, y: f64 },
+    /// Physical pixels to scroll, as reported by touchpads and other precise devices.
+    /// Not sent on SDL.
+    Pixels { x: f64, y: f64 },
+}
+
+/// Where a `Scroll` event is within a scrolling interaction.
+#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
+pub enum ScrollPhase {
+    /// The device does not report phases, as with most mouse wheels.
+    Unknown,
+    /// The user began scrolling, such as placing fingers on a touchpad.
+    Began,
+    Changed,
+    /// The user stopped scrolling. `Momentum` events may follow.
+    Ended,
+    /// Scrolling continues from momentum after the user has stopped scrolling.
+    Momentum,
+    MomentumEnded,
+}
+
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum Gesture {
+    /// `scale` is the change in magnification, where 0.0 is no change
+    /// and 0.1 is 10% larger than the previous update.
+    Pinch { scale: f64 },
+    /// `angle` is the change in rotation in radians.
+    /// Positive values are clockwise.
+    Rotate { angle: f64 },
+    /// A multi-finger swipe or pan.
+    /// Deltas are reported in physical coordinates.
+    Pan {
+        delta_x: f64,
+        delta_y: f64,
+        fingers: u32,
+    },
+}
+
+impl Gesture {
+    pub fn kind(&self) -> GestureKind {
+        match self {
+            Gesture::Pinch { .. } => GestureKind::Pinch,
+            Gesture::Rotate { .. } => GestureKind::Rotate,
+            Gesture::Pan { .. } => GestureKind::Pan,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
+pub enum GestureKind {
+    Pinch,
+    Rotate,
+    Pan,
+}
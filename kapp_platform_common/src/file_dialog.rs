@@ -0,0 +1,40 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Options for `Application::open_file_dialog` and `Application::save_file_dialog`.
+#[derive(Debug, Clone, Default)]
+pub struct FileDialogOptions {
+    pub title: String,
+    /// Restricts which files can be picked. Every file can be picked if this is empty.
+    pub filters: Vec<FileFilter>,
+    /// The folder the dialog starts in.
+    pub current_folder: Option<PathBuf>,
+    /// The suggested file name for a save dialog.
+    pub current_name: String,
+    /// Allows picking more than one file in an open dialog.
+    pub multiple: bool,
+    /// Picks folders instead of files in an open dialog.
+    pub directory: bool,
+}
+
+/// A named group of file extensions, like "Images" with `["png", "jpg"]`.
+/// Extensions don't include the leading period.
+#[derive(Debug, Clone)]
+pub struct FileFilter {
+    pub name: String,
+    pub extensions: Vec<String>,
+}
+
+/// Identifies the `Event::FileDialogResult` for a file dialog.
+#[derive(Debug, Hash, Eq, PartialEq, Clone, Copy)]
+pub struct FileDialogId(u64);
+
+static NEXT_FILE_DIALOG_ID: AtomicU64 = AtomicU64::new(0);
+
+impl FileDialogId {
+    /// Generates a FileDialogId that hasn't been used before.
+    /// There should never be a reason to call this directly.
+    pub fn generate() -> Self {
+        Self(NEXT_FILE_DIALOG_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
@@ -0,0 +1,35 @@
+/// The part of a window under the pointer, as returned by a window's hit-test.
+/// The OS moves or resizes the window when a region other than `Client` is dragged,
+/// which lets windows without a titlebar draw their own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HitTestResult {
+    /// Window content that receives pointer events as usual.
+    Client,
+    /// Dragging moves the window, like dragging a titlebar.
+    Caption,
+    ResizeNorth,
+    ResizeSouth,
+    ResizeEast,
+    ResizeWest,
+    ResizeNorthEast,
+    ResizeNorthWest,
+    ResizeSouthEast,
+    ResizeSouthWest,
+}
+
+/// A function that's passed a position in physical coordinates relative to the
+/// window's upper left corner and returns the part of the window at that position.
+pub type HitTest = Box<dyn Fn(f64, f64) -> HitTestResult>;
+
+/// An edge or corner of a window that a resize can be started from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResizeEdge {
+    North,
+    South,
+    East,
+    West,
+    NorthEast,
+    NorthWest,
+    SouthEast,
+    SouthWest,
+}
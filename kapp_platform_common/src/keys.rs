@@ -1,6 +1,27 @@
-#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
-#[non_exhaustive]
-pub enum Key {
+// Generates the `Key` enum along with a list of its variants and their names.
+macro_rules! keys {
+    ($($key:ident,)*) => {
+        #[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
+        #[non_exhaustive]
+        pub enum Key {
+            $($key,)*
+        }
+
+        impl Key {
+            /// Every `Key`, in declaration order.
+            pub const ALL: &'static [Key] = &[$(Key::$key,)*];
+
+            /// The name of the variant, which is also its `Display` and `FromStr` format.
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(Key::$key => stringify!($key),)*
+                }
+            }
+        }
+    };
+}
+
+keys! {
     Unknown,
     // --------- International and IME (Input method editor) keys --------------
     KanaHangul,
@@ -235,3 +256,118 @@ pub enum Key {
     Play,
     Zoom,
 }
+
+/// Formats the key as its variant name, for example `LeftShift` or `Digit1`.
+impl std::fmt::Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Parses a key from its variant name, for example `LeftShift` or `Digit1`.
+/// This lets key bindings be stored as text.
+impl std::str::FromStr for Key {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Key::ALL
+            .iter()
+            .copied()
+            .find(|key| key.name() == s)
+            .ok_or(())
+    }
+}
+
+impl Key {
+    /// The key that produces a character on a US keyboard without modifiers.
+    /// Letters of either case produce their letter key.
+    /// Returns `Key::Unknown` for characters without a corresponding key.
+    pub fn from_character(character: char) -> Key {
+        use Key::*;
+        match character.to_ascii_uppercase() {
+            'A' => A,
+            'B' => B,
+            'C' => C,
+            'D' => D,
+            'E' => E,
+            'F' => F,
+            'G' => G,
+            'H' => H,
+            'I' => I,
+            'J' => J,
+            'K' => K,
+            'L' => L,
+            'M' => M,
+            'N' => N,
+            'O' => O,
+            'P' => P,
+            'Q' => Q,
+            'R' => R,
+            'S' => S,
+            'T' => T,
+            'U' => U,
+            'V' => V,
+            'W' => W,
+            'X' => X,
+            'Y' => Y,
+            'Z' => Z,
+            '0' => Digit0,
+            '1' => Digit1,
+            '2' => Digit2,
+            '3' => Digit3,
+            '4' => Digit4,
+            '5' => Digit5,
+            '6' => Digit6,
+            '7' => Digit7,
+            '8' => Digit8,
+            '9' => Digit9,
+            ' ' => Space,
+            '!' => Exclaim,
+            '"' => Quotedbl,
+            '#' => Hash,
+            '$' => Dollar,
+            '%' => Percent,
+            '&' => Ampersand,
+            '\'' => Quote,
+            '(' => LeftParen,
+            ')' => RightParen,
+            '*' => Asterisk,
+            '+' => Plus,
+            ',' => Comma,
+            '-' => Minus,
+            '.' => Period,
+            '/' => Slash,
+            ':' => Colon,
+            ';' => Semicolon,
+            '<' => Less,
+            '=' => Equal,
+            '>' => Greater,
+            '?' => Question,
+            '@' => At,
+            '[' => BracketLeft,
+            '\\' => Backslash,
+            ']' => BracketRight,
+            '^' => Caret,
+            '_' => Underscore,
+            '`' => Backquote,
+            '\t' => Tab,
+            '\r' => Return,
+            '\u{8}' => Backspace,
+            '\u{1b}' => Escape,
+            '\u{7f}' => Delete,
+            _ => Unknown,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_and_from_str_round_trip() {
+        for key in Key::ALL {
+            assert_eq!(key.to_string().parse::<Key>(), Ok(*key));
+        }
+        assert_eq!("NotAKey".parse::<Key>(), Err(()));
+    }
+}
@@ -5,22 +5,44 @@
 ///   Each event has documented behavior that must be conformed to.
 ///   event_receiver should be used on platforms where calls to a platform
 ///   functions can trigger events.
+mod app_info;
+mod control_flow;
 mod cursors;
 pub mod event_receiver;
 mod events;
+mod file_dialog;
+mod hit_test;
 mod keys;
+mod message_box;
+mod modifiers;
 mod platform_traits;
+pub mod quit_manager;
 pub mod redraw_manager;
 mod screen_id;
+pub mod text_input_manager;
+mod user_event;
 mod window_id;
 mod window_parameters;
 
+pub use app_info::AppInfo;
+pub use control_flow::ControlFlow;
 pub use cursors::Cursor;
-pub use events::{Event, PointerButton, PointerSource};
+pub use events::{
+    CompositionSegment, CompositionSegmentKind, Event, Gesture, GestureKind, PointerButton,
+    PointerSource, ScrollDelta, ScrollPhase,
+};
+pub use file_dialog::{FileDialogId, FileDialogOptions, FileFilter};
+pub use hit_test::{HitTest, HitTestResult, ResizeEdge};
 pub use keys::Key;
-pub use platform_traits::{PlatformApplicationTrait, PlatformEventLoopTrait};
+pub use message_box::{MessageBox, MessageBoxButton, MessageBoxButtons, MessageBoxKind};
+pub use modifiers::Modifiers;
+pub use platform_traits::{
+    PlatformApplicationTrait, PlatformEventLoopProxyTrait, PlatformEventLoopPumpTrait,
+    PlatformEventLoopTrait,
+};
 pub use raw_window_handle;
 pub use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
 pub use screen_id::ScreenId;
+pub use user_event::UserEvent;
 pub use window_id::{RawWindowHandleTrait, WindowId};
-pub use window_parameters::WindowParameters;
+pub use window_parameters::{WindowKind, WindowParameters};
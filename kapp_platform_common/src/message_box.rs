@@ -0,0 +1,74 @@
+/// A modal dialog with a message, shown with `Application::message_box`.
+#[derive(Debug, Clone)]
+pub struct MessageBox {
+    pub title: String,
+    pub text: String,
+    pub kind: MessageBoxKind,
+    pub buttons: MessageBoxButtons,
+}
+
+/// Determines the icon shown in a message box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageBoxKind {
+    Info,
+    Warning,
+    Error,
+}
+
+/// The set of buttons shown in a message box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageBoxButtons {
+    Ok,
+    OkCancel,
+    YesNo,
+    YesNoCancel,
+}
+
+/// The button pressed to close a message box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageBoxButton {
+    Ok,
+    Cancel,
+    Yes,
+    No,
+}
+
+impl MessageBoxButtons {
+    /// The buttons in the order they should be presented.
+    /// The first button is the default.
+    pub fn buttons(&self) -> &'static [MessageBoxButton] {
+        match self {
+            MessageBoxButtons::Ok => &[MessageBoxButton::Ok],
+            MessageBoxButtons::OkCancel => &[MessageBoxButton::Ok, MessageBoxButton::Cancel],
+            MessageBoxButtons::YesNo => &[MessageBoxButton::Yes, MessageBoxButton::No],
+            MessageBoxButtons::YesNoCancel => &[
+                MessageBoxButton::Yes,
+                MessageBoxButton::No,
+                MessageBoxButton::Cancel,
+            ],
+        }
+    }
+
+    /// The button reported if the message box is dismissed without pressing a button,
+    /// like with the escape key.
+    pub fn dismiss_button(&self) -> MessageBoxButton {
+        match self {
+            MessageBoxButtons::Ok => MessageBoxButton::Ok,
+            MessageBoxButtons::YesNo => MessageBoxButton::No,
+            MessageBoxButtons::OkCancel | MessageBoxButtons::YesNoCancel => {
+                MessageBoxButton::Cancel
+            }
+        }
+    }
+}
+
+impl MessageBoxButton {
+    pub fn label(&self) -> &'static str {
+        match self {
+            MessageBoxButton::Ok => "OK",
+            MessageBoxButton::Cancel => "Cancel",
+            MessageBoxButton::Yes => "Yes",
+            MessageBoxButton::No => "No",
+        }
+    }
+}
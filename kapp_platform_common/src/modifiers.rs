@@ -0,0 +1,102 @@
+use std::ops::{BitAnd, BitOr, BitOrAssign, Not};
+
+/// The keyboard modifiers held when an event occurred.
+/// For events caused by a modifier key the modifiers include the key's change.
+///
+/// Combine modifiers with `|` and check them with `contains`:
+/// ```
+/// # use kapp_platform_common::Modifiers;
+/// let modifiers = Modifiers::CONTROL | Modifiers::SHIFT;
+/// assert!(modifiers.contains(Modifiers::CONTROL));
+/// ```
+#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone, Default)]
+pub struct Modifiers {
+    bits: u8,
+}
+
+impl Modifiers {
+    pub const SHIFT: Modifiers = Modifiers { bits: 1 << 0 };
+    pub const CONTROL: Modifiers = Modifiers { bits: 1 << 1 };
+    /// Option on MacOS.
+    pub const ALT: Modifiers = Modifiers { bits: 1 << 2 };
+    /// Command on MacOS and the Windows key on Windows.
+    pub const SUPER: Modifiers = Modifiers { bits: 1 << 3 };
+    pub const CAPS_LOCK: Modifiers = Modifiers { bits: 1 << 4 };
+    /// MacOS keyboards have no num lock, so this is never set on MacOS.
+    pub const NUM_LOCK: Modifiers = Modifiers { bits: 1 << 5 };
+
+    pub const fn empty() -> Self {
+        Self { bits: 0 }
+    }
+
+    pub const fn bits(self) -> u8 {
+        self.bits
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.bits == 0
+    }
+
+    /// Returns true if all of the modifiers in `other` are set.
+    pub const fn contains(self, other: Modifiers) -> bool {
+        self.bits & other.bits == other.bits
+    }
+
+    /// Sets or clears the modifiers in `other`.
+    pub fn set(&mut self, other: Modifiers, value: bool) {
+        if value {
+            self.bits |= other.bits;
+        } else {
+            self.bits &= !other.bits;
+        }
+    }
+
+    pub fn shift(self) -> bool {
+        self.contains(Self::SHIFT)
+    }
+
+    pub fn control(self) -> bool {
+        self.contains(Self::CONTROL)
+    }
+
+    pub fn alt(self) -> bool {
+        self.contains(Self::ALT)
+    }
+
+    pub fn super_key(self) -> bool {
+        self.contains(Self::SUPER)
+    }
+}
+
+impl BitOr for Modifiers {
+    type Output = Self;
+    fn bitor(self, other: Self) -> Self {
+        Self {
+            bits: self.bits | other.bits,
+        }
+    }
+}
+
+impl BitOrAssign for Modifiers {
+    fn bitor_assign(&mut self, other: Self) {
+        self.bits |= other.bits;
+    }
+}
+
+impl BitAnd for Modifiers {
+    type Output = Self;
+    fn bitand(self, other: Self) -> Self {
+        Self {
+            bits: self.bits & other.bits,
+        }
+    }
+}
+
+impl Not for Modifiers {
+    type Output = Self;
+    fn not(self) -> Self {
+        Self {
+            bits: !self.bits & 0b0011_1111,
+        }
+    }
+}
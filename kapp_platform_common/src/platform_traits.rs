@@ -1,10 +1,20 @@
 /// These are the core functions to be implemented by each platform.
-use crate::{raw_window_handle::RawWindowHandle, Cursor, WindowId, WindowParameters};
+use crate::{
+    raw_window_handle::RawWindowHandle, AppInfo, ControlFlow, Cursor, FileDialogId,
+    FileDialogOptions, HitTest, Key, MessageBox, MessageBoxButton, ResizeEdge, UserEvent, WindowId,
+    WindowParameters,
+};
+use std::time::Duration;
+
 pub trait PlatformApplicationTrait {
     type EventLoop: PlatformEventLoopTrait;
+    type EventLoopProxy: PlatformEventLoopProxyTrait;
 
-    fn new() -> Self;
+    /// `app_info` identifies the application to the OS.
+    /// It should be applied before any windows are created.
+    fn new(app_info: &AppInfo) -> Self;
     fn event_loop(&mut self) -> Self::EventLoop;
+    fn event_loop_proxy(&mut self) -> Self::EventLoopProxy;
 
     /// Sets window position in physical coordinates on its current screen.
     fn set_window_position(&mut self, window_id: WindowId, x: u32, y: u32);
@@ -17,6 +27,41 @@ pub trait PlatformApplicationTrait {
     /// Returns the window to the state where it's not minimized, maximized, or fullscreen
     fn restore_window(&mut self, window_id: WindowId);
     fn close_window(&mut self, window_id: WindowId);
+    fn show_window(&mut self, window_id: WindowId);
+    fn hide_window(&mut self, window_id: WindowId);
+    /// Brings the window to the front and gives it keyboard focus.
+    fn focus_window(&mut self, window_id: WindowId);
+    /// Requests the user's attention for the window, like by flashing its taskbar or dock icon.
+    fn request_window_attention(&mut self, window_id: WindowId);
+
+    fn is_window_visible(&mut self, window_id: WindowId) -> bool;
+    fn is_window_focused(&mut self, window_id: WindowId) -> bool;
+    fn is_window_minimized(&mut self, window_id: WindowId) -> bool;
+    fn is_window_maximized(&mut self, window_id: WindowId) -> bool;
+
+    /// Sets the minimum size of the window's content area with physical coordinates.
+    /// `None` removes the limit.
+    fn set_window_min_size(&mut self, window_id: WindowId, size: Option<(u32, u32)>);
+    /// Sets the maximum size of the window's content area with physical coordinates.
+    /// `None` removes the limit.
+    fn set_window_max_size(&mut self, window_id: WindowId, size: Option<(u32, u32)>);
+    fn set_window_resizable(&mut self, window_id: WindowId, resizable: bool);
+    /// Shows or hides the window's titlebar and borders.
+    fn set_window_decorations(&mut self, window_id: WindowId, decorations: bool);
+    /// Keeps the width to height ratio of the window's content area while the user resizes it.
+    fn set_window_aspect_ratio(&mut self, window_id: WindowId, ratio: Option<(u32, u32)>);
+    /// Resizes the window's content area in steps of a physical size while the user resizes it.
+    fn set_window_resize_increments(&mut self, window_id: WindowId, increments: Option<(u32, u32)>);
+    /// Sets the opacity of the entire window, from 0.0 (invisible) to 1.0 (opaque).
+    fn set_window_opacity(&mut self, window_id: WindowId, opacity: f64);
+    /// Sets the function consulted to find which part of the window is under the pointer.
+    /// `None` returns the window to its regular behavior.
+    /// The hit-test should be dropped when the window is closed.
+    fn set_window_hit_test(&mut self, window_id: WindowId, hit_test: Option<HitTest>);
+    /// Starts moving the window with the pointer as if its titlebar was dragged.
+    fn begin_window_drag_move(&mut self, window_id: WindowId);
+    /// Starts resizing the window with the pointer as if the edge was dragged.
+    fn begin_window_drag_resize(&mut self, window_id: WindowId, edge: ResizeEdge);
 
     fn get_window_size(&mut self, _window_id: WindowId) -> (u32, u32);
     fn get_window_scale(&mut self, _window_id: WindowId) -> f64;
@@ -32,6 +77,18 @@ pub trait PlatformApplicationTrait {
     /// Allow the mouse to move freely again
     fn unlock_mouse_position(&mut self);
 
+    /// Moves the pointer to a position in physical coordinates relative to the window's upper left corner.
+    fn set_pointer_position(&mut self, window_id: WindowId, x: f64, y: f64);
+    /// Keeps the pointer within the window without hiding it.
+    /// A `PointerLockLost` event should be sent if the OS releases the pointer.
+    fn confine_pointer(&mut self, window_id: WindowId, confine: bool);
+    /// Lock the pointer position while the window has focus.
+    /// A `PointerLockLost` event should be sent if the OS releases the pointer.
+    fn lock_pointer(&mut self, window_id: WindowId);
+    fn unlock_pointer(&mut self, window_id: WindowId);
+    /// Enables or disables `RawMouseMotion` events.
+    fn set_raw_mouse_motion(&mut self, enabled: bool);
+
     fn new_window(&mut self, window_parameters: &WindowParameters) -> WindowId;
 
     /// Request that the application should quit immediately.
@@ -40,7 +97,25 @@ pub trait PlatformApplicationTrait {
     /// If termination is initiated while the program closure is active then
     /// things may be borrowed multiple times.
     /// The termination should occur before any requested draw events.
-    fn quit(&self);
+    /// `exit_code` is returned from `PlatformEventLoopTrait::run`.
+    /// The last call's `exit_code` is used.
+    fn quit(&self, exit_code: i32);
+
+    /// Sets how the event loop waits after an iteration.
+    /// This should take effect at the end of the current iteration if called from within the event loop.
+    fn set_control_flow(&mut self, control_flow: ControlFlow);
+
+    /// Shows a modal message box and blocks until it's closed.
+    /// Returns `None` if the message box couldn't be shown.
+    /// This may be called before the event loop runs or from within it.
+    /// Events produced while the message box is open should be queued.
+    fn message_box(&mut self, message_box: &MessageBox) -> Option<MessageBoxButton>;
+
+    /// Shows a file dialog without blocking.
+    /// An `Event::FileDialogResult` with `id` must be sent when the dialog closes,
+    /// even if it couldn't be shown.
+    fn open_file_dialog(&mut self, id: FileDialogId, options: &FileDialogOptions);
+    fn save_file_dialog(&mut self, id: FileDialogId, options: &FileDialogOptions);
 
     /// Sets the cursor in a way that persists between all windows for the current program.
     fn set_cursor(&mut self, cursor: Cursor);
@@ -49,11 +124,17 @@ pub trait PlatformApplicationTrait {
     fn hide_cursor(&mut self);
     fn show_cursor(&mut self);
 
-    /// Enable whatever is needed for OS text events to be sent.
-    fn start_text_input(&mut self);
-    fn end_text_input(&mut self);
+    /// Enable whatever is needed for OS text events to be sent to the window,
+    /// or to every window if `window_id` is `None`.
+    /// `text_input_manager` tracks which windows text input is enabled for.
+    fn start_text_input(&mut self, window_id: Option<WindowId>);
+    fn end_text_input(&mut self, window_id: Option<WindowId>);
+
+    /// Returns the label of the key in the given physical position with the current keyboard layout.
+    /// Returns `None` if the label is unknown.
+    fn key_label(&self, physical_key: Key) -> Option<String>;
 
-    /// Set the rectangle used for text input.
+    /// Set the rectangle used for text input, in physical coordinates relative to the window's upper left corner.
     /// This lets the OS know where it should position text input related popups.
     fn set_text_input_rectangle(
         &mut self,
@@ -70,6 +151,33 @@ pub trait PlatformApplicationTrait {
 }
 
 pub trait PlatformEventLoopTrait {
-    /// Runs until the application quits.
-    fn run(&self, callback: Box<dyn FnMut(crate::Event)>);
+    /// Runs until the application quits and returns the exit code passed to `quit`.
+    /// `Event::Quit` must be the last event sent.
+    /// The callback must be dropped before returning so that windows it owns are closed
+    /// before the platform is torn down. `event_receiver::clear_callback` does this.
+    fn run(&self, callback: Box<dyn FnMut(crate::Event)>) -> i32;
+}
+
+/// Implemented by platforms whose event loop can return control between iterations.
+/// MacOS and Web run their event loops inside the OS or browser, so they don't implement this.
+pub trait PlatformEventLoopPumpTrait: PlatformEventLoopTrait {
+    /// Runs a single iteration of the event loop: processes pending events and redraw
+    /// requests, then returns.
+    /// If there are no redraw requests this first waits up to `timeout` for an event,
+    /// or until there is one if `timeout` is `None`.
+    /// The wait is shortened if the control flow asks for it.
+    /// If quit has been called `Event::Quit` is sent instead of draw events.
+    /// `Event::Quit` is only sent once and later calls return without processing events,
+    /// which `quit_manager::pump_events` handles.
+    /// Platforms that produce events outside the event loop should keep `callback`
+    /// after returning so those events aren't lost.
+    fn pump_events(&self, timeout: Option<Duration>, callback: Box<dyn FnMut(crate::Event)>);
+}
+
+/// Sends events to the event loop from any thread.
+pub trait PlatformEventLoopProxyTrait: Clone + Send + Sync + 'static {
+    /// Queues an `Event::User` and wakes the event loop if it's waiting.
+    /// The event should be sent from the event loop's thread during its next iteration.
+    /// Events sent after the event loop has quit are dropped.
+    fn send(&self, event: UserEvent);
 }
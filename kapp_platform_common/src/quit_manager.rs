@@ -0,0 +1,41 @@
+/// This file tracks whether `pump_events` has sent `Event::Quit`.
+/// `Event::Quit` is the last event, so after it's sent later calls to `pump_events`
+/// return without processing any events.
+use std::cell::Cell;
+
+thread_local!(
+    static QUIT_SENT: Cell<bool> = Cell::new(false);
+);
+
+/// Runs one iteration of a platform's `pump_events` with `process`,
+/// which returns true if quit has been called.
+/// Returns true if `Event::Quit` should be sent, which is only the first time quit is seen.
+/// `process` isn't called again after that.
+pub fn pump_events(process: impl FnOnce() -> bool) -> bool {
+    if QUIT_SENT.with(|q| q.get()) {
+        return false;
+    }
+    let quit = process();
+    QUIT_SENT.with(|q| q.set(quit));
+    quit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quit_is_sent_once() {
+        let mut processed = 0;
+        let mut quits = 0;
+        for quit in [false, true, true, true].iter() {
+            if pump_events(|| {
+                processed += 1;
+                *quit
+            }) {
+                quits += 1;
+            }
+        }
+        assert_eq!((processed, quits), (2, 1));
+    }
+}
@@ -0,0 +1,56 @@
+/// This file tracks which windows have text input enabled.
+/// Text input can be enabled for a single window or for every window.
+/// Platforms where text input is global should enable or disable it
+/// as focus moves between windows with 'text_input_enabled'.
+use crate::WindowId;
+use std::cell::RefCell;
+use std::collections::HashSet;
+
+struct TextInputState {
+    all_windows: bool,
+    windows: HashSet<WindowId>,
+}
+
+thread_local!(
+    static TEXT_INPUT: RefCell<TextInputState> = RefCell::new(TextInputState {
+        all_windows: false,
+        windows: HashSet::new(),
+    });
+);
+
+/// Enables text input for a window, or for every window if `window_id` is `None`.
+pub fn start_text_input(window_id: Option<WindowId>) {
+    TEXT_INPUT.with(|t| {
+        let mut state = t.borrow_mut();
+        match window_id {
+            Some(window_id) => {
+                state.windows.insert(window_id);
+            }
+            None => state.all_windows = true,
+        }
+    })
+}
+
+/// Disables text input for a window, or for every window if `window_id` is `None`.
+/// Disabling text input for a single window does not override text input enabled for every window.
+pub fn end_text_input(window_id: Option<WindowId>) {
+    TEXT_INPUT.with(|t| {
+        let mut state = t.borrow_mut();
+        match window_id {
+            Some(window_id) => {
+                state.windows.remove(&window_id);
+            }
+            None => {
+                state.all_windows = false;
+                state.windows.clear();
+            }
+        }
+    })
+}
+
+pub fn text_input_enabled(window_id: WindowId) -> bool {
+    TEXT_INPUT.with(|t| {
+        let state = t.borrow();
+        state.all_windows || state.windows.contains(&window_id)
+    })
+}
@@ -0,0 +1,38 @@
+use std::any::Any;
+use std::sync::{Arc, Mutex};
+
+/// A value sent to the event loop from any thread, received as `Event::User`.
+/// `Event` isn't generic, so the value's type is checked when it's taken.
+///
+/// `Event` is `Clone`, so clones of a `UserEvent` share the value
+/// and only the first `take` receives it.
+#[derive(Clone)]
+pub struct UserEvent(Arc<Mutex<Option<Box<dyn Any + Send>>>>);
+
+impl UserEvent {
+    pub fn new<T: Any + Send>(value: T) -> Self {
+        Self(Arc::new(Mutex::new(Some(Box::new(value)))))
+    }
+
+    /// Returns true if the value is a `T` and hasn't been taken.
+    pub fn is<T: Any>(&self) -> bool {
+        matches!(&*self.0.lock().unwrap(), Some(value) if value.is::<T>())
+    }
+
+    /// Moves the value out if it's a `T`.
+    /// Returns `None` if the value is another type or has already been taken.
+    pub fn take<T: Any>(&self) -> Option<T> {
+        let mut value = self.0.lock().unwrap();
+        if value.as_ref()?.is::<T>() {
+            value.take()?.downcast().ok().map(|value| *value)
+        } else {
+            None
+        }
+    }
+}
+
+impl std::fmt::Debug for UserEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("UserEvent(..)")
+    }
+}
@@ -1,3 +1,22 @@
+use crate::WindowId;
+
+/// The role of a window, which the OS uses to decide how it's decorated,
+/// where it's placed, and how it behaves relative to its parent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowKind {
+    Normal,
+    /// A dialog that blocks input to its parent until it's closed.
+    Dialog,
+    /// A borderless window like a dropdown menu that's positioned relative to its parent.
+    Popup,
+    /// A borderless window that doesn't take focus, positioned relative to its parent.
+    Tooltip,
+    /// A tool palette or inspector that floats above its parent.
+    Utility,
+    /// A borderless window shown while an application starts.
+    Splash,
+}
+
 #[derive(Clone)]
 pub struct WindowParameters {
     pub position: Option<(u32, u32)>,
@@ -5,7 +24,17 @@ pub struct WindowParameters {
     pub minimum_size: Option<(u32, u32)>,
     pub maximum_size: Option<(u32, u32)>,
     pub resizable: bool,
-    /// Only does anything on MacOS
+    /// On MacOS the content extends beneath a transparent titlebar.
+    /// On other platforms the window has no titlebar or borders.
     pub without_titlebar: bool,
+    pub visible: bool,
+    pub always_on_top: bool,
+    /// If the window's background should be transparent where its content has transparent pixels.
+    pub transparent: bool,
+    /// If the window should take focus when it's shown.
+    pub focused: bool,
     pub title: String,
+    /// The window this window is attached to.
+    pub parent: Option<WindowId>,
+    pub kind: WindowKind,
 }
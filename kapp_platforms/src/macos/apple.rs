@@ -215,8 +215,33 @@ pub use objc::{
 #[link(name = "AppKit", kind = "framework")]
 extern "C" {
     pub static NSBackingPropertyOldScaleFactorKey: *const c_void;
+    pub static NSTextInputContextKeyboardSelectionDidChangeNotification: *const c_void;
 }
 
+// Used to find the characters keys produce in the current keyboard layout.
+#[link(name = "Carbon", kind = "framework")]
+extern "C" {
+    pub static kTISPropertyUnicodeKeyLayoutData: CFStringRef;
+    pub fn TISCopyCurrentKeyboardLayoutInputSource() -> TISInputSourceRef;
+    pub fn TISGetInputSourceProperty(source: TISInputSourceRef, key: CFStringRef) -> CFDataRef;
+    pub fn LMGetKbdType() -> u8;
+    pub fn UCKeyTranslate(
+        key_layout: *const c_void, // UCKeyboardLayout
+        virtual_key_code: u16,
+        key_action: u16,
+        modifier_key_state: u32,
+        keyboard_type: u32,
+        key_translate_options: u32,
+        dead_key_state: *mut u32,
+        max_string_length: c_ulong,
+        actual_string_length: *mut c_ulong,
+        unicode_string: *mut u16,
+    ) -> i32;
+}
+
+pub const kUCKeyActionDisplay: u16 = 3;
+pub const kUCKeyTranslateNoDeadKeysMask: u32 = 1;
+
 #[cfg(target_pointer_width = "32")]
 pub type NSInteger = c_int;
 #[cfg(target_pointer_width = "32")]
@@ -250,16 +275,35 @@ pub const NSTerminateNow: NSUInteger = 1;
 pub const NSTerminateCancel: NSUInteger = 0;
 
 pub const NSEventModifierFlagCapsLock: NSUInteger = 1 << 16;
+pub const NSEventModifierFlagShift: NSUInteger = 1 << 17;
+pub const NSEventModifierFlagControl: NSUInteger = 1 << 18;
+pub const NSEventModifierFlagOption: NSUInteger = 1 << 19;
+pub const NSEventModifierFlagCommand: NSUInteger = 1 << 20;
+
+pub const NSEventPhaseBegan: NSUInteger = 0x1 << 0;
+pub const NSEventPhaseChanged: NSUInteger = 0x1 << 2;
+pub const NSEventPhaseEnded: NSUInteger = 0x1 << 3;
+pub const NSEventPhaseCancelled: NSUInteger = 0x1 << 4;
 
 pub const kCFRunLoopBeforeWaiting: CFRunLoopActivity = 1 << 5;
 
+pub const NSWindowStyleMaskBorderless: NSUInteger = 0;
 pub const NSWindowStyleMaskTitled: NSUInteger = 1;
 pub const NSWindowStyleMaskClosable: NSUInteger = 1 << 1;
 pub const NSWindowStyleMaskMiniaturizable: NSUInteger = 1 << 2;
 pub const NSWindowStyleMaskResizable: NSUInteger = 1 << 3;
+pub const NSWindowStyleMaskUtilityWindow: NSUInteger = 1 << 4;
 pub const NSWindowStyleMaskFullSizeContentView: NSUInteger = 1 << 15;
 
+pub const NSWindowCloseButton: NSUInteger = 0;
+pub const NSWindowMiniaturizeButton: NSUInteger = 1;
+pub const NSWindowZoomButton: NSUInteger = 2;
+
 pub const NSBackingStoreBuffered: NSUInteger = 2;
+pub const NSFloatingWindowLevel: NSInteger = 3;
+pub const NSPopUpMenuWindowLevel: NSInteger = 101;
+pub const NSWindowAbove: NSInteger = 1;
+pub const NSInformationalRequest: NSUInteger = 10;
 pub const UTF8_ENCODING: usize = 4;
 
 #[repr(i64)]
@@ -294,13 +338,29 @@ extern "C" {
         context: *mut CFRunLoopSourceContext,
     ) -> CFRunLoopSourceRef;
     pub fn CFRunLoopAddSource(rl: CFRunLoopRef, source: CFRunLoopSourceRef, mode: CFRunLoopMode);
+
+    pub fn CFAbsoluteTimeGetCurrent() -> CFAbsoluteTime;
+    pub fn CFRunLoopTimerCreate(
+        allocator: CFAllocatorRef,
+        fire_date: CFAbsoluteTime,
+        interval: CFTimeInterval,
+        flags: CFOptionFlags,
+        order: CFIndex,
+        callout: CFRunLoopTimerCallBack,
+        context: *const c_void, // CFRunLoopTimerContext
+    ) -> CFRunLoopTimerRef;
+    pub fn CFRunLoopAddTimer(rl: CFRunLoopRef, timer: CFRunLoopTimerRef, mode: CFRunLoopMode);
+    pub fn CFRunLoopTimerSetNextFireDate(timer: CFRunLoopTimerRef, fire_date: CFAbsoluteTime);
     #[allow(dead_code)]
     pub fn CFRunLoopSourceInvalidate(source: CFRunLoopSourceRef);
-    // pub fn CFRunLoopSourceSignal(source: CFRunLoopSourceRef);
+    pub fn CFRunLoopSourceSignal(source: CFRunLoopSourceRef);
+
+    pub fn CFDataGetBytePtr(data: CFDataRef) -> *const u8;
+    pub fn CFRelease(cf: *const c_void);
 }
 
 extern "C" {
-    // pub fn CGWarpMouseCursorPosition(new_cursor_position: CGPoint) -> i32;
+    pub fn CGWarpMouseCursorPosition(new_cursor_position: CGPoint) -> i32;
     pub fn CGAssociateMouseAndMouseCursorPosition(connected: bool) -> i32;
 }
 
@@ -330,14 +390,23 @@ pub type CFRunLoopRef = *mut CFRunLoop;
 pub type CFRunLoopMode = CFStringRef;
 pub enum CFRunLoopObserver {}
 pub type CFRunLoopObserverRef = *mut CFRunLoopObserver;
+pub enum CFRunLoopTimer {}
+pub type CFRunLoopTimerRef = *mut CFRunLoopTimer;
+pub type CFAbsoluteTime = c_double;
+pub type CFTimeInterval = c_double;
 
 pub type CFStringRef = *const Object; // CFString
+pub enum CFData {}
+pub type CFDataRef = *const CFData;
+pub enum TISInputSource {}
+pub type TISInputSourceRef = *mut TISInputSource;
 pub type CFIndex = std::os::raw::c_long;
 pub type CFOptionFlags = std::os::raw::c_ulong;
 pub type CFRunLoopActivity = CFOptionFlags;
 
 pub type CFRunLoopObserverCallBack =
     extern "C" fn(observer: CFRunLoopObserverRef, activity: CFRunLoopActivity, info: *mut c_void);
+pub type CFRunLoopTimerCallBack = extern "C" fn(timer: CFRunLoopTimerRef, info: *mut c_void);
 
 // https://developer.apple.com/documentation/corefoundation/cfrunloopobservercontext?language=objc
 #[repr(C)]
@@ -1,9 +1,11 @@
 use super::apple::*;
+use super::keys_mac::virtual_keycode_to_key;
 use super::window_mac::WindowState;
 use kapp_platform_common::*;
 
 use std::cell::RefCell;
 use std::ffi::c_void;
+use std::sync::Mutex;
 
 thread_local!(pub(crate) static APPLICATION_DATA: RefCell<Box<ApplicationData>> = RefCell::new(Box::new(ApplicationData::new())));
 
@@ -12,8 +14,11 @@ pub(crate) struct ApplicationData {
     ns_application: *mut Object,
     pub modifier_flags: u64,      // Key modifier flags
     pub actually_terminate: bool, // Set when quit is called. Indicates the program should quit.
-    pub text_input_enabled: bool, // Should text input be sent in addition to KeyDown events?
+    pub exit_code: i32,           // Passed to quit and returned from the event loop.
+    pub control_flow: ControlFlow,
+    wait_timer: CFRunLoopTimerRef, // Wakes the run loop for `ControlFlow::Poll` and `WaitUntil`.
     pub mouse_lock: bool,
+    pub pointer_lock_window: Option<WindowId>, // The window whose pointer lock is released when it loses focus.
 }
 
 impl ApplicationData {
@@ -22,8 +27,11 @@ impl ApplicationData {
             ns_application: std::ptr::null_mut(),
             modifier_flags: 0,
             actually_terminate: false,
-            text_input_enabled: false,
+            exit_code: 0,
+            control_flow: ControlFlow::Wait,
+            wait_timer: std::ptr::null_mut(),
             mouse_lock: false,
+            pointer_lock_window: None,
         }
     }
 }
@@ -49,8 +57,17 @@ fn application_delegate_declaration() -> *const objc::runtime::Class {
     decl.register()
 }
 
+// Pushed by `PlatformEventLoopProxy` from any thread and sent when the run loop source fires.
+static USER_EVENTS: Mutex<Vec<UserEvent>> = Mutex::new(Vec::new());
+
 fn create_run_loop_source() -> CFRunLoopSourceRef {
-    extern "C" fn event_loop_proxy_handler(_: *mut std::ffi::c_void) {}
+    extern "C" fn event_loop_proxy_handler(_: *mut std::ffi::c_void) {
+        // Taken before sending so the lock isn't held while the callback runs.
+        let events = std::mem::take(&mut *USER_EVENTS.lock().unwrap());
+        for event in events {
+            event_receiver::send_event(Event::User(event));
+        }
+    }
 
     unsafe {
         let rl = CFRunLoopGetMain();
@@ -120,9 +137,27 @@ extern "C" fn control_flow_end_handler(
             })
         };
 
+        // `terminate:` would exit the process without returning from the event loop,
+        // so the application is stopped instead.
+        // `stop:` only takes effect after an event is processed, so an empty event is posted.
         if let Ok((should_terminate, ns_application)) = data {
             if should_terminate {
-                let () = msg(ns_application, Sels::terminate, (nil,));
+                let () = msg_send![ns_application, stop: nil];
+
+                // NSEventTypeApplicationDefined
+                let event: *mut Object = msg_send![
+                    class!(NSEvent),
+                    otherEventWithType: 15 as NSUInteger
+                    location: NSPoint::new(0., 0.)
+                    modifierFlags: 0 as NSUInteger
+                    timestamp: 0. as f64
+                    windowNumber: 0 as NSInteger
+                    context: nil
+                    subtype: 0 as i16
+                    data1: 0 as NSInteger
+                    data2: 0 as NSInteger
+                ];
+                let () = msg_send![ns_application, postEvent: event atStart: YES];
             }
         }
     }
@@ -134,6 +169,44 @@ extern "C" fn control_flow_end_handler(
             CFRunLoopWakeUp(rl);
         }
     }
+
+    // Schedule the next wake up requested by the control flow.
+    // The timer is rescheduled here every time the run loop is about to wait.
+    let data = APPLICATION_DATA.try_with(|d| {
+        let d = d.borrow();
+        (d.control_flow, d.wait_timer)
+    });
+    if let Ok((control_flow, wait_timer)) = data {
+        unsafe {
+            let fire_date = match control_flow.wait_duration(None) {
+                Some(wait) => CFAbsoluteTimeGetCurrent() + wait.as_secs_f64(),
+                None => f64::MAX,
+            };
+            CFRunLoopTimerSetNextFireDate(wait_timer, fire_date);
+        }
+    }
+}
+
+// The timer only needs to wake the run loop so that it runs another iteration.
+extern "C" fn wait_timer_handler(_: CFRunLoopTimerRef, _: *mut std::ffi::c_void) {}
+
+#[derive(Clone)]
+pub struct PlatformEventLoopProxy {
+    run_loop_custom_event_source: CFRunLoopSourceRef,
+}
+
+// CFRunLoopSourceSignal and CFRunLoopWakeUp may be called from any thread.
+unsafe impl Send for PlatformEventLoopProxy {}
+unsafe impl Sync for PlatformEventLoopProxy {}
+
+impl PlatformEventLoopProxyTrait for PlatformEventLoopProxy {
+    fn send(&self, event: UserEvent) {
+        USER_EVENTS.lock().unwrap().push(event);
+        unsafe {
+            CFRunLoopSourceSignal(self.run_loop_custom_event_source);
+            CFRunLoopWakeUp(CFRunLoopGetMain());
+        }
+    }
 }
 
 pub struct PlatformEventLoop {
@@ -141,31 +214,51 @@ pub struct PlatformEventLoop {
 }
 
 impl PlatformEventLoopTrait for PlatformEventLoop {
-    fn run(&self, callback: Box<dyn FnMut(Event)>) {
+    fn run(&self, callback: Box<dyn FnMut(Event)>) -> i32 {
         event_receiver::set_callback(callback);
 
+        // Returns once the application is stopped by a call to quit.
         unsafe {
             let () = msg(self.ns_application, Sels::run, ());
         }
+
+        event_receiver::send_event(Event::Quit);
+
+        // Dropping the callback drops the user's `Window`s, which closes them.
+        event_receiver::clear_callback();
+
+        APPLICATION_DATA.with(|d| d.borrow().exit_code)
     }
 }
 
+// `PlatformEventLoopPumpTrait` isn't implemented because events and draws are driven by
+// observers on NSApplication's run loop, which would need to be reworked to return control
+// between iterations.
+
 pub struct PlatformApplication {
     // application_data: Rc<RefCell<ApplicationData>>,
     window_class: *const objc::runtime::Class,
     view_class: *const objc::runtime::Class,
     ns_application: *mut Object,
-    _run_loop_custom_event_source: CFRunLoopSourceRef,
+    run_loop_custom_event_source: CFRunLoopSourceRef,
 }
 
 impl PlatformApplicationTrait for PlatformApplication {
     type EventLoop = PlatformEventLoop;
+    type EventLoopProxy = PlatformEventLoopProxy;
 
-    fn new() -> Self {
+    fn new(app_info: &AppInfo) -> Self {
         unsafe {
             // Requests and loads the relevant Objc classes.
             initialize_classes();
 
+            // The id is determined by the application's bundle, but the process name can be changed.
+            if !app_info.name.is_empty() {
+                let process_info: *mut Object = msg_send![class!(NSProcessInfo), processInfo];
+                let name = NSString::new(&app_info.name);
+                let () = msg_send![process_info, setProcessName: name.raw];
+            }
+
             // https://developer.apple.com/documentation/appkit/nsapplication
             // Retrieve the global 'sharedApplication'
             let ns_application: *mut Object = msg(NSApplicationClass, Sels::sharedApplication, ());
@@ -196,6 +289,15 @@ impl PlatformApplicationTrait for PlatformApplication {
                 (ns_application_delegate,),
             );
 
+            // The delegate also sends `KeyboardLayoutChanged` when the input source changes.
+            let notification_center: *mut Object =
+                msg_send![class!(NSNotificationCenter), defaultCenter];
+            let () = msg_send![notification_center,
+                addObserver: ns_application_delegate
+                selector: sel!(keyboardLayoutChanged:)
+                name: NSTextInputContextKeyboardSelectionDidChangeNotification
+                object: nil];
+
             // Create an observer that runs at the end of the event loop to
             // produce `Draw` and `EventsCleared` events.
             let run_loop_custom_event_source = self::create_run_loop_source();
@@ -209,16 +311,30 @@ impl PlatformApplicationTrait for PlatformApplication {
             );
             CFRunLoopAddObserver(CFRunLoopGetMain(), observer, kCFRunLoopCommonModes);
 
+            // A repeating timer that doesn't fire until the observer schedules it.
+            let wait_timer = CFRunLoopTimerCreate(
+                std::ptr::null_mut(),
+                f64::MAX,
+                0.000_000_1,
+                0,
+                0,
+                wait_timer_handler,
+                std::ptr::null(),
+            );
+            CFRunLoopAddTimer(CFRunLoopGetMain(), wait_timer, kCFRunLoopCommonModes);
+
             // Store the application in a thread local.
             APPLICATION_DATA.with(|d| {
-                d.borrow_mut().ns_application = ns_application;
+                let mut d = d.borrow_mut();
+                d.ns_application = ns_application;
+                d.wait_timer = wait_timer;
             });
 
             Self {
                 window_class: window_delegate_declaration(),
                 view_class: view_delegate_declaration(),
                 ns_application,
-                _run_loop_custom_event_source: run_loop_custom_event_source,
+                run_loop_custom_event_source,
             }
         }
     }
@@ -229,6 +345,12 @@ impl PlatformApplicationTrait for PlatformApplication {
         }
     }
 
+    fn event_loop_proxy(&mut self) -> Self::EventLoopProxy {
+        PlatformEventLoopProxy {
+            run_loop_custom_event_source: self.run_loop_custom_event_source,
+        }
+    }
+
     fn set_window_position(&mut self, window_id: WindowId, x: u32, y: u32) {
         unsafe {
             let screen: *const Object = msg(window_id.raw() as *mut Object, Sels::screen, ());
@@ -292,8 +414,158 @@ impl PlatformApplicationTrait for PlatformApplication {
         todo!()
     }
 
+    fn show_window(&mut self, window_id: WindowId) {
+        unsafe {
+            let () = msg_send![window_id.raw() as *mut Object, orderFront: nil];
+        }
+    }
+
+    fn hide_window(&mut self, window_id: WindowId) {
+        unsafe {
+            let () = msg_send![window_id.raw() as *mut Object, orderOut: nil];
+        }
+    }
+
+    fn focus_window(&mut self, window_id: WindowId) {
+        unsafe {
+            let () = msg_send![self.ns_application, activateIgnoringOtherApps: YES];
+            let () = msg_send![window_id.raw() as *mut Object, makeKeyAndOrderFront: nil];
+        }
+    }
+
+    // Bounces the dock icon once.
+    fn request_window_attention(&mut self, _window_id: WindowId) {
+        unsafe {
+            let _: NSInteger =
+                msg_send![self.ns_application, requestUserAttention: NSInformationalRequest];
+        }
+    }
+
+    fn is_window_visible(&mut self, window_id: WindowId) -> bool {
+        unsafe {
+            let visible: BOOL = msg_send![window_id.raw() as *mut Object, isVisible];
+            visible == YES
+        }
+    }
+
+    fn is_window_focused(&mut self, window_id: WindowId) -> bool {
+        unsafe {
+            let focused: BOOL = msg_send![window_id.raw() as *mut Object, isKeyWindow];
+            focused == YES
+        }
+    }
+
+    fn is_window_minimized(&mut self, window_id: WindowId) -> bool {
+        unsafe {
+            let minimized: BOOL = msg_send![window_id.raw() as *mut Object, isMiniaturized];
+            minimized == YES
+        }
+    }
+
+    fn is_window_maximized(&mut self, window_id: WindowId) -> bool {
+        unsafe {
+            let zoomed: BOOL = msg_send![window_id.raw() as *mut Object, isZoomed];
+            zoomed == YES
+        }
+    }
+
+    fn set_window_min_size(&mut self, window_id: WindowId, size: Option<(u32, u32)>) {
+        let backing_scale = get_backing_scale(window_id);
+        let (width, height) = size.unwrap_or((0, 0));
+        unsafe {
+            let () = msg_send![window_id.raw() as *mut Object, setContentMinSize: NSSize::new(width as f64 / backing_scale, height as f64 / backing_scale)];
+        }
+    }
+
+    fn set_window_max_size(&mut self, window_id: WindowId, size: Option<(u32, u32)>) {
+        let backing_scale = get_backing_scale(window_id);
+        // The default maximum size is FLT_MAX, which removes the limit.
+        let (width, height) = size.map_or((f32::MAX as f64, f32::MAX as f64), |(width, height)| {
+            (width as f64 / backing_scale, height as f64 / backing_scale)
+        });
+        unsafe {
+            let () = msg_send![window_id.raw() as *mut Object, setContentMaxSize: NSSize::new(width, height)];
+        }
+    }
+
+    fn set_window_resizable(&mut self, window_id: WindowId, resizable: bool) {
+        set_style_mask(window_id, NSWindowStyleMaskResizable, resizable);
+    }
+
+    // Windows without the titled style can't become the key window,
+    // so instead the titlebar is made transparent and its buttons are hidden.
+    fn set_window_decorations(&mut self, window_id: WindowId, decorations: bool) {
+        set_style_mask(window_id, NSWindowStyleMaskFullSizeContentView, !decorations);
+        let ns_window = window_id.raw() as *mut Object;
+        let hidden = if decorations { NO } else { YES };
+        unsafe {
+            let () = msg_send![ns_window, setTitlebarAppearsTransparent: hidden];
+            let () = msg_send![ns_window, setTitleVisibility: hidden as NSInteger];
+            for button in &[NSWindowCloseButton, NSWindowMiniaturizeButton, NSWindowZoomButton] {
+                let button: *mut Object = msg_send![ns_window, standardWindowButton: *button];
+                let () = msg_send![button, setHidden: hidden];
+            }
+        }
+    }
+
+    // Setting content resize increments of 1 removes the aspect ratio and vice versa.
+    fn set_window_aspect_ratio(&mut self, window_id: WindowId, ratio: Option<(u32, u32)>) {
+        unsafe {
+            if let Some((width, height)) = ratio {
+                let () = msg_send![window_id.raw() as *mut Object, setContentAspectRatio: NSSize::new(width as f64, height as f64)];
+            } else {
+                let () = msg_send![window_id.raw() as *mut Object, setContentResizeIncrements: NSSize::new(1.0, 1.0)];
+            }
+        }
+    }
+
+    fn set_window_resize_increments(
+        &mut self,
+        window_id: WindowId,
+        increments: Option<(u32, u32)>,
+    ) {
+        let backing_scale = get_backing_scale(window_id);
+        let (width, height) = increments.map_or((1.0, 1.0), |(width, height)| {
+            (width as f64 / backing_scale, height as f64 / backing_scale)
+        });
+        unsafe {
+            let () = msg_send![window_id.raw() as *mut Object, setContentResizeIncrements: NSSize::new(width, height)];
+        }
+    }
+
+    fn set_window_opacity(&mut self, window_id: WindowId, opacity: f64) {
+        unsafe {
+            let () = msg_send![window_id.raw() as *mut Object, setAlphaValue: opacity as CGFloat];
+        }
+    }
+
+    fn set_window_hit_test(&mut self, window_id: WindowId, hit_test: Option<HitTest>) {
+        unsafe {
+            let ns_view: &Object = msg(window_id.raw() as *mut Object, Sels::contentView, ());
+            let window_state: *mut c_void = *ns_view.get_ivar("kappState");
+            let window_state = window_state as *mut WindowState;
+            (*window_state).hit_test = hit_test;
+        }
+    }
+
+    // This must be called while handling a mouse down event.
+    fn begin_window_drag_move(&mut self, window_id: WindowId) {
+        unsafe {
+            let event: *mut Object = msg_send![self.ns_application, currentEvent];
+            let () = msg_send![window_id.raw() as *mut Object, performWindowDragWithEvent: event];
+        }
+    }
+
+    // MacOS has no public API to start a resize.
+    fn begin_window_drag_resize(&mut self, _window_id: WindowId, _edge: ResizeEdge) {}
+
     fn close_window(&mut self, window_id: WindowId) {
         unsafe {
+            // Sheets must be ended before they're closed to unblock their parent.
+            let sheet_parent: *mut Object = msg_send![window_id.raw() as *mut Object, sheetParent];
+            if sheet_parent != nil {
+                let () = msg_send![sheet_parent, endSheet: window_id.raw() as *mut Object];
+            }
             let () = msg(window_id.raw() as *mut Object, Sels::close, ());
         }
     }
@@ -340,6 +612,59 @@ impl PlatformApplicationTrait for PlatformApplication {
         });
     }
 
+    fn set_pointer_position(&mut self, window_id: WindowId, x: f64, y: f64) {
+        unsafe {
+            let window = window_id.raw() as *mut Object;
+            let backing_scale = get_backing_scale(window_id);
+            let view: *mut Object = msg(window, Sels::contentView, ());
+            let view_frame: CGRect = msg(view, Sels::frame, ());
+
+            // Flip y because Mac window coordinates start in the lower left corner.
+            let window_rect = CGRect::new(
+                CGPoint::new(x / backing_scale, view_frame.size.height - y / backing_scale),
+                CGSize::new(0., 0.),
+            );
+            let screen_rect: CGRect = msg_send![window, convertRectToScreen: window_rect];
+
+            // CoreGraphics measures from the upper left corner of the main screen
+            // instead of the lower left corner.
+            let screens: *mut Object = msg_send![class!(NSScreen), screens];
+            let main_screen: *mut Object = msg_send![screens, objectAtIndex: 0 as NSUInteger];
+            let main_screen_frame: CGRect = msg(main_screen, Sels::frame, ());
+            CGWarpMouseCursorPosition(CGPoint::new(
+                screen_rect.origin.x,
+                main_screen_frame.size.height - screen_rect.origin.y,
+            ));
+
+            // Warping the cursor briefly freezes it unless the mouse is associated again.
+            let mouse_lock = APPLICATION_DATA.with(|d| d.borrow().mouse_lock);
+            CGAssociateMouseAndMouseCursorPosition(!mouse_lock);
+        }
+    }
+
+    // MacOS has no way to confine the cursor to a window.
+    fn confine_pointer(&mut self, _window_id: WindowId, _confine: bool) {}
+
+    fn lock_pointer(&mut self, window_id: WindowId) {
+        self.lock_mouse_position();
+        APPLICATION_DATA.with(|d| {
+            d.borrow_mut().pointer_lock_window = Some(window_id);
+        });
+    }
+
+    fn unlock_pointer(&mut self, window_id: WindowId) {
+        let pointer_lock_window = APPLICATION_DATA.with(|d| d.borrow().pointer_lock_window);
+        if pointer_lock_window == Some(window_id) {
+            self.unlock_mouse_position();
+            APPLICATION_DATA.with(|d| {
+                d.borrow_mut().pointer_lock_window = None;
+            });
+        }
+    }
+
+    // Unaccelerated motion is only available through IOKit, which is not used here.
+    fn set_raw_mouse_motion(&mut self, _enabled: bool) {}
+
     // https://developer.apple.com/documentation/appkit/nscursor?language=objc
     fn set_cursor(&mut self, cursor: Cursor) {
         let ns_cursor = unsafe { &*NSCursorClass };
@@ -379,16 +704,114 @@ impl PlatformApplicationTrait for PlatformApplication {
         result.unwrap()
     }
 
-    fn quit(&self) {
+    fn quit(&self, exit_code: i32) {
         // This thread local cannot be accessed if the program is already terminating.
         let _ = APPLICATION_DATA.try_with(|d| {
-            d.borrow_mut().actually_terminate = true;
+            let mut d = d.borrow_mut();
+            d.actually_terminate = true;
+            d.exit_code = exit_code;
         });
 
         // Actual termination is postponed until the end of the event loop
         // to give the user program a chance to process events.
     }
 
+    // Takes effect when the run loop is about to wait, in `control_flow_end_handler`.
+    fn set_control_flow(&mut self, control_flow: ControlFlow) {
+        APPLICATION_DATA.with(|d| d.borrow_mut().control_flow = control_flow);
+    }
+
+    // https://developer.apple.com/documentation/appkit/nsalert?language=objc
+    fn message_box(&mut self, message_box: &MessageBox) -> Option<MessageBoxButton> {
+        unsafe {
+            let alert: *mut Object = msg_send![class!(NSAlert), new];
+
+            // NSAlertStyleWarning = 0, NSAlertStyleInformational = 1, NSAlertStyleCritical = 2
+            let style: NSUInteger = match message_box.kind {
+                MessageBoxKind::Info => 1,
+                MessageBoxKind::Warning => 0,
+                MessageBoxKind::Error => 2,
+            };
+            let () = msg_send![alert, setAlertStyle: style];
+
+            let title = NSString::new(&message_box.title);
+            let text = NSString::new(&message_box.text);
+            let () = msg_send![alert, setMessageText: title.raw];
+            let () = msg_send![alert, setInformativeText: text.raw];
+
+            let buttons = message_box.buttons.buttons();
+            let dismiss_button = message_box.buttons.dismiss_button();
+            for (i, button) in buttons.iter().enumerate() {
+                let label = NSString::new(button.label());
+                let ns_button: *mut Object = msg_send![alert, addButtonWithTitle: label.raw];
+
+                // NSAlert only assigns the escape key to buttons titled "Cancel".
+                if i != 0 && *button == dismiss_button {
+                    let escape = NSString::new("\u{1b}");
+                    let () = msg_send![ns_button, setKeyEquivalent: escape.raw];
+                }
+            }
+
+            // The application may not be active yet if this is called before the event loop.
+            let () = msg_send![self.ns_application, activateIgnoringOtherApps: YES];
+
+            // `runModal` runs its own event loop. Events for kapp's windows that are produced
+            // while it runs are queued if the user callback is running.
+            // NSAlertFirstButtonReturn is 1000 and each subsequent button adds one.
+            let response: NSInteger = msg_send![alert, runModal];
+            let () = msg_send![alert, release];
+
+            Some(
+                buttons
+                    .get((response - 1000) as usize)
+                    .copied()
+                    .unwrap_or(dismiss_button),
+            )
+        }
+    }
+
+    // https://developer.apple.com/documentation/appkit/nsopenpanel?language=objc
+    // The panels are run modally, and the result is sent when they close.
+    fn open_file_dialog(&mut self, id: FileDialogId, options: &FileDialogOptions) {
+        let paths = unsafe {
+            let panel: *mut Object = msg_send![class!(NSOpenPanel), openPanel];
+            let () = msg_send![panel, setCanChooseFiles: !options.directory as BOOL];
+            let () = msg_send![panel, setCanChooseDirectories: options.directory as BOOL];
+            let () = msg_send![panel, setAllowsMultipleSelection: options.multiple as BOOL];
+
+            if run_file_panel(self.ns_application, panel, options) {
+                let urls: *mut Object = msg_send![panel, URLs];
+                let count: NSUInteger = msg_send![urls, count];
+                (0..count)
+                    .map(|i| {
+                        let url: *mut Object = msg_send![urls, objectAtIndex: i];
+                        path_from_url(url)
+                    })
+                    .collect()
+            } else {
+                Vec::new()
+            }
+        };
+        event_receiver::send_event(Event::FileDialogResult { id, paths });
+    }
+
+    // https://developer.apple.com/documentation/appkit/nssavepanel?language=objc
+    fn save_file_dialog(&mut self, id: FileDialogId, options: &FileDialogOptions) {
+        let paths = unsafe {
+            let panel: *mut Object = msg_send![class!(NSSavePanel), savePanel];
+            let name = NSString::new(&options.current_name);
+            let () = msg_send![panel, setNameFieldStringValue: name.raw];
+
+            if run_file_panel(self.ns_application, panel, options) {
+                let url: *mut Object = msg_send![panel, URL];
+                vec![path_from_url(url)]
+            } else {
+                Vec::new()
+            }
+        };
+        event_receiver::send_event(Event::FileDialogResult { id, paths });
+    }
+
     fn raw_window_handle(&self, window_id: WindowId) -> RawWindowHandle {
         unsafe {
             let ns_window = window_id.raw();
@@ -401,16 +824,65 @@ impl PlatformApplicationTrait for PlatformApplication {
         }
     }
 
-    fn start_text_input(&mut self) {
-        APPLICATION_DATA.with(|d| {
-            d.borrow_mut().text_input_enabled = true;
-        });
+    // Key events are only interpreted as text for windows with text input enabled.
+    fn start_text_input(&mut self, window_id: Option<WindowId>) {
+        text_input_manager::start_text_input(window_id);
     }
 
-    fn end_text_input(&mut self) {
-        APPLICATION_DATA.with(|d| {
-            d.borrow_mut().text_input_enabled = false;
-        });
+    fn end_text_input(&mut self, window_id: Option<WindowId>) {
+        text_input_manager::end_text_input(window_id);
+    }
+
+    // The label is the character the key types without modifiers in the current keyboard layout.
+    fn key_label(&self, physical_key: Key) -> Option<String> {
+        if physical_key == Key::Unknown {
+            return None;
+        }
+        let key_code =
+            (0..=0x7F).find(|key_code| virtual_keycode_to_key(*key_code) == physical_key)?;
+
+        let label = unsafe {
+            let source = TISCopyCurrentKeyboardLayoutInputSource();
+            if source.is_null() {
+                return None;
+            }
+            let layout_data = TISGetInputSourceProperty(source, kTISPropertyUnicodeKeyLayoutData);
+            let mut buffer = [0u16; 8];
+            let mut length = 0;
+            let status = if layout_data.is_null() {
+                -1
+            } else {
+                let mut dead_key_state = 0;
+                UCKeyTranslate(
+                    CFDataGetBytePtr(layout_data).cast(),
+                    key_code,
+                    kUCKeyActionDisplay,
+                    0,
+                    LMGetKbdType() as u32,
+                    kUCKeyTranslateNoDeadKeysMask,
+                    &mut dead_key_state,
+                    buffer.len() as c_ulong,
+                    &mut length,
+                    buffer.as_mut_ptr(),
+                )
+            };
+            CFRelease(source as *const c_void);
+            if status != 0 {
+                return None;
+            }
+            String::from_utf16(&buffer[..length as usize]).ok()?
+        };
+
+        // Keys like Space, Return, and the arrow keys type whitespace, control characters,
+        // or characters from the private use area that aren't useful labels.
+        let visible = |c: char| {
+            !c.is_whitespace() && !c.is_control() && !('\u{F700}'..='\u{F8FF}').contains(&c)
+        };
+        if !label.is_empty() && label.chars().all(visible) {
+            Some(label.to_uppercase())
+        } else {
+            None
+        }
     }
 
     fn set_text_input_rectangle(
@@ -430,13 +902,67 @@ impl PlatformApplicationTrait for PlatformApplication {
     }
 }
 
+/// Applies the options shared by open and save panels, then runs the panel modally.
+/// Returns true if the panel was closed with its confirm button.
+unsafe fn run_file_panel(
+    ns_application: *mut Object,
+    panel: *mut Object,
+    options: &FileDialogOptions,
+) -> bool {
+    // Open panels don't show their title on newer MacOS versions, so it's also the message.
+    let title = NSString::new(&options.title);
+    let () = msg_send![panel, setTitle: title.raw];
+    let () = msg_send![panel, setMessage: title.raw];
+
+    if let Some(current_folder) = &options.current_folder {
+        let path = NSString::new(&current_folder.to_string_lossy());
+        let url: *mut Object = msg_send![class!(NSURL), fileURLWithPath: path.raw];
+        let () = msg_send![panel, setDirectoryURL: url];
+    }
+
+    // Panels can't switch between filters, so the extensions of every filter are allowed.
+    if !options.filters.is_empty() {
+        let file_types: *mut Object = msg_send![class!(NSMutableArray), array];
+        for extension in options.filters.iter().flat_map(|f| f.extensions.iter()) {
+            let extension = NSString::new(extension);
+            let () = msg_send![file_types, addObject: extension.raw];
+        }
+        let () = msg_send![panel, setAllowedFileTypes: file_types];
+    }
+
+    // The application may not be active yet if this is called before the event loop.
+    let () = msg_send![ns_application, activateIgnoringOtherApps: YES];
+
+    // NSModalResponseOK
+    let response: NSInteger = msg_send![panel, runModal];
+    response == 1
+}
+
+unsafe fn path_from_url(url: *mut Object) -> std::path::PathBuf {
+    use std::os::unix::ffi::OsStrExt;
+    let path: *mut Object = msg_send![url, path];
+    let utf8_string: *const std::os::raw::c_char = msg_send![path, UTF8String];
+    let bytes = std::ffi::CStr::from_ptr(utf8_string).to_bytes();
+    std::path::PathBuf::from(std::ffi::OsStr::from_bytes(bytes))
+}
+
 pub fn get_backing_scale(window_id: WindowId) -> CGFloat {
     unsafe { msg(window_id.raw() as *mut Object, Sels::backingScaleFactor, ()) }
 }
 
+/// Adds or removes flags from a window's style mask.
+fn set_style_mask(window_id: WindowId, flags: NSUInteger, enabled: bool) {
+    unsafe {
+        let ns_window = window_id.raw() as *mut Object;
+        let style: NSUInteger = msg_send![ns_window, styleMask];
+        let style = if enabled { style | flags } else { style & !flags };
+        let () = msg_send![ns_window, setStyleMask: style];
+    }
+}
+
 // When the application is dropped, quit the program.
 impl Drop for PlatformApplication {
     fn drop(&mut self) {
-        self.quit();
+        self.quit(0);
     }
 }
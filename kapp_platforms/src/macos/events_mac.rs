@@ -1,7 +1,10 @@
 use super::apple::*;
 use super::application_mac::APPLICATION_DATA;
 use super::window_mac::WindowState;
-use kapp_platform_common::{Event, Key, PointerButton, PointerSource, WindowId};
+use kapp_platform_common::{
+    text_input_manager, CompositionSegment, Event, Gesture, HitTestResult, Key, Modifiers,
+    PointerButton, PointerSource, ScrollDelta, ScrollPhase, WindowId,
+};
 use objc::runtime::Protocol;
 use std::ffi::c_void;
 
@@ -90,13 +93,30 @@ extern "C" fn window_did_become_key(_this: &Object, _sel: Sel, ns_notification:
     self::submit_event(Event::WindowGainedFocus {
         window_id: WindowId::new(window),
     });
+
+    // Modifiers may have changed while another application had focus.
+    let modifier_flags: NSUInteger = unsafe { msg_send![class!(NSEvent), modifierFlags] };
+    update_modifier_flags(modifier_flags);
 }
 
 extern "C" fn window_did_resign_key(_this: &Object, _sel: Sel, ns_notification: *mut Object) {
     let window: *mut c_void = unsafe { msg(ns_notification, Sels::object, ()) };
-    self::submit_event(Event::WindowLostFocus {
-        window_id: WindowId::new(window),
-    });
+    let window_id = WindowId::new(window);
+    self::submit_event(Event::WindowLostFocus { window_id });
+
+    // Release the pointer so it can be used with other windows.
+    let pointer_lock_window = APPLICATION_DATA.with(|d| d.borrow().pointer_lock_window);
+    if pointer_lock_window == Some(window_id) {
+        unsafe {
+            CGAssociateMouseAndMouseCursorPosition(true);
+        }
+        APPLICATION_DATA.with(|d| {
+            let mut d = d.borrow_mut();
+            d.mouse_lock = false;
+            d.pointer_lock_window = None;
+        });
+        self::submit_event(Event::PointerLockLost { window_id });
+    }
 }
 
 extern "C" fn window_should_close(_this: &Object, _sel: Sel, sender: *mut Object) -> BOOL {
@@ -218,8 +238,16 @@ extern "C" fn application_should_terminate(
     }
 }
 
+// The event loop is stopped instead of terminated when quit is called, so this is only
+// reached if something else terminates the application. The process exits afterwards.
 extern "C" fn application_will_terminate(_this: &Object, _sel: Sel, _application: *mut Object) {
     self::submit_event(Event::Quit {});
+    kapp_platform_common::event_receiver::clear_callback();
+}
+
+// Observed from NSNotificationCenter, so it's called when the keyboard input source changes.
+extern "C" fn keyboard_layout_changed(_this: &Object, _sel: Sel, _notification: *mut Object) {
+    self::submit_event(Event::KeyboardLayoutChanged);
 }
 
 pub fn add_application_events_to_decl(decl: &mut ClassDecl) {
@@ -237,6 +265,10 @@ pub fn add_application_events_to_decl(decl: &mut ClassDecl) {
             Sel::from_ptr(Sels::applicationWillTerminate),
             application_will_terminate as extern "C" fn(&Object, Sel, *mut Object),
         );
+        decl.add_method(
+            sel!(keyboardLayoutChanged:),
+            keyboard_layout_changed as extern "C" fn(&Object, Sel, *mut Object),
+        );
     }
 }
 // ------------------------ End Application Events --------------------------
@@ -249,25 +281,47 @@ extern "C" fn draw_rect(this: &Object, _sel: Sel, _rect: CGRect) {
 
 extern "C" fn key_down(this: &Object, _sel: Sel, event: *mut Object) {
     unsafe {
-        let key_code = msg(event, Sels::keyCode, ());
+        let key_code: u16 = msg(event, Sels::keyCode, ());
         let repeat: bool = msg(event, Sels::isARepeat, ());
-        let key = super::keys_mac::virtual_keycode_to_key(key_code);
+        let (key, physical_key) = get_keys(event, key_code);
+
+        // Function keys produce characters in a private use area, which are not text.
+        let text = ns_string_to_string(msg_send![event, characters]);
+        let text = if text.is_empty()
+            || text
+                .chars()
+                .any(|c| c.is_control() || ('\u{F700}'..='\u{F8FF}').contains(&c))
+        {
+            None
+        } else {
+            Some(text)
+        };
+
         let kapp_event = if repeat {
             Event::KeyRepeat {
                 key,
+                physical_key,
+                scancode: key_code as u32,
+                text,
+                window_id: get_window_id(event),
+                modifiers: get_modifiers(event),
                 timestamp: get_timestamp(event),
             }
         } else {
             Event::KeyDown {
                 key,
+                physical_key,
+                scancode: key_code as u32,
+                text,
+                window_id: get_window_id(event),
+                modifiers: get_modifiers(event),
                 timestamp: get_timestamp(event),
             }
         };
         self::submit_event(kapp_event);
 
         // If text input is enabled forward the key event so that the OS can produce other events with it.
-        let text_input_enabled = APPLICATION_DATA.with(|d| d.borrow().text_input_enabled);
-        if text_input_enabled {
+        if text_input_manager::text_input_enabled(get_window_id(event)) {
             let array: *mut Object = msg_send![class!(NSArray), arrayWithObject: event];
             let () = msg_send![this, interpretKeyEvents: array];
         }
@@ -276,9 +330,14 @@ extern "C" fn key_down(this: &Object, _sel: Sel, event: *mut Object) {
 
 extern "C" fn key_up(_this: &Object, _sel: Sel, event: *mut Object) {
     unsafe {
-        let key_code = msg(event, Sels::keyCode, ());
+        let key_code: u16 = msg(event, Sels::keyCode, ());
+        let (key, physical_key) = get_keys(event, key_code);
         self::submit_event(Event::KeyUp {
-            key: super::keys_mac::virtual_keycode_to_key(key_code),
+            key,
+            physical_key,
+            scancode: key_code as u32,
+            window_id: get_window_id(event),
+            modifiers: get_modifiers(event),
             timestamp: get_timestamp(event),
         });
     }
@@ -318,14 +377,22 @@ extern "C" fn flags_changed(_this: &Object, _sel: Sel, event: *mut Object) {
     let modifier_flags_old = APPLICATION_DATA.with(|d| d.borrow().modifier_flags);
 
     let modifier_flags_new: NSUInteger = unsafe { msg(event, Sels::modifierFlags, ()) };
+    let key_code: u16 = unsafe { msg(event, Sels::keyCode, ()) };
 
     let flag_state_old = get_modifier_state(modifier_flags_old);
     let flag_state_new = get_modifier_state(modifier_flags_new);
+    let modifiers = modifiers_from_flags(modifier_flags_new);
+    update_modifier_flags(modifier_flags_new);
 
     for i in 0..8 {
         if !flag_state_old[i] && flag_state_new[i] {
             self::submit_event(Event::KeyDown {
                 key: KEYS[i],
+                physical_key: KEYS[i],
+                scancode: key_code as u32,
+                text: None,
+                window_id: get_window_id(event),
+                modifiers,
                 timestamp: get_timestamp(event),
             })
         }
@@ -333,14 +400,14 @@ extern "C" fn flags_changed(_this: &Object, _sel: Sel, event: *mut Object) {
         if flag_state_old[i] && !flag_state_new[i] {
             self::submit_event(Event::KeyUp {
                 key: KEYS[i],
+                physical_key: KEYS[i],
+                scancode: key_code as u32,
+                window_id: get_window_id(event),
+                modifiers,
                 timestamp: get_timestamp(event),
             })
         }
     }
-
-    APPLICATION_DATA.with(|d| {
-        d.borrow_mut().modifier_flags = modifier_flags_new;
-    });
 }
 
 extern "C" fn mouse_moved(this: &Object, _sel: Sel, event: *mut Object) {
@@ -349,11 +416,29 @@ extern "C" fn mouse_moved(this: &Object, _sel: Sel, event: *mut Object) {
 
 extern "C" fn mouse_down(this: &Object, _sel: Sel, event: *mut Object) {
     let (x, y) = get_mouse_position(this, event);
+
+    // MacOS has no way to start a resize, so only `Caption` regions are handled.
+    let hit_test_result = unsafe {
+        let window_state: *const c_void = *this.get_ivar("kappState");
+        let window_state = window_state as *const WindowState;
+        (*window_state)
+            .hit_test
+            .as_ref()
+            .map(|hit_test| hit_test(x, y))
+    };
+    if hit_test_result == Some(HitTestResult::Caption) {
+        let window: *mut Object = unsafe { msg(event, Sels::window, ()) };
+        let () = unsafe { msg_send![window, performWindowDragWithEvent: event] };
+        return;
+    }
     self::submit_event(Event::PointerDown {
         x,
         y,
         button: PointerButton::Primary,
         source: PointerSource::Mouse,
+        pointer_id: 0,
+        window_id: get_window_id(event),
+        modifiers: get_modifiers(event),
         timestamp: get_timestamp(event),
     });
     let click_count: c_int = unsafe { msg(event, Sels::clickCount, ()) };
@@ -362,6 +447,7 @@ extern "C" fn mouse_down(this: &Object, _sel: Sel, event: *mut Object) {
             x,
             y,
             button: PointerButton::Primary,
+            window_id: get_window_id(event),
             timestamp: get_timestamp(event),
         });
     }
@@ -374,6 +460,9 @@ extern "C" fn mouse_up(this: &Object, _sel: Sel, event: *mut Object) {
         y,
         button: PointerButton::Primary,
         source: PointerSource::Mouse,
+        pointer_id: 0,
+        window_id: get_window_id(event),
+        modifiers: get_modifiers(event),
         timestamp: get_timestamp(event),
     });
     let click_count: c_int = unsafe { msg(event, Sels::clickCount, ()) };
@@ -382,12 +471,14 @@ extern "C" fn mouse_up(this: &Object, _sel: Sel, event: *mut Object) {
             x,
             y,
             button: PointerButton::Primary,
+            window_id: get_window_id(event),
             timestamp: get_timestamp(event),
         });
         self::submit_event(Event::DoubleClick {
             x,
             y,
             button: PointerButton::Primary,
+            window_id: get_window_id(event),
             timestamp: get_timestamp(event),
         });
     }
@@ -401,6 +492,9 @@ extern "C" fn right_mouse_down(this: &Object, _sel: Sel, event: *mut Object) {
         y,
         button: PointerButton::Secondary,
         source: PointerSource::Mouse,
+        pointer_id: 0,
+        window_id: get_window_id(event),
+        modifiers: get_modifiers(event),
         timestamp: get_timestamp(event),
     });
     let click_count: c_int = unsafe { msg(event, Sels::clickCount, ()) };
@@ -409,6 +503,7 @@ extern "C" fn right_mouse_down(this: &Object, _sel: Sel, event: *mut Object) {
             x,
             y,
             button: PointerButton::Secondary,
+            window_id: get_window_id(event),
             timestamp: get_timestamp(event),
         });
     }
@@ -422,6 +517,9 @@ extern "C" fn right_mouse_up(this: &Object, _sel: Sel, event: *mut Object) {
         y,
         button: PointerButton::Secondary,
         source: PointerSource::Mouse,
+        pointer_id: 0,
+        window_id: get_window_id(event),
+        modifiers: get_modifiers(event),
         timestamp: get_timestamp(event),
     });
     let click_count: c_int = unsafe { msg(event, Sels::clickCount, ()) };
@@ -430,12 +528,14 @@ extern "C" fn right_mouse_up(this: &Object, _sel: Sel, event: *mut Object) {
             x,
             y,
             button: PointerButton::Secondary,
+            window_id: get_window_id(event),
             timestamp: get_timestamp(event),
         });
         self::submit_event(Event::DoubleClick {
             x,
             y,
             button: PointerButton::Secondary,
+            window_id: get_window_id(event),
             timestamp: get_timestamp(event),
         });
     }
@@ -457,6 +557,9 @@ extern "C" fn other_mouse_down(this: &Object, _sel: Sel, event: *mut Object) {
         y,
         button,
         source: PointerSource::Mouse,
+        pointer_id: 0,
+        window_id: get_window_id(event),
+        modifiers: get_modifiers(event),
         timestamp: get_timestamp(event),
     });
     let click_count: c_int = unsafe { msg(event, Sels::clickCount, ()) };
@@ -465,6 +568,7 @@ extern "C" fn other_mouse_down(this: &Object, _sel: Sel, event: *mut Object) {
             x,
             y,
             button,
+            window_id: get_window_id(event),
             timestamp: get_timestamp(event),
         });
     }
@@ -486,6 +590,9 @@ extern "C" fn other_mouse_up(this: &Object, _sel: Sel, event: *mut Object) {
         y,
         button,
         source: PointerSource::Mouse,
+        pointer_id: 0,
+        window_id: get_window_id(event),
+        modifiers: get_modifiers(event),
         timestamp: get_timestamp(event),
     });
     let click_count: c_int = unsafe { msg(event, Sels::clickCount, ()) };
@@ -494,17 +601,35 @@ extern "C" fn other_mouse_up(this: &Object, _sel: Sel, event: *mut Object) {
             x,
             y,
             button,
+            window_id: get_window_id(event),
             timestamp: get_timestamp(event),
         });
         self::submit_event(Event::DoubleClick {
             x,
             y,
             button,
+            window_id: get_window_id(event),
             timestamp: get_timestamp(event),
         });
     }
 }
 
+// The view's tracking area only reports entering and exiting while the window is the key window.
+extern "C" fn mouse_entered(this: &Object, _sel: Sel, event: *mut Object) {
+    let (x, y) = get_mouse_position(this, event);
+    self::submit_event(Event::PointerEntered {
+        window_id: get_window_id(event),
+        x,
+        y,
+    });
+}
+
+extern "C" fn mouse_exited(_this: &Object, _sel: Sel, event: *mut Object) {
+    self::submit_event(Event::PointerLeft {
+        window_id: get_window_id(event),
+    });
+}
+
 extern "C" fn mouse_dragged(this: &Object, _sel: Sel, event: *mut Object) {
     send_mouse_move(this, event);
 }
@@ -520,15 +645,31 @@ extern "C" fn other_mouse_dragged(this: &Object, _sel: Sel, event: *mut Object)
 // https://developer.apple.com/documentation/appkit/nsresponder/1534192-scrollwheel?language=objc
 extern "C" fn scroll_wheel(_this: &mut Object, _sel: Sel, event: *mut Object) {
     unsafe {
-        let delta_x: CGFloat = msg(event, Sels::scrollingDeltaX, ());
-        let delta_y: CGFloat = msg(event, Sels::scrollingDeltaY, ());
+        let x: CGFloat = msg(event, Sels::scrollingDeltaX, ());
+        let y: CGFloat = msg(event, Sels::scrollingDeltaY, ());
         let window: *mut c_void = msg(event, Sels::window, ());
 
+        // Touchpads and other precise devices report deltas in points instead of lines.
+        let has_precise_deltas: BOOL = msg_send![event, hasPreciseScrollingDeltas];
+        let delta = if has_precise_deltas == YES {
+            let scale = get_backing_scale(window as *const Object);
+            ScrollDelta::Pixels {
+                x: x * scale,
+                y: y * scale,
+            }
+        } else {
+            ScrollDelta::Lines { x, y }
+        };
+
+        let phase: NSUInteger = msg_send![event, phase];
+        let momentum_phase: NSUInteger = msg_send![event, momentumPhase];
+
         self::submit_event(Event::Scroll {
-            delta_x,
-            delta_y,
+            delta,
+            phase: get_scroll_phase(phase, momentum_phase),
             timestamp: get_timestamp(event),
             window_id: WindowId::new(window),
+            modifiers: get_modifiers(event),
         });
     }
 }
@@ -540,11 +681,72 @@ extern "C" fn accepts_first_responder(_this: &Object, _sel: Sel) -> BOOL {
 // https://developer.apple.com/documentation/appkit/nsresponder/1525862-magnifywithevent
 extern "C" fn magnify_with_event(_this: &Object, _sel: Sel, event: *mut Object) {
     let magnification: CGFloat = unsafe { msg(event, Sels::magnification, ()) };
+    submit_gesture(
+        event,
+        Gesture::Pinch {
+            scale: magnification,
+        },
+    );
+}
 
-    self::submit_event(Event::PinchGesture {
-        delta: magnification,
-        timestamp: get_timestamp(event),
+// https://developer.apple.com/documentation/appkit/nsresponder/1525572-rotatewithevent
+extern "C" fn rotate_with_event(_this: &Object, _sel: Sel, event: *mut Object) {
+    // MacOS reports degrees with counter-clockwise as positive.
+    let rotation: f32 = unsafe { msg_send![event, rotation] };
+    submit_gesture(
+        event,
+        Gesture::Rotate {
+            angle: -(rotation as f64).to_radians(),
+        },
+    );
+}
+
+// Mouse wheels report neither a phase nor a momentum phase.
+fn get_scroll_phase(phase: NSUInteger, momentum_phase: NSUInteger) -> ScrollPhase {
+    if momentum_phase & (NSEventPhaseEnded | NSEventPhaseCancelled) != 0 {
+        ScrollPhase::MomentumEnded
+    } else if momentum_phase != 0 {
+        ScrollPhase::Momentum
+    } else if phase & NSEventPhaseBegan != 0 {
+        ScrollPhase::Began
+    } else if phase & NSEventPhaseChanged != 0 {
+        ScrollPhase::Changed
+    } else if phase & (NSEventPhaseEnded | NSEventPhaseCancelled) != 0 {
+        ScrollPhase::Ended
+    } else {
+        ScrollPhase::Unknown
+    }
+}
+
+// Sends the begin, update, and end events for a gesture based on the NSEvent's phase.
+fn submit_gesture(event: *mut Object, gesture: Gesture) {
+    let phase: NSUInteger = unsafe { msg_send![event, phase] };
+    let window: *mut c_void = unsafe { msg(event, Sels::window, ()) };
+    let window_id = WindowId::new(window);
+    let timestamp = get_timestamp(event);
+
+    if phase & NSEventPhaseBegan != 0 {
+        self::submit_event(Event::GestureBegin {
+            kind: gesture.kind(),
+            window_id,
+            timestamp,
+        });
+    }
+
+    self::submit_event(Event::GestureUpdate {
+        gesture,
+        window_id,
+        timestamp,
     });
+
+    if phase & (NSEventPhaseEnded | NSEventPhaseCancelled) != 0 {
+        self::submit_event(Event::GestureEnd {
+            kind: gesture.kind(),
+            cancelled: phase & NSEventPhaseCancelled != 0,
+            window_id,
+            timestamp,
+        });
+    }
 }
 
 extern "C" fn display_layer(this: &Object, _sel: Sel, _layer: *mut Object) {
@@ -584,7 +786,7 @@ extern "C" fn set_marked_text(
     this: &mut Object,
     _sel: Sel,
     string: *mut Object,
-    _selected_range: NSRange,
+    selected_range: NSRange,
     _replacement_range: NSRange,
 ) {
     unsafe {
@@ -598,6 +800,28 @@ extern "C" fn set_marked_text(
             let () = msg_send![marked_text, initWithString: string];
         };
         *marked_text_ref = marked_text;
+
+        let window_id = WindowId::new(msg(this, Sels::window, ()));
+        let composition = ns_string_to_string(msg_send![marked_text, string]);
+        if composition.is_empty() {
+            self::submit_event(Event::IMEEndComposition { window_id });
+            return;
+        }
+
+        // The selected range is measured in UTF-16 code units.
+        let start = utf16_to_byte_offset(&composition, selected_range.location as usize);
+        let end = utf16_to_byte_offset(
+            &composition,
+            (selected_range.location + selected_range.length) as usize,
+        );
+        let selection = if end > start { Some(start..end) } else { None };
+        self::submit_event(Event::IMEComposition {
+            cursor: Some(end),
+            segments: CompositionSegment::from_selection(composition.len(), &selection),
+            composition,
+            selection,
+            window_id,
+        });
     }
 }
 
@@ -605,7 +829,14 @@ extern "C" fn unmark_text(this: &Object, _sel: Sel) {
     unsafe {
         let marked_text: *mut Object = *this.get_ivar("markedText");
         let mutable_string: *mut Object = msg_send![marked_text, mutableString];
+        let length: NSUInteger = msg_send![mutable_string, length];
         let () = msg_send![mutable_string, setString:""];
+
+        if length > 0 {
+            self::submit_event(Event::IMEEndComposition {
+                window_id: WindowId::new(msg(this, Sels::window, ())),
+            });
+        }
     }
 }
 
@@ -623,7 +854,7 @@ extern "C" fn attributed_substring_for_proposed_range(
 }
 
 extern "C" fn insert_text(
-    _this: &Object,
+    this: &Object,
     _sel: Sel,
     string: *mut Object,
     _replacement_range: NSRange,
@@ -642,9 +873,16 @@ extern "C" fn insert_text(
         let slice = std::slice::from_raw_parts(utf8_string, utf8_len);
         let string = std::str::from_utf8_unchecked(slice);
 
+        // Inserting text ends the composition.
+        unmark_text(this, sel!(unmarkText));
+
         // Each character received is submitted as an individual event.
+        let window: *mut c_void = msg(this, Sels::window, ());
         for c in string.chars() {
-            self::submit_event(Event::CharacterReceived { character: c });
+            self::submit_event(Event::CharacterReceived {
+                character: c,
+                window_id: WindowId::new(window),
+            });
         }
     }
 }
@@ -670,18 +908,20 @@ extern "C" fn first_rect_for_character_range(
 
         // Get the frame for this view's window.
         let window: *const Object = msg(this, Sels::window, ());
-        let frame: CGRect = msg(window, Sels::frame, ());
+        let backing_scale = get_backing_scale(window);
+        let frame: CGRect = msg(this, Sels::frame, ());
 
-        // The screen's origin is in the bottom left but kapp's API specifies
-        // the text input rect position relative to the window's upper left.
-        // The coordinates are adjusted here accordingly.
-        CGRect::new(
+        // The view's origin is in the bottom left but kapp's API specifies
+        // the text input rect position relative to the window's upper left in physical coordinates.
+        // The coordinates are adjusted here accordingly and then converted to screen coordinates.
+        let rect = CGRect::new(
             CGPoint::new(
-                frame.origin.x + x,
-                frame.origin.y + frame.size.height - (y + height),
+                x / backing_scale,
+                frame.size.height - (y + height) / backing_scale,
             ),
-            CGSize::new(width, height),
-        )
+            CGSize::new(width / backing_scale, height / backing_scale),
+        );
+        msg_send![window, convertRectToScreen: rect]
     }
 }
 
@@ -712,6 +952,10 @@ pub fn add_view_events_to_decl(decl: &mut ClassDecl) {
             Sel::from_ptr(Sels::magnifyWithEvent),
             magnify_with_event as extern "C" fn(&Object, Sel, *mut Object),
         );
+        decl.add_method(
+            sel!(rotateWithEvent:),
+            rotate_with_event as extern "C" fn(&Object, Sel, *mut Object),
+        );
 
         decl.add_method(
             Sel::from_ptr(Sels::drawRect),
@@ -754,6 +998,14 @@ pub fn add_view_events_to_decl(decl: &mut ClassDecl) {
             Sel::from_ptr(Sels::mouseMoved),
             mouse_moved as extern "C" fn(&Object, Sel, *mut Object),
         );
+        decl.add_method(
+            sel!(mouseEntered:),
+            mouse_entered as extern "C" fn(&Object, Sel, *mut Object),
+        );
+        decl.add_method(
+            sel!(mouseExited:),
+            mouse_exited as extern "C" fn(&Object, Sel, *mut Object),
+        );
         decl.add_method(
             Sel::from_ptr(Sels::mouseDragged),
             mouse_dragged as extern "C" fn(&Object, Sel, *mut Object),
@@ -841,6 +1093,83 @@ fn get_timestamp(event: *mut Object) -> std::time::Duration {
     std::time::Duration::from_secs_f64(number)
 }
 
+fn utf16_to_byte_offset(string: &str, offset: usize) -> usize {
+    let mut utf16_offset = 0;
+    for (i, c) in string.char_indices() {
+        if utf16_offset >= offset {
+            return i;
+        }
+        utf16_offset += c.len_utf16();
+    }
+    string.len()
+}
+
+fn ns_string_to_string(string: *mut Object) -> String {
+    unsafe {
+        let utf8_string: *const std::os::raw::c_uchar = msg_send![string, UTF8String];
+        let utf8_len: usize = msg_send![string, lengthOfBytesUsingEncoding: UTF8_ENCODING];
+        let slice = std::slice::from_raw_parts(utf8_string, utf8_len);
+        std::str::from_utf8_unchecked(slice).to_string()
+    }
+}
+
+/// Returns the logical key for the current layout and the physical key.
+/// Mac key codes are positions on the keyboard, regardless of layout.
+fn get_keys(event: *mut Object, key_code: u16) -> (Key, Key) {
+    let physical_key = super::keys_mac::virtual_keycode_to_key(key_code);
+    let characters = ns_string_to_string(unsafe { msg_send![event, charactersIgnoringModifiers] });
+    let key = match characters.chars().next().map(Key::from_character) {
+        Some(Key::Unknown) | None => physical_key,
+        Some(key) => key,
+    };
+    (key, physical_key)
+}
+
+fn modifiers_from_flags(modifier_flags: NSUInteger) -> Modifiers {
+    let mut modifiers = Modifiers::empty();
+    modifiers.set(
+        Modifiers::SHIFT,
+        modifier_flags & NSEventModifierFlagShift != 0,
+    );
+    modifiers.set(
+        Modifiers::CONTROL,
+        modifier_flags & NSEventModifierFlagControl != 0,
+    );
+    modifiers.set(
+        Modifiers::ALT,
+        modifier_flags & NSEventModifierFlagOption != 0,
+    );
+    modifiers.set(
+        Modifiers::SUPER,
+        modifier_flags & NSEventModifierFlagCommand != 0,
+    );
+    modifiers.set(
+        Modifiers::CAPS_LOCK,
+        modifier_flags & NSEventModifierFlagCapsLock != 0,
+    );
+    modifiers
+}
+
+fn get_modifiers(event: *mut Object) -> Modifiers {
+    let modifier_flags: NSUInteger = unsafe { msg(event, Sels::modifierFlags, ()) };
+    modifiers_from_flags(modifier_flags)
+}
+
+/// Stores the new modifier flags and sends `ModifiersChanged` if the modifiers differ.
+fn update_modifier_flags(modifier_flags: NSUInteger) {
+    let modifier_flags_old = APPLICATION_DATA
+        .with(|d| std::mem::replace(&mut d.borrow_mut().modifier_flags, modifier_flags));
+    let modifiers = modifiers_from_flags(modifier_flags);
+    if modifiers_from_flags(modifier_flags_old) != modifiers {
+        self::submit_event(Event::ModifiersChanged { modifiers });
+    }
+}
+
+fn get_window_id(event: *mut Object) -> WindowId {
+    let window: *mut c_void = unsafe { msg(event, Sels::window, ()) };
+    WindowId::new(window)
+}
+
 fn get_backing_scale(window: *const Object) -> CGFloat {
     unsafe { msg(window, Sels::backingScaleFactor, ()) }
 }
@@ -876,6 +1205,7 @@ fn send_mouse_move(this: &Object, event: *mut Object) {
     submit_event(Event::MouseMotion {
         delta_x,
         delta_y,
+        window_id: get_window_id(event),
         timestamp,
     });
 
@@ -885,6 +1215,9 @@ fn send_mouse_move(this: &Object, event: *mut Object) {
             x,
             y,
             source: PointerSource::Mouse,
+            pointer_id: 0,
+            window_id: get_window_id(event),
+            modifiers: get_modifiers(event),
             timestamp,
         });
     }
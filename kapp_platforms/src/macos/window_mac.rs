@@ -1,11 +1,12 @@
 use super::apple::*;
-use kapp_platform_common::{WindowId, WindowParameters};
+use kapp_platform_common::{HitTest, WindowId, WindowKind, WindowParameters};
 use std::ffi::c_void;
 
 /// Per window state stored in an ivar on each window.
 pub(crate) struct WindowState {
     // Relative to the window.
     pub(crate) text_input_rectangle: (f64, f64, f64, f64),
+    pub(crate) hit_test: Option<HitTest>,
 }
 
 pub(crate) fn build(
@@ -18,19 +19,34 @@ pub(crate) fn build(
         // just be reset with another call once the backing scale is known.
         let rect = NSRect::new(NSPoint::new(0., 0.), NSSize::new(500.0, 500.0));
 
-        let mut style =
-            NSWindowStyleMaskTitled | NSWindowStyleMaskClosable | NSWindowStyleMaskMiniaturizable;
+        let kind = window_parameters.kind;
+        let popup = matches!(kind, WindowKind::Popup | WindowKind::Tooltip);
 
-        if window_parameters.resizable {
+        // Popups, tooltips, and splash screens are borderless.
+        let mut style = if popup || kind == WindowKind::Splash {
+            NSWindowStyleMaskBorderless
+        } else {
+            NSWindowStyleMaskTitled | NSWindowStyleMaskClosable | NSWindowStyleMaskMiniaturizable
+        };
+
+        if window_parameters.resizable && style != NSWindowStyleMaskBorderless {
             style |= NSWindowStyleMaskResizable;
         }
 
+        // Utility windows must be panels.
+        let ns_window_class = if kind == WindowKind::Utility {
+            style |= NSWindowStyleMaskUtilityWindow;
+            class!(NSPanel)
+        } else {
+            class!(NSWindow)
+        };
+
         if window_parameters.without_titlebar {
             style |= NSWindowStyleMaskFullSizeContentView
         }
 
         // This allocation will be released when the window is dropped.
-        let ns_window: *mut Object = msg_send![class!(NSWindow), alloc];
+        let ns_window: *mut Object = msg_send![ns_window_class, alloc];
         let () = msg_send![
             ns_window,
             initWithContentRect:rect.clone()
@@ -75,9 +91,64 @@ pub(crate) fn build(
             let () = msg_send![ns_window, setContentSize: NSSize::new((width as f64) / backing_scale, (height as f64) / backing_scale)];
         }
 
+        // Popups and tooltips are positioned relative to their parent's content area.
+        // This is done after the size is set because resizing keeps the lower left corner in place.
+        if let (Some((x, y)), Some(parent), true) =
+            (window_parameters.position, window_parameters.parent, popup)
+        {
+            let parent = parent.raw() as *mut Object;
+            let parent_view: *mut Object = msg_send![parent, contentView];
+            let frame: NSRect = msg_send![parent_view, frame];
+            let frame: NSRect = msg_send![parent, convertRectToScreen: frame];
+            let top_left = NSPoint::new(
+                frame.origin.x + x as f64 / backing_scale,
+                frame.origin.y + frame.size.height - y as f64 / backing_scale,
+            );
+            let () = msg_send![ns_window, setFrameTopLeftPoint: top_left];
+        }
+
+        if popup {
+            let () = msg_send![ns_window, setLevel: NSPopUpMenuWindowLevel];
+        }
+        if kind == WindowKind::Tooltip {
+            let () = msg_send![ns_window, setIgnoresMouseEvents: YES];
+        }
+
         let title = NSString::new(&window_parameters.title);
         let () = msg_send![ns_window, setTitle: title.raw];
-        let () = msg_send![ns_window, makeKeyAndOrderFront: nil];
+
+        if window_parameters.always_on_top {
+            let () = msg_send![ns_window, setLevel: NSFloatingWindowLevel];
+        }
+
+        // The GL context also needs a transparent surface, which it sets up for non-opaque windows.
+        if window_parameters.transparent {
+            let clear_color: *mut Object = msg_send![class!(NSColor), clearColor];
+            let () = msg_send![ns_window, setOpaque: NO];
+            let () = msg_send![ns_window, setBackgroundColor: clear_color];
+        }
+
+        if window_parameters.visible {
+            match (kind, window_parameters.parent) {
+                // A sheet is attached to its parent and blocks input to it until it's closed.
+                (WindowKind::Dialog, Some(parent)) => {
+                    let () = msg_send![parent.raw() as *mut Object, beginSheet: ns_window completionHandler: nil];
+                }
+                _ => {
+                    // Popups and tooltips leave focus with their parent.
+                    if window_parameters.focused && !popup {
+                        let () = msg_send![ns_window, makeKeyAndOrderFront: nil];
+                    } else {
+                        let () = msg_send![ns_window, orderFront: nil];
+                    }
+
+                    // Child windows move with their parent.
+                    if let Some(parent) = window_parameters.parent {
+                        let () = msg_send![parent.raw() as *mut Object, addChildWindow: ns_window ordered: NSWindowAbove];
+                    }
+                }
+            }
+        }
 
         // Setup window delegate that receives events.
         // This allocation will be released when the window is dropped.
@@ -93,6 +164,7 @@ pub(crate) fn build(
             "kappState",
             Box::leak(Box::new(WindowState {
                 text_input_rectangle: (0., 0., 0., 0.),
+                hit_test: None,
             })) as *mut WindowState as *mut c_void,
         );
         let () = msg_send![ns_view, initWithFrame: rect.clone()];
@@ -0,0 +1,711 @@
+/// A minimal blocking D-Bus client.
+/// It only implements what's needed to call methods and receive signals on the session bus.
+/// https://dbus.freedesktop.org/doc/dbus-specification.html
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+
+pub const METHOD_CALL: u8 = 1;
+pub const METHOD_RETURN: u8 = 2;
+pub const SIGNAL: u8 = 4;
+
+const PATH: u8 = 1;
+const INTERFACE: u8 = 2;
+const MEMBER: u8 = 3;
+const ERROR_NAME: u8 = 4;
+const REPLY_SERIAL: u8 = 5;
+const DESTINATION: u8 = 6;
+const SENDER: u8 = 7;
+const SIGNATURE: u8 = 8;
+
+extern "C" {
+    fn getuid() -> u32;
+}
+
+#[derive(Default)]
+pub struct Message {
+    pub message_type: u8,
+    pub serial: u32,
+    pub reply_serial: Option<u32>,
+    pub path: String,
+    pub interface: String,
+    pub member: String,
+    pub error_name: String,
+    pub destination: String,
+    pub sender: String,
+    pub signature: String,
+    pub body: Vec<u8>,
+    big_endian: bool,
+}
+
+impl Message {
+    pub fn method_call(destination: &str, path: &str, interface: &str, member: &str) -> Self {
+        Self {
+            message_type: METHOD_CALL,
+            destination: destination.to_string(),
+            path: path.to_string(),
+            interface: interface.to_string(),
+            member: member.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn reader(&self) -> Reader<'_> {
+        Reader {
+            data: &self.body,
+            position: 0,
+            little_endian: !self.big_endian,
+        }
+    }
+}
+
+pub struct Connection {
+    stream: UnixStream,
+    next_serial: u32,
+    pub unique_name: String,
+}
+
+impl Connection {
+    /// Connects to the bus in `DBUS_SESSION_BUS_ADDRESS`,
+    /// or the default session bus socket in `XDG_RUNTIME_DIR`.
+    pub fn session() -> Result<Self, ()> {
+        let stream = match std::env::var("DBUS_SESSION_BUS_ADDRESS") {
+            Ok(addresses) => addresses
+                .split(';')
+                .find_map(|address| connect(address).ok())
+                .ok_or(())?,
+            Err(_) => {
+                let runtime_dir = std::env::var("XDG_RUNTIME_DIR").map_err(|_| ())?;
+                UnixStream::connect(format!("{}/bus", runtime_dir)).map_err(|_| ())?
+            }
+        };
+
+        let mut connection = Self {
+            stream,
+            next_serial: 1,
+            unique_name: String::new(),
+        };
+        connection.authenticate()?;
+
+        let hello = Message::method_call(
+            "org.freedesktop.DBus",
+            "/org/freedesktop/DBus",
+            "org.freedesktop.DBus",
+            "Hello",
+        );
+        let reply = connection.call(hello)?;
+        connection.unique_name = reply.reader().string()?;
+        Ok(connection)
+    }
+
+    fn authenticate(&mut self) -> Result<(), ()> {
+        // The EXTERNAL mechanism is passed the user id as hex encoded ASCII.
+        let uid = unsafe { getuid() }.to_string();
+        let hex_uid: String = uid.bytes().map(|b| format!("{:02x}", b)).collect();
+        self.stream
+            .write_all(format!("\0AUTH EXTERNAL {}\r\n", hex_uid).as_bytes())
+            .map_err(|_| ())?;
+
+        let mut line = Vec::new();
+        while !line.ends_with(b"\r\n") {
+            let mut byte = [0];
+            self.stream.read_exact(&mut byte).map_err(|_| ())?;
+            line.push(byte[0]);
+        }
+        if !line.starts_with(b"OK ") {
+            return Err(());
+        }
+        self.stream.write_all(b"BEGIN\r\n").map_err(|_| ())
+    }
+
+    /// Sends a message and returns its serial.
+    pub fn send(&mut self, mut message: Message) -> Result<u32, ()> {
+        message.serial = self.next_serial;
+        self.next_serial += 1;
+        self.stream
+            .write_all(&serialize(&message))
+            .map_err(|_| ())?;
+        Ok(message.serial)
+    }
+
+    /// Sends a method call and waits for its reply.
+    /// Other messages received in the meantime are discarded.
+    pub fn call(&mut self, message: Message) -> Result<Message, ()> {
+        let serial = self.send(message)?;
+        loop {
+            let reply = self.receive()?;
+            if reply.reply_serial == Some(serial) {
+                return match reply.message_type {
+                    METHOD_RETURN => Ok(reply),
+                    _ => Err(()),
+                };
+            }
+        }
+    }
+
+    /// Blocks until a message is received.
+    pub fn receive(&mut self) -> Result<Message, ()> {
+        // The fixed part of the header is followed by the length of the header field array.
+        let mut fixed_header = [0; 16];
+        self.stream.read_exact(&mut fixed_header).map_err(|_| ())?;
+        let little_endian = match fixed_header[0] {
+            b'l' => true,
+            b'B' => false,
+            _ => return Err(()),
+        };
+        let read_u32 = |bytes: &[u8]| {
+            let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+            if little_endian {
+                u32::from_le_bytes(bytes)
+            } else {
+                u32::from_be_bytes(bytes)
+            }
+        };
+        let body_length = read_u32(&fixed_header[4..8]) as usize;
+        let fields_length = read_u32(&fixed_header[12..16]) as usize;
+
+        let header_length = align(16 + fields_length, 8);
+        let mut data = fixed_header.to_vec();
+        data.resize(header_length + body_length, 0);
+        self.stream.read_exact(&mut data[16..]).map_err(|_| ())?;
+
+        let mut message = Message {
+            message_type: fixed_header[1],
+            serial: read_u32(&fixed_header[8..12]),
+            body: data[header_length..].to_vec(),
+            big_endian: !little_endian,
+            ..Default::default()
+        };
+
+        let mut reader = Reader {
+            data: &data[..16 + fields_length],
+            position: 16,
+            little_endian,
+        };
+        while reader.position < reader.data.len() {
+            reader.align(8)?;
+            let code = reader.byte()?;
+            let signature = reader.signature()?;
+            match (code, signature.as_str()) {
+                (PATH, "o") => message.path = reader.string()?,
+                (INTERFACE, "s") => message.interface = reader.string()?,
+                (MEMBER, "s") => message.member = reader.string()?,
+                (ERROR_NAME, "s") => message.error_name = reader.string()?,
+                (REPLY_SERIAL, "u") => message.reply_serial = Some(reader.u32()?),
+                (DESTINATION, "s") => message.destination = reader.string()?,
+                (SENDER, "s") => message.sender = reader.string()?,
+                (SIGNATURE, "g") => message.signature = reader.signature()?,
+                _ => reader.skip(&signature)?,
+            }
+        }
+        Ok(message)
+    }
+}
+
+fn connect(address: &str) -> Result<UnixStream, ()> {
+    let parameters = address.strip_prefix("unix:").ok_or(())?;
+    for parameter in parameters.split(',') {
+        if let Some(path) = parameter.strip_prefix("path=") {
+            return UnixStream::connect(unescape(path)).map_err(|_| ());
+        }
+        if let Some(name) = parameter.strip_prefix("abstract=") {
+            use std::os::linux::net::SocketAddrExt;
+            let address =
+                std::os::unix::net::SocketAddr::from_abstract_name(unescape(name).as_bytes())
+                    .map_err(|_| ())?;
+            return UnixStream::connect_addr(&address).map_err(|_| ());
+        }
+    }
+    Err(())
+}
+
+/// Address values escape bytes as '%' followed by two hex digits.
+fn unescape(value: &str) -> String {
+    String::from_utf8_lossy(&percent_decode(value)).into_owned()
+}
+
+pub fn percent_decode(value: &str) -> Vec<u8> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match (bytes[i], escaped) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    decoded
+}
+
+fn serialize(message: &Message) -> Vec<u8> {
+    let mut header = Writer {
+        data: Vec::new(),
+        big_endian: message.big_endian,
+    };
+    header.byte(if message.big_endian { b'B' } else { b'l' });
+    header.byte(message.message_type);
+    header.byte(0);
+    header.byte(1);
+    header.u32(message.body.len() as u32);
+    header.u32(message.serial);
+
+    let fields = header.begin_array(8);
+    let mut field = |code, signature, write: &dyn Fn(&mut Writer)| {
+        header.begin_struct();
+        header.byte(code);
+        header.signature(signature);
+        write(&mut header);
+    };
+    if !message.path.is_empty() {
+        field(PATH, "o", &|w| w.string(&message.path));
+    }
+    if !message.interface.is_empty() {
+        field(INTERFACE, "s", &|w| w.string(&message.interface));
+    }
+    if !message.member.is_empty() {
+        field(MEMBER, "s", &|w| w.string(&message.member));
+    }
+    if !message.error_name.is_empty() {
+        field(ERROR_NAME, "s", &|w| w.string(&message.error_name));
+    }
+    if let Some(reply_serial) = message.reply_serial {
+        field(REPLY_SERIAL, "u", &|w| w.u32(reply_serial));
+    }
+    if !message.destination.is_empty() {
+        field(DESTINATION, "s", &|w| w.string(&message.destination));
+    }
+    if !message.signature.is_empty() {
+        field(SIGNATURE, "g", &|w| w.signature(&message.signature));
+    }
+    header.end_array(fields);
+    header.align(8);
+
+    let mut data = header.data;
+    data.extend_from_slice(&message.body);
+    data
+}
+
+/// Alignments are always powers of two.
+fn align(position: usize, alignment: usize) -> usize {
+    (position + alignment - 1) & !(alignment - 1)
+}
+
+/// Writes values in the D-Bus wire format.
+/// Messages are written little endian, but big endian is supported to match `Reader`.
+#[derive(Default)]
+pub struct Writer {
+    pub data: Vec<u8>,
+    big_endian: bool,
+}
+
+impl Writer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn u32_bytes(&self, value: u32) -> [u8; 4] {
+        if self.big_endian {
+            value.to_be_bytes()
+        } else {
+            value.to_le_bytes()
+        }
+    }
+
+    pub fn align(&mut self, alignment: usize) {
+        let length = align(self.data.len(), alignment);
+        self.data.resize(length, 0);
+    }
+
+    pub fn byte(&mut self, value: u8) {
+        self.data.push(value);
+    }
+
+    pub fn boolean(&mut self, value: bool) {
+        self.u32(value as u32);
+    }
+
+    pub fn u32(&mut self, value: u32) {
+        self.align(4);
+        let bytes = self.u32_bytes(value);
+        self.data.extend_from_slice(&bytes);
+    }
+
+    /// Writes a string or an object path.
+    pub fn string(&mut self, value: &str) {
+        self.u32(value.len() as u32);
+        self.data.extend_from_slice(value.as_bytes());
+        self.data.push(0);
+    }
+
+    pub fn signature(&mut self, value: &str) {
+        self.byte(value.len() as u8);
+        self.data.extend_from_slice(value.as_bytes());
+        self.data.push(0);
+    }
+
+    /// Begins an array of elements with the given alignment.
+    /// The returned value must be passed to `end_array`.
+    pub fn begin_array(&mut self, element_alignment: usize) -> (usize, usize) {
+        self.u32(0);
+        let length_position = self.data.len() - 4;
+        self.align(element_alignment);
+        (length_position, self.data.len())
+    }
+
+    pub fn end_array(&mut self, (length_position, start): (usize, usize)) {
+        let length = self.u32_bytes((self.data.len() - start) as u32);
+        self.data[length_position..length_position + 4].copy_from_slice(&length);
+    }
+
+    /// Structs and dictionary entries are 8 byte aligned.
+    pub fn begin_struct(&mut self) {
+        self.align(8);
+    }
+}
+
+/// Reads values in the D-Bus wire format.
+pub struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+    little_endian: bool,
+}
+
+impl<'a> Reader<'a> {
+    pub fn align(&mut self, alignment: usize) -> Result<(), ()> {
+        self.position = align(self.position, alignment);
+        if self.position > self.data.len() {
+            return Err(());
+        }
+        Ok(())
+    }
+
+    fn bytes(&mut self, length: usize) -> Result<&'a [u8], ()> {
+        let bytes = self
+            .data
+            .get(self.position..self.position + length)
+            .ok_or(())?;
+        self.position += length;
+        Ok(bytes)
+    }
+
+    pub fn byte(&mut self) -> Result<u8, ()> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub fn u32(&mut self) -> Result<u32, ()> {
+        self.align(4)?;
+        let bytes = self.bytes(4)?;
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        Ok(if self.little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    }
+
+    /// Reads a string or an object path.
+    pub fn string(&mut self) -> Result<String, ()> {
+        let length = self.u32()? as usize;
+        let bytes = self.bytes(length + 1)?;
+        String::from_utf8(bytes[..length].to_vec()).map_err(|_| ())
+    }
+
+    pub fn signature(&mut self) -> Result<String, ()> {
+        let length = self.byte()? as usize;
+        let bytes = self.bytes(length + 1)?;
+        String::from_utf8(bytes[..length].to_vec()).map_err(|_| ())
+    }
+
+    /// Reads the length of an array and aligns to its first element.
+    /// Returns the position the array ends at.
+    pub fn begin_array(&mut self, element_alignment: usize) -> Result<usize, ()> {
+        let length = self.u32()? as usize;
+        self.align(element_alignment)?;
+        let end = self.position + length;
+        if end > self.data.len() {
+            return Err(());
+        }
+        Ok(end)
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn begin_struct(&mut self) -> Result<(), ()> {
+        self.align(8)
+    }
+
+    /// Skips a value of each complete type in the signature.
+    pub fn skip(&mut self, signature: &str) -> Result<(), ()> {
+        let signature = signature.as_bytes();
+        let mut i = 0;
+        while i < signature.len() {
+            i = self.skip_type(signature, i)?;
+        }
+        Ok(())
+    }
+
+    /// Skips a value of the complete type starting at `signature[i]`
+    /// and returns the index after the type.
+    fn skip_type(&mut self, signature: &[u8], i: usize) -> Result<usize, ()> {
+        match *signature.get(i).ok_or(())? {
+            b'y' => {
+                self.bytes(1)?;
+            }
+            b'n' | b'q' => {
+                self.align(2)?;
+                self.bytes(2)?;
+            }
+            b'b' | b'i' | b'u' | b'h' => {
+                self.u32()?;
+            }
+            b'x' | b't' | b'd' => {
+                self.align(8)?;
+                self.bytes(8)?;
+            }
+            b's' | b'o' => {
+                self.string()?;
+            }
+            b'g' => {
+                self.signature()?;
+            }
+            b'v' => {
+                let signature = self.signature()?;
+                self.skip(&signature)?;
+            }
+            b'a' => {
+                let element_end = type_end(signature, i + 1)?;
+                let array_end = self.begin_array(alignment_of(signature[i + 1]))?;
+                self.position = array_end;
+                return Ok(element_end);
+            }
+            b'(' | b'{' => {
+                self.align(8)?;
+                let end = type_end(signature, i)?;
+                let mut j = i + 1;
+                while j < end - 1 {
+                    j = self.skip_type(signature, j)?;
+                }
+                return Ok(end);
+            }
+            _ => return Err(()),
+        }
+        Ok(i + 1)
+    }
+}
+
+fn alignment_of(type_code: u8) -> usize {
+    match type_code {
+        b'n' | b'q' => 2,
+        b'b' | b'i' | b'u' | b'h' | b's' | b'o' | b'a' => 4,
+        b'x' | b't' | b'd' | b'(' | b'{' => 8,
+        _ => 1,
+    }
+}
+
+/// Returns the index after the complete type starting at `signature[i]`.
+fn type_end(signature: &[u8], i: usize) -> Result<usize, ()> {
+    match *signature.get(i).ok_or(())? {
+        b'a' => type_end(signature, i + 1),
+        b'(' | b'{' => {
+            let mut j = i + 1;
+            while *signature.get(j).ok_or(())? != b')' && signature[j] != b'}' {
+                j = type_end(signature, j)?;
+            }
+            Ok(j + 1)
+        }
+        _ => Ok(i + 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn connection(stream: UnixStream) -> Connection {
+        Connection {
+            stream,
+            next_serial: 1,
+            unique_name: String::new(),
+        }
+    }
+
+    fn writer(big_endian: bool) -> Writer {
+        Writer {
+            data: Vec::new(),
+            big_endian,
+        }
+    }
+
+    fn round_trip(big_endian: bool) {
+        let (a, b) = UnixStream::pair().unwrap();
+        let (mut sender, mut receiver) = (connection(a), connection(b));
+
+        let mut body = writer(big_endian);
+        body.string("hello");
+        body.u32(0x1234_5678);
+        let message = Message {
+            message_type: SIGNAL,
+            reply_serial: Some(7),
+            path: "/org/example/Object".to_string(),
+            interface: "org.example.Interface".to_string(),
+            member: "Changed".to_string(),
+            error_name: "org.example.Error".to_string(),
+            destination: ":1.42".to_string(),
+            signature: "su".to_string(),
+            body: body.data.clone(),
+            big_endian,
+            ..Default::default()
+        };
+        sender.send(message).unwrap();
+        let serial = sender
+            .send(Message::method_call("a.b", "/", "a.b", "C"))
+            .unwrap();
+
+        let received = receiver.receive().unwrap();
+        assert_eq!(received.message_type, SIGNAL);
+        assert_eq!(received.serial, 1);
+        assert_eq!(received.reply_serial, Some(7));
+        assert_eq!(received.path, "/org/example/Object");
+        assert_eq!(received.interface, "org.example.Interface");
+        assert_eq!(received.member, "Changed");
+        assert_eq!(received.error_name, "org.example.Error");
+        assert_eq!(received.destination, ":1.42");
+        assert_eq!(received.signature, "su");
+        assert_eq!(received.body, body.data);
+        assert_eq!(received.big_endian, big_endian);
+        let mut reader = received.reader();
+        assert_eq!(reader.string(), Ok("hello".to_string()));
+        assert_eq!(reader.u32(), Ok(0x1234_5678));
+
+        // The header padding of the first message must be consumed exactly.
+        let received = receiver.receive().unwrap();
+        assert_eq!(received.serial, serial);
+        assert_eq!(received.member, "C");
+        assert_eq!(received.reply_serial, None);
+    }
+
+    #[test]
+    fn round_trip_little_endian() {
+        round_trip(false);
+    }
+
+    #[test]
+    fn round_trip_big_endian() {
+        round_trip(true);
+    }
+
+    const END: u32 = 0xDEAD_BEEF;
+
+    // Writes `a{sv}` with nested dictionaries, structs, and an empty array.
+    fn write_vardict(w: &mut Writer) {
+        let dict = w.begin_array(8);
+        w.begin_struct();
+        w.string("byte");
+        w.signature("y");
+        w.byte(3);
+        w.begin_struct();
+        w.string("nested");
+        w.signature("a{sv}");
+        let nested = w.begin_array(8);
+        w.begin_struct();
+        w.string("flag");
+        w.signature("b");
+        w.boolean(true);
+        w.end_array(nested);
+        w.begin_struct();
+        w.string("filter");
+        w.signature("(sa(us))");
+        write_filter(w, "Images", &["*.png", "*.jpg"]);
+        w.begin_struct();
+        w.string("empty");
+        w.signature("a(us)");
+        let empty = w.begin_array(8);
+        w.end_array(empty);
+        w.end_array(dict);
+    }
+
+    fn write_filter(w: &mut Writer, name: &str, patterns: &[&str]) {
+        w.begin_struct();
+        w.string(name);
+        let array = w.begin_array(8);
+        for pattern in patterns {
+            w.begin_struct();
+            w.u32(0);
+            w.string(pattern);
+        }
+        w.end_array(array);
+    }
+
+    fn skip_then_read_end(data: &[u8], big_endian: bool, signature: &str) {
+        let mut reader = Reader {
+            data,
+            position: 0,
+            little_endian: !big_endian,
+        };
+        reader.skip(signature).unwrap();
+        assert_eq!(reader.u32(), Ok(END));
+        assert_eq!(reader.position(), data.len());
+    }
+
+    #[test]
+    fn skip_nested_vardict() {
+        for big_endian in [false, true] {
+            let mut w = writer(big_endian);
+            // The array's length ends at 12, so its elements are padded to 16.
+            w.byte(1);
+            w.u32(2);
+            write_vardict(&mut w);
+            w.u32(END);
+            skip_then_read_end(&w.data, big_endian, "yua{sv}");
+        }
+    }
+
+    #[test]
+    fn skip_filters() {
+        for big_endian in [false, true] {
+            let mut w = writer(big_endian);
+            w.byte(1);
+            w.u32(2);
+            let filters = w.begin_array(8);
+            write_filter(&mut w, "Images", &["*.png", "*.jpg"]);
+            write_filter(&mut w, "Empty", &[]);
+            write_filter(&mut w, "Text", &["*.txt"]);
+            w.end_array(filters);
+            w.u32(END);
+            skip_then_read_end(&w.data, big_endian, "yua(sa(us))");
+        }
+    }
+
+    #[test]
+    fn skip_rejects_truncated_data() {
+        let mut w = writer(false);
+        write_vardict(&mut w);
+        let data = &w.data[..w.data.len() - 1];
+        let mut reader = Reader {
+            data,
+            position: 0,
+            little_endian: true,
+        };
+        assert_eq!(reader.skip("a{sv}"), Err(()));
+    }
+
+    #[test]
+    fn percent_decoding() {
+        assert_eq!(percent_decode("/tmp/a%20b"), b"/tmp/a b");
+        assert_eq!(percent_decode("%C3%a9"), "é".as_bytes());
+        assert_eq!(percent_decode("%2"), b"%2");
+        assert_eq!(percent_decode("100%zz"), b"100%zz");
+        assert_eq!(percent_decode("%%41"), b"%A");
+        assert_eq!(percent_decode(""), b"");
+    }
+}
@@ -1,6 +1,20 @@
 use fermium::scancode::*;
 
+use fermium::keycode::*;
 use kapp_platform_common::{Key, Key::*};
+
+/// Keycodes without this bit set are the character the key produces in the current layout.
+const SCANCODE_MASK: SDL_Keycode = 1 << 30;
+
+/// Converts a layout dependent keycode to a `Key`.
+pub fn keycode_to_key(key_in: SDL_Keycode) -> Key {
+    if key_in & SCANCODE_MASK != 0 {
+        scancode_to_key(SDL_Scancode((key_in & !SCANCODE_MASK) as _))
+    } else {
+        std::char::from_u32(key_in as u32).map_or(Unknown, Key::from_character)
+    }
+}
+
 pub fn scancode_to_key(key_in: SDL_Scancode) -> Key {
     match key_in {
         SDL_SCANCODE_0 => Digit0,
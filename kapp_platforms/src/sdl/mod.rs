@@ -1,11 +1,25 @@
+#[cfg(target_os = "linux")]
+mod dbus;
 mod keys_sdl;
+#[cfg(target_os = "linux")]
+mod portal;
+mod touch_gesture;
+#[cfg(target_os = "linux")]
+mod x11;
 use kapp_platform_common::*;
 use keys_sdl::*;
+use touch_gesture::TouchGesture;
 
-use fermium::{events::*, keyboard::*, mouse::*, rect::*, stdinc::*, touch::*, video::*, *};
+use fermium::{
+    events::*, hints::*, keyboard::*, messagebox::*, mouse::*, rect::*, scancode::*, stdinc::*,
+    touch::*, video::*, *,
+};
 
-use core::cell::Cell;
+use core::cell::{Cell, RefCell};
+use std::collections::HashMap;
 use std::ffi::{CStr, CString};
+use std::path::PathBuf;
+use std::sync::Mutex;
 use std::time::Duration;
 
 pub mod prelude {
@@ -22,7 +36,21 @@ pub struct PlatformApplication {
 
 impl PlatformApplicationTrait for PlatformApplication {
     type EventLoop = PlatformEventLoop;
-    fn new() -> Self {
+    type EventLoopProxy = PlatformEventLoopProxy;
+    fn new(app_info: &AppInfo) -> Self {
+        // SDL 2.0.14 reads the X11 WM_CLASS and Wayland app id from these variables when
+        // it initializes. Variables set by the user take priority.
+        // SDL sets `_NET_WM_PID` itself. It has no way to set the application name,
+        // and doesn't complete startup notification or XDG activation,
+        // so X11 startup notification is completed when the first window is shown.
+        if !app_info.id.is_empty() {
+            for variable in &["SDL_VIDEO_X11_WMCLASS", "SDL_VIDEO_WAYLAND_WMCLASS"] {
+                if std::env::var_os(variable).is_none() {
+                    std::env::set_var(variable, &app_info.id);
+                }
+            }
+        }
+
         unsafe {
             assert!(SDL_Init(SDL_INIT_EVERYTHING) == 0);
 
@@ -38,6 +66,10 @@ impl PlatformApplicationTrait for PlatformApplication {
         PlatformEventLoop {}
     }
 
+    fn event_loop_proxy(&mut self) -> Self::EventLoopProxy {
+        PlatformEventLoopProxy {}
+    }
+
     fn set_window_position(&mut self, window_id: WindowId, x: u32, y: u32) {
         unsafe {
             SDL_SetWindowPosition(window_id.raw() as *mut SDL_Window, x as i32, y as i32);
@@ -104,10 +136,140 @@ impl PlatformApplicationTrait for PlatformApplication {
         }
     }
     fn close_window(&mut self, window_id: WindowId) {
+        TEXT_INPUT_RECTANGLES.with(|r| r.borrow_mut().remove(&window_id));
+        HIT_TESTS.with(|h| h.borrow_mut().remove(&window_id));
+        LAST_TOUCH_WINDOW.with(|w| {
+            if w.get() == Some(window_id) {
+                w.set(None)
+            }
+        });
+        ACTIVE_GESTURE.with(|g| {
+            let mut gesture = g.borrow_mut();
+            if gesture.as_ref().map(|g| g.window_id()) == Some(window_id) {
+                *gesture = None;
+            }
+        });
         unsafe {
             SDL_DestroyWindow(window_id.raw() as *mut SDL_Window);
         }
     }
+    fn show_window(&mut self, window_id: WindowId) {
+        unsafe {
+            SDL_ShowWindow(window_id.raw() as *mut SDL_Window);
+        }
+        #[cfg(target_os = "linux")]
+        x11::complete_startup(window_id);
+    }
+    fn hide_window(&mut self, window_id: WindowId) {
+        unsafe {
+            SDL_HideWindow(window_id.raw() as *mut SDL_Window);
+        }
+    }
+    fn focus_window(&mut self, window_id: WindowId) {
+        unsafe {
+            SDL_RaiseWindow(window_id.raw() as *mut SDL_Window);
+        }
+    }
+    // SDL_FlashWindow requires SDL 2.0.16, so on X11 the window manager is asked directly.
+    fn request_window_attention(&mut self, window_id: WindowId) {
+        #[cfg(target_os = "linux")]
+        x11::request_attention(window_id);
+        #[cfg(not(target_os = "linux"))]
+        let _ = window_id;
+    }
+    fn is_window_visible(&mut self, window_id: WindowId) -> bool {
+        window_has_flag(window_id, SDL_WINDOW_SHOWN)
+    }
+    fn is_window_focused(&mut self, window_id: WindowId) -> bool {
+        window_has_flag(window_id, SDL_WINDOW_INPUT_FOCUS)
+    }
+    fn is_window_minimized(&mut self, window_id: WindowId) -> bool {
+        window_has_flag(window_id, SDL_WINDOW_MINIMIZED)
+    }
+    fn is_window_maximized(&mut self, window_id: WindowId) -> bool {
+        window_has_flag(window_id, SDL_WINDOW_MAXIMIZED)
+    }
+    fn set_window_min_size(&mut self, window_id: WindowId, size: Option<(u32, u32)>) {
+        unsafe {
+            set_minimum_size(window_id.raw() as *mut SDL_Window, size);
+        }
+    }
+    fn set_window_max_size(&mut self, window_id: WindowId, size: Option<(u32, u32)>) {
+        unsafe {
+            set_maximum_size(window_id.raw() as *mut SDL_Window, size);
+        }
+    }
+    fn set_window_resizable(&mut self, window_id: WindowId, resizable: bool) {
+        unsafe {
+            SDL_SetWindowResizable(
+                window_id.raw() as *mut SDL_Window,
+                if resizable { SDL_TRUE } else { SDL_FALSE },
+            );
+        }
+    }
+    fn set_window_decorations(&mut self, window_id: WindowId, decorations: bool) {
+        unsafe {
+            SDL_SetWindowBordered(
+                window_id.raw() as *mut SDL_Window,
+                if decorations { SDL_TRUE } else { SDL_FALSE },
+            );
+        }
+    }
+    // SDL has no way to constrain the aspect ratio or resize increments of a window,
+    // so on X11 they're set in the window manager hints directly.
+    fn set_window_aspect_ratio(&mut self, window_id: WindowId, ratio: Option<(u32, u32)>) {
+        #[cfg(target_os = "linux")]
+        x11::set_aspect_ratio(window_id, ratio);
+        #[cfg(not(target_os = "linux"))]
+        let _ = (window_id, ratio);
+    }
+    fn set_window_resize_increments(
+        &mut self,
+        window_id: WindowId,
+        increments: Option<(u32, u32)>,
+    ) {
+        #[cfg(target_os = "linux")]
+        x11::set_resize_increments(window_id, increments);
+        #[cfg(not(target_os = "linux"))]
+        let _ = (window_id, increments);
+    }
+    fn set_window_opacity(&mut self, window_id: WindowId, opacity: f64) {
+        unsafe {
+            SDL_SetWindowOpacity(window_id.raw() as *mut SDL_Window, opacity as f32);
+        }
+    }
+    fn set_window_hit_test(&mut self, window_id: WindowId, hit_test: Option<HitTest>) {
+        let callback: SDL_HitTest = if hit_test.is_some() {
+            Some(hit_test_callback)
+        } else {
+            None
+        };
+        HIT_TESTS.with(|h| match hit_test {
+            Some(hit_test) => h.borrow_mut().insert(window_id, hit_test),
+            None => h.borrow_mut().remove(&window_id),
+        });
+        unsafe {
+            SDL_SetWindowHitTest(
+                window_id.raw() as *mut SDL_Window,
+                callback,
+                std::ptr::null_mut(),
+            );
+        }
+    }
+    // SDL 2.0.14 can't start a move or resize outside of its hit-test,
+    // so on X11 the window manager is asked directly.
+    fn begin_window_drag_move(&mut self, window_id: WindowId) {
+        #[cfg(target_os = "linux")]
+        x11::begin_move_resize(window_id, None);
+        #[cfg(not(target_os = "linux"))]
+        let _ = window_id;
+    }
+    fn begin_window_drag_resize(&mut self, window_id: WindowId, edge: ResizeEdge) {
+        #[cfg(target_os = "linux")]
+        x11::begin_move_resize(window_id, Some(edge));
+        #[cfg(not(target_os = "linux"))]
+        let _ = (window_id, edge);
+    }
     fn redraw_window(&mut self, window_id: WindowId) {
         redraw_manager::add_draw_request(window_id);
     }
@@ -122,10 +284,77 @@ impl PlatformApplicationTrait for PlatformApplication {
         }
     }
 
+    fn set_pointer_position(&mut self, window_id: WindowId, x: f64, y: f64) {
+        unsafe {
+            // SDL warps the pointer in logical coordinates.
+            let window = window_id.raw() as *mut SDL_Window;
+            let scale = logical_scale(window);
+            SDL_WarpMouseInWindow(window, (x * scale) as i32, (y * scale) as i32);
+        }
+    }
+    fn confine_pointer(&mut self, window_id: WindowId, confine: bool) {
+        unsafe {
+            SDL_SetWindowGrab(
+                window_id.raw() as *mut SDL_Window,
+                if confine { SDL_TRUE } else { SDL_FALSE },
+            );
+        }
+        CONFINED_WINDOW.with(|w| {
+            if confine {
+                w.set(Some(window_id));
+            } else if w.get() == Some(window_id) {
+                w.set(None);
+            }
+        });
+    }
+    // SDL's relative mouse mode applies to whichever window has focus,
+    // so the lock is released when the locked window loses focus.
+    fn lock_pointer(&mut self, window_id: WindowId) {
+        unsafe {
+            SDL_SetRelativeMouseMode(SDL_TRUE);
+        }
+        LOCKED_WINDOW.with(|w| w.set(Some(window_id)));
+    }
+    fn unlock_pointer(&mut self, window_id: WindowId) {
+        if LOCKED_WINDOW.with(|w| w.get()) == Some(window_id) {
+            unsafe {
+                SDL_SetRelativeMouseMode(SDL_FALSE);
+            }
+            LOCKED_WINDOW.with(|w| w.set(None));
+        }
+    }
+    // SDL only reports unaccelerated motion, from XInput2 or Windows raw input, while in
+    // relative mouse mode, so `RawMouseMotion` is only sent while the pointer is locked.
+    // If relative mouse mode is emulated by warping the cursor the motion is accelerated.
+    fn set_raw_mouse_motion(&mut self, enabled: bool) {
+        RAW_MOUSE_MOTION.with(|r| r.set(enabled));
+    }
+
     fn new_window(&mut self, window_parameters: &WindowParameters) -> WindowId {
-        let (x, y) = window_parameters.position.unwrap_or((
-            SDL_WINDOWPOS_UNDEFINED as u32,
-            SDL_WINDOWPOS_UNDEFINED as u32,
+        let mut position = window_parameters
+            .position
+            .map(|(x, y)| (x as i32, y as i32));
+
+        // Popups and tooltips are positioned relative to their parent.
+        if let (Some((x, y)), Some(parent)) = (position, window_parameters.parent) {
+            if matches!(
+                window_parameters.kind,
+                WindowKind::Popup | WindowKind::Tooltip
+            ) {
+                let (mut parent_x, mut parent_y) = (0, 0);
+                unsafe {
+                    SDL_GetWindowPosition(
+                        parent.raw() as *mut SDL_Window,
+                        &mut parent_x,
+                        &mut parent_y,
+                    );
+                }
+                position = Some((parent_x + x, parent_y + y));
+            }
+        }
+        let (x, y) = position.unwrap_or((
+            SDL_WINDOWPOS_UNDEFINED as i32,
+            SDL_WINDOWPOS_UNDEFINED as i32,
         ));
 
         // TODO: Width and height are presently incorrect as SDL interprets them as logical pixels.
@@ -139,45 +368,179 @@ impl PlatformApplicationTrait for PlatformApplication {
         if window_parameters.resizable {
             flags |= SDL_WINDOW_RESIZABLE;
         }
-
-        if window_parameters.resizable {
-            flags |= SDL_WINDOW_RESIZABLE;
+        if window_parameters.without_titlebar {
+            flags |= SDL_WINDOW_BORDERLESS;
+        }
+        if !window_parameters.visible {
+            flags |= SDL_WINDOW_HIDDEN;
+        }
+        // Only supported by SDL on X11.
+        if window_parameters.always_on_top {
+            flags |= SDL_WINDOW_ALWAYS_ON_TOP;
         }
+        // SDL only uses these flags on X11, where popups and tooltips are also undecorated.
+        flags |= match window_parameters.kind {
+            WindowKind::Popup => SDL_WINDOW_POPUP_MENU | SDL_WINDOW_SKIP_TASKBAR,
+            WindowKind::Tooltip => SDL_WINDOW_TOOLTIP | SDL_WINDOW_SKIP_TASKBAR,
+            WindowKind::Utility => SDL_WINDOW_UTILITY | SDL_WINDOW_SKIP_TASKBAR,
+            WindowKind::Splash => SDL_WINDOW_BORDERLESS,
+            WindowKind::Normal | WindowKind::Dialog => SDL_WindowFlags(0),
+        };
         unsafe {
+            // SDL 2.0.14 has no transparent window flag.
+            // Requesting alpha bits lets SDL pick an OpenGL surface with an alpha channel,
+            // which a compositor can blend with what's behind the window where that's supported.
+            SDL_GL_SetAttribute(
+                SDL_GL_ALPHA_SIZE,
+                if window_parameters.transparent { 8 } else { 0 },
+            );
+            // X11 windows also need a visual with an alpha channel.
+            // SDL ignores the hint when it's empty, so it's cleared for the next window.
+            #[cfg(target_os = "linux")]
+            let visual_id = if window_parameters.transparent {
+                x11::transparent_visual_id()
+            } else {
+                None
+            };
+            #[cfg(target_os = "linux")]
+            if let Some(visual_id) = visual_id {
+                let visual_id = CString::new(visual_id.to_string()).unwrap();
+                SDL_SetHint(VISUAL_ID_HINT.as_ptr().cast(), visual_id.as_ptr());
+            }
             let window = SDL_CreateWindow(
                 b"demo\0".as_ptr().cast(),
-                x as i32,
-                y as i32,
+                x,
+                y,
                 (width / 2) as i32,
                 (height / 2) as i32,
                 flags.0,
             );
-
-            // How can min / max sizes be unset later?
-            if let Some((min_width, min_height)) = window_parameters.minimum_size {
-                SDL_SetWindowMinimumSize(window, min_width as i32, min_height as i32)
+            #[cfg(target_os = "linux")]
+            if visual_id.is_some() {
+                SDL_SetHint(VISUAL_ID_HINT.as_ptr().cast(), b"\0".as_ptr().cast());
             }
 
-            if let Some((max_width, max_height)) = window_parameters.maximum_size {
-                SDL_SetWindowMaximumSize(window, max_width as i32, max_height as i32)
+            if window_parameters.minimum_size.is_some() {
+                set_minimum_size(window, window_parameters.minimum_size);
+            }
+            if window_parameters.maximum_size.is_some() {
+                set_maximum_size(window, window_parameters.maximum_size);
             }
 
             let c_string = std::ffi::CString::new(window_parameters.title.clone()).unwrap();
             SDL_SetWindowTitle(window, c_string.as_ptr());
 
+            // Only supported by SDL on X11.
+            if let (WindowKind::Dialog, Some(parent)) =
+                (window_parameters.kind, window_parameters.parent)
+            {
+                SDL_SetWindowModalFor(window, parent.raw() as *mut SDL_Window);
+            }
+
+            // SDL 2.0.14 has no way to show a window without focusing it,
+            // but new windows may not be focused without raising them.
+            // Popups and tooltips leave focus with their parent.
+            let takes_focus = !matches!(
+                window_parameters.kind,
+                WindowKind::Popup | WindowKind::Tooltip
+            );
+            if window_parameters.visible && window_parameters.focused && takes_focus {
+                SDL_RaiseWindow(window);
+            }
+
             let window_id = WindowId::new(window as *mut c_void);
             // When a window is created immediately request that it should redraw
             redraw_manager::add_draw_request(window_id);
 
+            // Startup is complete once the first window is shown.
+            #[cfg(target_os = "linux")]
+            if window_parameters.visible {
+                x11::complete_startup(window_id);
+            }
+
             window_id
         }
     }
 
-    fn quit(&self) {
+    // SDL is shut down when the PlatformApplication is dropped, after the event loop
+    // has sent `Event::Quit` and dropped the callback.
+    fn quit(&self, exit_code: i32) {
+        EXIT_CODE.with(|c| c.set(Some(exit_code)));
+    }
+
+    fn set_control_flow(&mut self, control_flow: ControlFlow) {
+        CONTROL_FLOW.with(|c| c.set(control_flow));
+    }
+
+    fn message_box(&mut self, message_box: &MessageBox) -> Option<MessageBoxButton> {
+        let flags = match message_box.kind {
+            MessageBoxKind::Info => SDL_MESSAGEBOX_INFORMATION,
+            MessageBoxKind::Warning => SDL_MESSAGEBOX_WARNING,
+            MessageBoxKind::Error => SDL_MESSAGEBOX_ERROR,
+        };
+
+        let buttons = message_box.buttons.buttons();
+        let dismiss_button = message_box.buttons.dismiss_button();
+        let labels: Vec<CString> = buttons
+            .iter()
+            .map(|b| CString::new(b.label()).unwrap())
+            .collect();
+        let button_data: Vec<SDL_MessageBoxButtonData> = buttons
+            .iter()
+            .zip(labels.iter())
+            .enumerate()
+            .map(|(i, (button, label))| {
+                let mut flags = 0;
+                if i == 0 {
+                    flags |= SDL_MESSAGEBOX_BUTTON_RETURNKEY_DEFAULT.0;
+                }
+                if *button == dismiss_button {
+                    flags |= SDL_MESSAGEBOX_BUTTON_ESCAPEKEY_DEFAULT.0;
+                }
+                SDL_MessageBoxButtonData {
+                    flags,
+                    buttonid: i as i32,
+                    text: label.as_ptr(),
+                }
+            })
+            .collect();
+
+        let title = CString::new(message_box.title.as_str()).ok()?;
+        let text = CString::new(message_box.text.as_str()).ok()?;
+        let data = SDL_MessageBoxData {
+            flags: flags.0,
+            window: std::ptr::null_mut(),
+            title: title.as_ptr(),
+            message: text.as_ptr(),
+            numbuttons: button_data.len() as i32,
+            buttons: button_data.as_ptr(),
+            colorScheme: std::ptr::null(),
+        };
+
+        // `SDL_ShowMessageBox` runs its own loop. Events for kapp's windows stay in SDL's
+        // queue until the event loop processes them.
+        let mut button_id = -1;
         unsafe {
-            SDL_Quit();
-            ACTUALLY_QUIT.with(|b| b.set(true));
+            if SDL_ShowMessageBox(&data, &mut button_id) != 0 {
+                return None;
+            }
         }
+
+        // A `button_id` of -1 means the message box was closed without pressing a button.
+        Some(
+            buttons
+                .get(button_id as usize)
+                .copied()
+                .unwrap_or(dismiss_button),
+        )
+    }
+
+    fn open_file_dialog(&mut self, id: FileDialogId, options: &FileDialogOptions) {
+        file_dialog(id, options.clone(), false);
+    }
+
+    fn save_file_dialog(&mut self, id: FileDialogId, options: &FileDialogOptions) {
+        file_dialog(id, options.clone(), true);
     }
 
     fn set_cursor(&mut self, cursor: Cursor) {
@@ -282,34 +645,52 @@ impl PlatformApplicationTrait for PlatformApplication {
         }
     }
 
-    fn start_text_input(&mut self) {
+    fn start_text_input(&mut self, window_id: Option<WindowId>) {
+        text_input_manager::start_text_input(window_id);
         unsafe {
-            SDL_StartTextInput();
+            update_text_input();
         }
     }
 
-    fn end_text_input(&mut self) {
+    fn end_text_input(&mut self, window_id: Option<WindowId>) {
+        text_input_manager::end_text_input(window_id);
         unsafe {
-            SDL_StopTextInput();
+            update_text_input();
+        }
+    }
+
+    fn key_label(&self, physical_key: Key) -> Option<String> {
+        if physical_key == Key::Unknown {
+            return None;
+        }
+        // There are 512 scancodes (SDL_NUM_SCANCODES).
+        let scancode = (0..512)
+            .map(|scancode| SDL_Scancode(scancode as _))
+            .find(|scancode| scancode_to_key(*scancode) == physical_key)?;
+        unsafe {
+            let keycode = SDL_GetKeyFromScancode(scancode);
+            let name = CStr::from_ptr(SDL_GetKeyName(keycode)).to_string_lossy();
+            if name.is_empty() {
+                None
+            } else {
+                Some(name.into_owned())
+            }
         }
     }
 
     fn set_text_input_rectangle(
         &mut self,
-        _window_id: WindowId,
+        window_id: WindowId,
         x: f64,
         y: f64,
         width: f64,
         height: f64,
     ) {
-        let mut rectangle = SDL_Rect {
-            x: x as c_int,
-            y: y as c_int,
-            w: width as c_int,
-            h: height as c_int,
-        };
+        TEXT_INPUT_RECTANGLES.with(|r| {
+            r.borrow_mut().insert(window_id, (x, y, width, height));
+        });
         unsafe {
-            SDL_SetTextInputRect(&mut rectangle);
+            update_text_input();
         }
     }
 }
@@ -327,17 +708,306 @@ impl Drop for PlatformApplication {
 }
 
 thread_local! {
-    static ACTUALLY_QUIT: Cell<bool> = Cell::new(false);
+    // Set when quit is called.
+    static EXIT_CODE: Cell<Option<i32>> = Cell::new(None);
+    static CONTROL_FLOW: Cell<ControlFlow> = Cell::new(ControlFlow::Wait);
+    // An in progress multi-finger gesture.
+    static ACTIVE_GESTURE: RefCell<Option<TouchGesture>> = RefCell::new(None);
+    // SDL_MULTIGESTURE has no window, so it uses the window of the finger events that caused it.
+    static LAST_TOUCH_WINDOW: Cell<Option<WindowId>> = Cell::new(None);
+    static LAST_MODIFIERS: Cell<Modifiers> = Cell::new(Modifiers::empty());
+    // Windows with a pointer lock or confinement that should be released when they lose focus.
+    static LOCKED_WINDOW: Cell<Option<WindowId>> = Cell::new(None);
+    static CONFINED_WINDOW: Cell<Option<WindowId>> = Cell::new(None);
+    static RAW_MOUSE_MOTION: Cell<bool> = Cell::new(false);
+    static TEXT_INPUT_RECTANGLES: RefCell<HashMap<WindowId, (f64, f64, f64, f64)>> = RefCell::new(HashMap::new());
+    static HIT_TESTS: RefCell<HashMap<WindowId, HitTest>> = RefCell::new(HashMap::new());
+}
+
+// Results are pushed from the threads that wait on file dialogs and sent from the event loop
+// when the SDL_USEREVENT pushed alongside them is processed.
+static FILE_DIALOG_RESULTS: Mutex<Vec<(FileDialogId, Vec<PathBuf>)>> = Mutex::new(Vec::new());
+// Pushed by `PlatformEventLoopProxy` the same way.
+static USER_EVENTS: Mutex<Vec<UserEvent>> = Mutex::new(Vec::new());
+
+// SDL_HINT_VIDEO_X11_WINDOW_VISUALID, which SDL reads when it creates an X11 window.
+#[cfg(target_os = "linux")]
+const VISUAL_ID_HINT: &[u8] = b"SDL_VIDEO_X11_WINDOW_VISUALID\0";
+
+// `code`s for the SDL_USEREVENTs kapp pushes.
+const FILE_DIALOG_RESULT: i32 = 0;
+const USER_EVENT: i32 = 1;
+
+/// SDL has no file dialogs, so on Linux the XDG desktop portal is used instead.
+/// The portal blocks until the dialog is closed, so it's waited on from another thread.
+fn file_dialog(id: FileDialogId, options: FileDialogOptions, save: bool) {
+    #[cfg(target_os = "linux")]
+    std::thread::spawn(move || {
+        let paths = portal::file_dialog(&options, save);
+        send_file_dialog_result(id, paths);
+    });
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (options, save);
+        send_file_dialog_result(id, Vec::new());
+    }
+}
+
+/// May be called from any thread.
+fn send_file_dialog_result(id: FileDialogId, paths: Vec<PathBuf>) {
+    FILE_DIALOG_RESULTS.lock().unwrap().push((id, paths));
+    push_user_event(FILE_DIALOG_RESULT);
+}
+
+/// SDL_PushEvent is thread safe and wakes SDL_WaitEvent.
+fn push_user_event(code: i32) {
+    unsafe {
+        let mut event: SDL_Event = std::mem::zeroed();
+        event.type_ = SDL_USEREVENT;
+        event.user.code = code;
+        SDL_PushEvent(&mut event);
+    }
+}
+
+/// Converts an SDL_Keymod to `Modifiers`. The masks are from SDL_keycode.h.
+fn modifiers_from_sdl(keymod: u32) -> Modifiers {
+    let mut modifiers = Modifiers::empty();
+    modifiers.set(Modifiers::SHIFT, keymod & 0x0003 != 0);
+    modifiers.set(Modifiers::CONTROL, keymod & 0x00C0 != 0);
+    modifiers.set(Modifiers::ALT, keymod & 0x0300 != 0);
+    modifiers.set(Modifiers::SUPER, keymod & 0x0C00 != 0);
+    modifiers.set(Modifiers::NUM_LOCK, keymod & 0x1000 != 0);
+    modifiers.set(Modifiers::CAPS_LOCK, keymod & 0x2000 != 0);
+    modifiers
+}
+
+/// Sends `ModifiersChanged` if the modifiers differ from the last ones seen.
+fn update_modifiers(callback: &mut Box<dyn FnMut(Event)>, modifiers: Modifiers) -> Modifiers {
+    if LAST_MODIFIERS.with(|m| m.replace(modifiers)) != modifiers {
+        callback(Event::ModifiersChanged { modifiers });
+    }
+    modifiers
+}
+
+/// SDL reports the text produced by a key press as a separate SDL_TEXTINPUT event
+/// queued directly after the SDL_KEYDOWN, but only while text input is enabled.
+unsafe fn peek_key_text() -> Option<String> {
+    let mut next_event = std::mem::zeroed::<SDL_Event>();
+    let count = SDL_PeepEvents(
+        &mut next_event,
+        1,
+        SDL_PEEKEVENT,
+        SDL_FIRSTEVENT.0 as u32,
+        SDL_LASTEVENT.0 as u32,
+    );
+    if count == 1 && next_event.type_ == SDL_TEXTINPUT {
+        let text = CStr::from_ptr(next_event.text.text.as_ptr())
+            .to_str()
+            .ok()?;
+        if !text.is_empty() && !text.chars().any(char::is_control) {
+            return Some(text.to_string());
+        }
+    }
+    None
+}
+
+unsafe fn window_from_id(id: u32) -> WindowId {
+    WindowId::new(SDL_GetWindowFromID(id) as *mut c_void)
+}
+
+fn char_to_byte_offset(string: &str, characters: usize) -> usize {
+    string
+        .char_indices()
+        .nth(characters)
+        .map_or(string.len(), |(i, _)| i)
+}
+
+/// Called by SDL to find which part of a window is under the pointer.
+unsafe extern "C" fn hit_test_callback(
+    window: *mut SDL_Window,
+    area: *const SDL_Point,
+    _data: *mut c_void,
+) -> SDL_HitTestResult {
+    // SDL passes logical coordinates but the hit-test expects physical coordinates.
+    let (mut logical_width, mut logical_height) = (0, 0);
+    let (mut physical_width, mut physical_height) = (0, 0);
+    SDL_GetWindowSize(window, &mut logical_width, &mut logical_height);
+    SDL_GL_GetDrawableSize(window, &mut physical_width, &mut physical_height);
+    let scale = if logical_width > 0 {
+        physical_width as f64 / logical_width as f64
+    } else {
+        1.0
+    };
+
+    let window_id = WindowId::new(window as *mut c_void);
+    let result = HIT_TESTS.with(|h| {
+        h.borrow()
+            .get(&window_id)
+            .map(|hit_test| hit_test((*area).x as f64 * scale, (*area).y as f64 * scale))
+    });
+    match result.unwrap_or(HitTestResult::Client) {
+        HitTestResult::Client => SDL_HITTEST_NORMAL,
+        HitTestResult::Caption => SDL_HITTEST_DRAGGABLE,
+        HitTestResult::ResizeNorth => SDL_HITTEST_RESIZE_TOP,
+        HitTestResult::ResizeSouth => SDL_HITTEST_RESIZE_BOTTOM,
+        HitTestResult::ResizeEast => SDL_HITTEST_RESIZE_RIGHT,
+        HitTestResult::ResizeWest => SDL_HITTEST_RESIZE_LEFT,
+        HitTestResult::ResizeNorthEast => SDL_HITTEST_RESIZE_TOPRIGHT,
+        HitTestResult::ResizeNorthWest => SDL_HITTEST_RESIZE_TOPLEFT,
+        HitTestResult::ResizeSouthEast => SDL_HITTEST_RESIZE_BOTTOMRIGHT,
+        HitTestResult::ResizeSouthWest => SDL_HITTEST_RESIZE_BOTTOMLEFT,
+    }
+}
+
+fn window_has_flag(window_id: WindowId, flag: SDL_WindowFlags) -> bool {
+    unsafe { SDL_GetWindowFlags(window_id.raw() as *mut SDL_Window) & flag.0 != 0 }
+}
+
+/// The size of a physical pixel in the window's logical coordinates,
+/// which SDL uses for window sizes and positions.
+unsafe fn logical_scale(window: *mut SDL_Window) -> f64 {
+    let (mut logical_width, mut logical_height) = (0, 0);
+    let (mut physical_width, mut physical_height) = (0, 0);
+    SDL_GetWindowSize(window, &mut logical_width, &mut logical_height);
+    SDL_GL_GetDrawableSize(window, &mut physical_width, &mut physical_height);
+    if physical_width > 0 {
+        logical_width as f64 / physical_width as f64
+    } else {
+        1.0
+    }
+}
+
+/// Sets the minimum size of a window's content area from a physical size.
+unsafe fn set_minimum_size(window: *mut SDL_Window, size: Option<(u32, u32)>) {
+    // SDL rejects a minimum size of zero, so the smallest valid size removes the limit.
+    // Rounded up so the window can't be smaller than the physical minimum.
+    let scale = logical_scale(window);
+    let (width, height) = size.map_or((1., 1.), |(width, height)| {
+        (
+            (width as f64 * scale).ceil(),
+            (height as f64 * scale).ceil(),
+        )
+    });
+    SDL_SetWindowMinimumSize(window, width.max(1.) as i32, height.max(1.) as i32);
+}
+
+/// Sets the maximum size of a window's content area from a physical size.
+unsafe fn set_maximum_size(window: *mut SDL_Window, size: Option<(u32, u32)>) {
+    // SDL rejects a maximum size of zero, so the largest valid size removes the limit.
+    // Rounded down so the window can't be larger than the physical maximum.
+    let scale = logical_scale(window);
+    let (width, height) = size.map_or((f64::MAX, f64::MAX), |(width, height)| {
+        (
+            (width as f64 * scale).floor(),
+            (height as f64 * scale).floor(),
+        )
+    });
+    SDL_SetWindowMaximumSize(window, width.max(1.) as i32, height.max(1.) as i32);
+}
+
+/// SDL's text input is shared by all windows,
+/// so it's enabled only while a window with text input enabled has focus.
+unsafe fn update_text_input() {
+    let window = SDL_GetKeyboardFocus();
+    if window.is_null() {
+        return;
+    }
+    let window_id = WindowId::new(window as *mut c_void);
+    if !text_input_manager::text_input_enabled(window_id) {
+        SDL_StopTextInput();
+        return;
+    }
+
+    if let Some((x, y, width, height)) =
+        TEXT_INPUT_RECTANGLES.with(|r| r.borrow().get(&window_id).copied())
+    {
+        // SDL's text input rectangle is in logical coordinates.
+        let scale = logical_scale(window);
+        let mut rectangle = SDL_Rect {
+            x: (x * scale) as c_int,
+            y: (y * scale) as c_int,
+            w: (width * scale) as c_int,
+            h: (height * scale) as c_int,
+        };
+        SDL_SetTextInputRect(&mut rectangle);
+    }
+    SDL_StartTextInput();
+}
+
+/// Releases a window's pointer lock or confinement when the window loses focus.
+unsafe fn release_pointer(callback: &mut Box<dyn FnMut(Event)>, window_id: WindowId) {
+    let locked = LOCKED_WINDOW.with(|w| w.get()) == Some(window_id);
+    let confined = CONFINED_WINDOW.with(|w| w.get()) == Some(window_id);
+    if locked {
+        SDL_SetRelativeMouseMode(SDL_FALSE);
+        LOCKED_WINDOW.with(|w| w.set(None));
+    }
+    if confined {
+        SDL_SetWindowGrab(window_id.raw() as *mut SDL_Window, SDL_FALSE);
+        CONFINED_WINDOW.with(|w| w.set(None));
+    }
+    if locked || confined {
+        callback(Event::PointerLockLost { window_id });
+    }
+}
+
+/// Finger events report their window since SDL 2.0.12.
+/// Touch devices that aren't attached to a window, like some touchpads, report no window
+/// but move the mouse, so the window with mouse focus is used for them.
+unsafe fn touch_window(window_id: u32) -> Option<WindowId> {
+    let mut window = SDL_GetWindowFromID(window_id);
+    if window.is_null() {
+        window = SDL_GetMouseFocus();
+    }
+    if window.is_null() {
+        None
+    } else {
+        let window_id = WindowId::new(window as *mut c_void);
+        LAST_TOUCH_WINDOW.with(|w| w.set(Some(window_id)));
+        Some(window_id)
+    }
+}
+
+/// The mouse is always pointer 0 but SDL finger ids can be 0, so they're offset by one.
+fn finger_pointer_id(finger_id: SDL_FingerID) -> u64 {
+    (finger_id as u64).wrapping_add(1)
+}
+
+/// Converts normalized touch coordinates to coordinates within the window.
+unsafe fn finger_position(window_id: WindowId, x: f32, y: f32) -> (f64, f64) {
+    let mut width = 0;
+    let mut height = 0;
+    SDL_GetWindowSize(window_id.raw() as *mut SDL_Window, &mut width, &mut height);
+    ((x * width as f32) as f64, (y * height as f32) as f64)
+}
+
+/// The average distance of all fingers from a point in normalized touch coordinates.
+unsafe fn average_finger_distance(touch_id: SDL_TouchID, x: f32, y: f32) -> f32 {
+    let count = SDL_GetNumTouchFingers(touch_id);
+    let mut total = 0.0;
+    for i in 0..count {
+        let finger = SDL_GetTouchFinger(touch_id, i);
+        if !finger.is_null() {
+            let (delta_x, delta_y) = ((*finger).x - x, (*finger).y - y);
+            total += (delta_x * delta_x + delta_y * delta_y).sqrt();
+        }
+    }
+    if count > 0 {
+        total / count as f32
+    } else {
+        0.0
+    }
 }
 
 fn process_event(callback: &mut Box<dyn FnMut(Event)>, event: &SDL_Event) {
     unsafe {
+        let modifiers = LAST_MODIFIERS.with(|m| m.get());
         match event.type_ {
             SDL_QUIT => callback(Event::QuitRequested),
             SDL_WINDOWEVENT => {
                 let window_event = event.window;
-                let window_id =
-                    WindowId::new(SDL_GetWindowFromID(window_event.windowID) as *mut c_void);
+                let window_id = window_from_id(window_event.windowID);
                 match window_event.event {
                     SDL_WINDOWEVENT_MINIMIZED => callback(Event::WindowMinimized { window_id }),
                     SDL_WINDOWEVENT_MAXIMIZED => callback(Event::WindowMaximized { window_id }),
@@ -352,10 +1022,28 @@ fn process_event(callback: &mut Box<dyn FnMut(Event)>, event: &SDL_Event) {
                         y: window_event.data2 as u32,
                     }),
                     SDL_WINDOWEVENT_FOCUS_GAINED => {
-                        callback(Event::WindowGainedFocus { window_id })
+                        callback(Event::WindowGainedFocus { window_id });
+                        update_text_input();
+                        // Modifiers may have changed while another application had focus.
+                        update_modifiers(callback, modifiers_from_sdl(SDL_GetModState().0 as u32));
+                    }
+                    SDL_WINDOWEVENT_FOCUS_LOST => {
+                        callback(Event::WindowLostFocus { window_id });
+                        release_pointer(callback, window_id);
                     }
-                    SDL_WINDOWEVENT_FOCUS_LOST => callback(Event::WindowLostFocus { window_id }),
                     SDL_WINDOWEVENT_CLOSE => callback(Event::WindowCloseRequested { window_id }),
+                    SDL_WINDOWEVENT_ENTER => {
+                        // The mouse state is relative to the window with mouse focus, which is now this window.
+                        let mut x = 0;
+                        let mut y = 0;
+                        SDL_GetMouseState(&mut x, &mut y);
+                        callback(Event::PointerEntered {
+                            window_id,
+                            x: x as f64,
+                            y: y as f64,
+                        })
+                    }
+                    SDL_WINDOWEVENT_LEAVE => callback(Event::PointerLeft { window_id }),
                     // Presently SDL will block during resizing, which isn't ideal and doesn't match the other
                     // `kapp` platforms. There are ways to alleviate it, but investigation is required.
                     SDL_WINDOWEVENT_SIZE_CHANGED => callback(Event::WindowResized {
@@ -366,22 +1054,57 @@ fn process_event(callback: &mut Box<dyn FnMut(Event)>, event: &SDL_Event) {
                     _ => {}
                 }
             }
+            SDL_KEYMAPCHANGED => callback(Event::KeyboardLayoutChanged),
             SDL_KEYDOWN | SDL_KEYUP => {
                 let keyboard_event = event.key;
 
                 // Are milliseconds the correct units?
                 let timestamp = Duration::from_millis(keyboard_event.timestamp as u64);
 
-                let key = scancode_to_key(keyboard_event.keysym.scancode);
+                let physical_key = scancode_to_key(keyboard_event.keysym.scancode);
+                let key = match keycode_to_key(keyboard_event.keysym.sym) {
+                    Key::Unknown => physical_key,
+                    key => key,
+                };
+                let scancode = keyboard_event.keysym.scancode.0 as u32;
+                let window_id = window_from_id(keyboard_event.windowID);
+                let modifiers = update_modifiers(
+                    callback,
+                    modifiers_from_sdl(keyboard_event.keysym.mod_ as u32),
+                );
                 match keyboard_event.type_ {
                     SDL_KEYDOWN => {
+                        let text = peek_key_text();
                         if keyboard_event.repeat > 0 {
-                            callback(Event::KeyRepeat { key, timestamp })
+                            callback(Event::KeyRepeat {
+                                key,
+                                physical_key,
+                                scancode,
+                                text,
+                                window_id,
+                                modifiers,
+                                timestamp,
+                            })
                         } else {
-                            callback(Event::KeyDown { key, timestamp })
+                            callback(Event::KeyDown {
+                                key,
+                                physical_key,
+                                scancode,
+                                text,
+                                window_id,
+                                modifiers,
+                                timestamp,
+                            })
                         }
                     }
-                    SDL_KEYUP => callback(Event::KeyUp { key, timestamp }),
+                    SDL_KEYUP => callback(Event::KeyUp {
+                        key,
+                        physical_key,
+                        scancode,
+                        window_id,
+                        modifiers,
+                        timestamp,
+                    }),
                     _ => {}
                 }
             }
@@ -390,6 +1113,7 @@ fn process_event(callback: &mut Box<dyn FnMut(Event)>, event: &SDL_Event) {
 
                 // Are milliseconds the correct units?
                 let timestamp = Duration::from_millis(mouse_motion_event.timestamp as u64);
+                let window_id = window_from_id(mouse_motion_event.windowID);
                 let source = match mouse_motion_event.which {
                     SDL_TOUCH_MOUSEID => PointerSource::Touch,
                     _ => PointerSource::Mouse,
@@ -399,14 +1123,33 @@ fn process_event(callback: &mut Box<dyn FnMut(Event)>, event: &SDL_Event) {
                 callback(Event::MouseMotion {
                     delta_x: mouse_motion_event.xrel as f64,
                     delta_y: mouse_motion_event.yrel as f64,
+                    window_id,
                     timestamp,
                 });
-                callback(Event::PointerMoved {
-                    x: mouse_motion_event.x as f64,
-                    y: mouse_motion_event.y as f64,
-                    source,
-                    timestamp,
-                });
+                // SDL 2.0.14 rounds raw motion to whole pixels.
+                if source == PointerSource::Mouse
+                    && RAW_MOUSE_MOTION.with(|r| r.get())
+                    && SDL_GetRelativeMouseMode() == SDL_TRUE
+                {
+                    callback(Event::RawMouseMotion {
+                        delta_x: mouse_motion_event.xrel as f64,
+                        delta_y: mouse_motion_event.yrel as f64,
+                        window_id,
+                        timestamp,
+                    });
+                }
+                // Touches are sent separately by SDL_FINGERMOTION with a pointer_id per finger.
+                if source == PointerSource::Mouse {
+                    callback(Event::PointerMoved {
+                        x: mouse_motion_event.x as f64,
+                        y: mouse_motion_event.y as f64,
+                        source,
+                        pointer_id: 0,
+                        window_id,
+                        modifiers,
+                        timestamp,
+                    });
+                }
             }
             SDL_MOUSEBUTTONDOWN => {
                 let event = event.button;
@@ -418,6 +1161,7 @@ fn process_event(callback: &mut Box<dyn FnMut(Event)>, event: &SDL_Event) {
 
                 // Are milliseconds the correct units?
                 let timestamp = Duration::from_millis(event.timestamp as u64);
+                let window_id = window_from_id(event.windowID);
                 let button = match event.button as u32 {
                     SDL_BUTTON_LEFT => PointerButton::Primary,
                     SDL_BUTTON_MIDDLE => PointerButton::Auxillary,
@@ -427,25 +1171,32 @@ fn process_event(callback: &mut Box<dyn FnMut(Event)>, event: &SDL_Event) {
                     _ => PointerButton::Unknown,
                 };
 
-                callback(Event::PointerDown {
-                    x: event.x as f64,
-                    y: event.y as f64,
-                    source,
-                    button,
-                    timestamp,
-                });
+                if source == PointerSource::Mouse {
+                    callback(Event::PointerDown {
+                        x: event.x as f64,
+                        y: event.y as f64,
+                        source,
+                        button,
+                        pointer_id: 0,
+                        window_id,
+                        modifiers,
+                        timestamp,
+                    });
+                }
 
                 if event.clicks == 2 {
                     callback(Event::DoubleClickDown {
                         x: event.x as f64,
                         y: event.y as f64,
                         button,
+                        window_id,
                         timestamp,
                     });
                     callback(Event::DoubleClick {
                         x: event.x as f64,
                         y: event.y as f64,
                         button,
+                        window_id,
                         timestamp,
                     });
                 }
@@ -460,6 +1211,7 @@ fn process_event(callback: &mut Box<dyn FnMut(Event)>, event: &SDL_Event) {
 
                 // Are milliseconds the correct units?
                 let timestamp = Duration::from_millis(event.timestamp as u64);
+                let window_id = window_from_id(event.windowID);
                 let button = match event.button as u32 {
                     SDL_BUTTON_LEFT => PointerButton::Primary,
                     SDL_BUTTON_MIDDLE => PointerButton::Auxillary,
@@ -468,18 +1220,24 @@ fn process_event(callback: &mut Box<dyn FnMut(Event)>, event: &SDL_Event) {
                     SDL_BUTTON_X2 => PointerButton::Extra2,
                     _ => PointerButton::Unknown,
                 };
-                callback(Event::PointerUp {
-                    x: event.x as f64,
-                    y: event.y as f64,
-                    source,
-                    button,
-                    timestamp,
-                });
+                if source == PointerSource::Mouse {
+                    callback(Event::PointerUp {
+                        x: event.x as f64,
+                        y: event.y as f64,
+                        source,
+                        button,
+                        pointer_id: 0,
+                        window_id,
+                        modifiers,
+                        timestamp,
+                    });
+                }
                 if event.clicks == 2 {
                     callback(Event::DoubleClickUp {
                         x: event.x as f64,
                         y: event.y as f64,
                         button,
+                        window_id,
                         timestamp,
                     });
                 }
@@ -494,64 +1252,259 @@ fn process_event(callback: &mut Box<dyn FnMut(Event)>, event: &SDL_Event) {
                     delta_y *= -1.0;
                 }
 
-                let window_id = WindowId::new(SDL_GetWindowFromID(event.windowID) as *mut c_void);
+                let window_id = window_from_id(event.windowID);
                 let timestamp = Duration::from_millis(event.timestamp as u64);
 
+                // SDL 2.0.18 adds fractional `preciseX` and `preciseY` values,
+                // but the SDL version used here only reports whole lines.
                 callback(Event::Scroll {
-                    delta_x,
-                    delta_y,
+                    delta: ScrollDelta::Lines {
+                        x: delta_x,
+                        y: delta_y,
+                    },
+                    phase: ScrollPhase::Unknown,
                     window_id,
+                    modifiers,
                     timestamp,
                 });
             }
+            // SDL only reports multi-finger gestures from touchscreens, not touchpads.
+            SDL_MULTIGESTURE => {
+                let gesture_event = event.mgesture;
+                let timestamp = Duration::from_millis(gesture_event.timestamp as u64);
+                let window_id = match LAST_TOUCH_WINDOW.with(|w| w.get()) {
+                    Some(window_id) => window_id,
+                    None => return,
+                };
+
+                let center = finger_position(window_id, gesture_event.x, gesture_event.y);
+
+                // SDL reports the change in the fingers' average distance from their center.
+                // Convert that to a change in scale relative to the previous distance.
+                let distance = average_finger_distance(
+                    gesture_event.touchId,
+                    gesture_event.x,
+                    gesture_event.y,
+                );
+                let previous_distance = distance - gesture_event.dDist;
+                let scale = if previous_distance > 0.0 {
+                    gesture_event.dDist / previous_distance
+                } else {
+                    0.0
+                };
+
+                let events = ACTIVE_GESTURE.with(|g| {
+                    g.borrow_mut()
+                        .get_or_insert_with(|| TouchGesture::new(window_id, center))
+                        .update(
+                            center,
+                            scale as f64,
+                            gesture_event.dTheta as f64,
+                            gesture_event.numFingers as u32,
+                            timestamp,
+                        )
+                });
+                for event in events {
+                    callback(event);
+                }
+            }
+            SDL_FINGERDOWN | SDL_FINGERMOTION => {
+                let finger_event = event.tfinger;
+                let window_id = match touch_window(finger_event.windowID) {
+                    Some(window_id) => window_id,
+                    None => return,
+                };
+                let (x, y) = finger_position(window_id, finger_event.x, finger_event.y);
+                let timestamp = Duration::from_millis(finger_event.timestamp as u64);
+                let pointer_id = finger_pointer_id(finger_event.fingerId);
+
+                if finger_event.type_ == SDL_FINGERDOWN {
+                    callback(Event::PointerDown {
+                        x,
+                        y,
+                        source: PointerSource::Touch,
+                        button: PointerButton::Primary,
+                        pointer_id,
+                        window_id,
+                        modifiers,
+                        timestamp,
+                    });
+                } else {
+                    callback(Event::PointerMoved {
+                        x,
+                        y,
+                        source: PointerSource::Touch,
+                        pointer_id,
+                        window_id,
+                        modifiers,
+                        timestamp,
+                    });
+                }
+            }
+            SDL_FINGERUP => {
+                let finger_event = event.tfinger;
+                if let Some(window_id) = touch_window(finger_event.windowID) {
+                    let (x, y) = finger_position(window_id, finger_event.x, finger_event.y);
+                    callback(Event::PointerUp {
+                        x,
+                        y,
+                        source: PointerSource::Touch,
+                        button: PointerButton::Primary,
+                        pointer_id: finger_pointer_id(finger_event.fingerId),
+                        window_id,
+                        modifiers,
+                        timestamp: Duration::from_millis(finger_event.timestamp as u64),
+                    });
+                }
+
+                // The lifted finger is no longer counted.
+                if SDL_GetNumTouchFingers(finger_event.touchId) < 2 {
+                    if let Some(gesture) = ACTIVE_GESTURE.with(|g| g.borrow_mut().take()) {
+                        let timestamp = Duration::from_millis(finger_event.timestamp as u64);
+                        for event in gesture.end(timestamp) {
+                            callback(event);
+                        }
+                    }
+                }
+            }
             SDL_TEXTINPUT => {
                 let c_str = CStr::from_ptr(event.text.text.as_ptr()).to_str().unwrap();
+                let window_id = window_from_id(event.text.windowID);
                 for character in c_str.chars() {
                     // Send a character received for each key.
-                    callback(Event::CharacterReceived { character });
+                    callback(Event::CharacterReceived {
+                        character,
+                        window_id,
+                    });
                 }
             }
+            // SDL sends empty text when the composition ends.
+            // The text is truncated to 32 bytes, which SDL 2.0.22 addresses with SDL_TEXTEDITING_EXT.
             SDL_TEXTEDITING => {
-                let c_str = CStr::from_ptr(event.text.text.as_ptr()).to_str().unwrap();
+                let composition = CStr::from_ptr(event.edit.text.as_ptr()).to_str().unwrap();
+                let window_id = window_from_id(event.edit.windowID);
+                if composition.is_empty() {
+                    callback(Event::IMEEndComposition { window_id });
+                    return;
+                }
+
+                // SDL's start and length are measured in characters.
+                let start = char_to_byte_offset(composition, event.edit.start as usize);
+                let end = char_to_byte_offset(
+                    composition,
+                    (event.edit.start + event.edit.length) as usize,
+                );
+                let selection = if end > start { Some(start..end) } else { None };
                 callback(Event::IMEComposition {
-                    composition: c_str.to_string(),
+                    composition: composition.to_string(),
+                    cursor: Some(start),
+                    segments: CompositionSegment::from_selection(composition.len(), &selection),
+                    selection,
+                    window_id,
                 });
             }
+            SDL_USEREVENT => {
+                if event.user.code == FILE_DIALOG_RESULT {
+                    // Taken before the callback so the lock isn't held while it runs.
+                    let results = std::mem::take(&mut *FILE_DIALOG_RESULTS.lock().unwrap());
+                    for (id, paths) in results {
+                        callback(Event::FileDialogResult { id, paths });
+                    }
+                }
+                if event.user.code == USER_EVENT {
+                    let events = std::mem::take(&mut *USER_EVENTS.lock().unwrap());
+                    for event in events {
+                        callback(Event::User(event));
+                    }
+                }
+            }
             _ => {}
         }
     }
 }
 
+#[derive(Clone)]
+pub struct PlatformEventLoopProxy {}
+
+impl PlatformEventLoopProxyTrait for PlatformEventLoopProxy {
+    fn send(&self, event: UserEvent) {
+        USER_EVENTS.lock().unwrap().push(event);
+        push_user_event(USER_EVENT);
+    }
+}
+
 pub struct PlatformEventLoop {}
 
 impl PlatformEventLoopTrait for PlatformEventLoop {
-    fn run(&self, mut callback: Box<dyn FnMut(Event)>) {
-        unsafe {
-            let mut event = std::mem::zeroed();
-            loop {
-                if ACTUALLY_QUIT.with(|b| b.get()) {
-                    break;
-                }
-                // Wait for a new event if we don't have any redraw requests
-                if redraw_manager::draw_requests_count() == 0 {
-                    SDL_WaitEvent(&mut event);
-                    process_event(&mut callback, &event);
-                }
+    fn run(&self, mut callback: Box<dyn FnMut(Event)>) -> i32 {
+        let exit_code = loop {
+            if let Some(exit_code) = EXIT_CODE.with(|c| c.get()) {
+                break exit_code;
+            }
+            if let Some(exit_code) = process_events(&mut callback, None) {
+                break exit_code;
+            }
+        };
 
-                // Process all events.
-                while SDL_PollEvent(&mut event) != 0 {
-                    process_event(&mut callback, &event);
-                }
+        callback(Event::Quit);
 
-                // When there are no events remaining, we're at the end of the event loop
-                callback(Event::EventsCleared);
+        // Dropping the callback drops the user's `Window`s, which destroys them before SDL_Quit.
+        drop(callback);
+        exit_code
+    }
+}
 
-                // Send a draw event for each window that needs to be drawn.
-                redraw_manager::begin_draw_flush();
-                while let Some(window_id) = redraw_manager::get_draw_request() {
-                    callback(Event::Draw { window_id });
-                }
+impl PlatformEventLoopPumpTrait for PlatformEventLoop {
+    fn pump_events(&self, timeout: Option<Duration>, mut callback: Box<dyn FnMut(Event)>) {
+        if quit_manager::pump_events(|| process_events(&mut callback, timeout).is_some()) {
+            callback(Event::Quit);
+        }
+    }
+}
+
+/// Runs one iteration of the event loop.
+/// Waits up to `timeout` for an event unless there are redraw requests or the control flow
+/// asks for a shorter wait.
+/// Returns the exit code if quit was called, in which case draws are skipped.
+fn process_events(callback: &mut Box<dyn FnMut(Event)>, timeout: Option<Duration>) -> Option<i32> {
+    unsafe {
+        let mut event = std::mem::zeroed();
+
+        // Wait for a new event if we don't have any redraw requests
+        if redraw_manager::draw_requests_count() == 0 {
+            let wait = CONTROL_FLOW.with(|c| c.get()).wait_duration(timeout);
+            let received = match wait {
+                // Rounded up so that a `WaitUntil` isn't woken just before its instant.
+                // A timeout of 0 polls without waiting.
+                Some(wait) => SDL_WaitEventTimeout(
+                    &mut event,
+                    ((wait.as_micros() + 999) / 1000).min(i32::MAX as u128) as i32,
+                ),
+                None => SDL_WaitEvent(&mut event),
+            };
+            if received != 0 {
+                process_event(callback, &event);
             }
         }
+
+        // Process all events.
+        while SDL_PollEvent(&mut event) != 0 {
+            process_event(callback, &event);
+        }
+
+        // When there are no events remaining, we're at the end of the event loop
+        callback(Event::EventsCleared);
+
+        // Quitting happens before any requested draws.
+        if let Some(exit_code) = EXIT_CODE.with(|c| c.get()) {
+            return Some(exit_code);
+        }
+
+        // Send a draw event for each window that needs to be drawn.
+        redraw_manager::begin_draw_flush();
+        while let Some(window_id) = redraw_manager::get_draw_request() {
+            callback(Event::Draw { window_id });
+        }
+        None
     }
 }
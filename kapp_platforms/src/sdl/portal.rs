@@ -0,0 +1,394 @@
+/// File dialogs through the XDG desktop portal's FileChooser interface.
+/// https://flatpak.github.io/xdg-desktop-portal/docs/doc-org.freedesktop.portal.FileChooser.html
+use super::dbus::{self, Connection, Message, Writer};
+use kapp_platform_common::FileDialogOptions;
+use std::os::unix::ffi::OsStringExt;
+use std::path::PathBuf;
+
+/// Shows a file dialog and blocks until it's closed.
+/// Returns the picked paths, or no paths if the dialog was cancelled or the portal isn't available.
+pub fn file_dialog(options: &FileDialogOptions, save: bool) -> Vec<PathBuf> {
+    request(options, save).unwrap_or_default()
+}
+
+fn request(options: &FileDialogOptions, save: bool) -> Result<Vec<PathBuf>, ()> {
+    let mut connection = Connection::session()?;
+
+    // The portal replies with a request object that emits `Response` when the dialog closes.
+    // Subscribe before calling so the `Response` can't be missed.
+    let mut add_match = Message::method_call(
+        "org.freedesktop.DBus",
+        "/org/freedesktop/DBus",
+        "org.freedesktop.DBus",
+        "AddMatch",
+    );
+    add_match.signature = "s".to_string();
+    let mut body = Writer::new();
+    body.string("type='signal',interface='org.freedesktop.portal.Request',member='Response'");
+    add_match.body = body.data;
+    connection.call(add_match)?;
+
+    let mut call = Message::method_call(
+        "org.freedesktop.portal.Desktop",
+        "/org/freedesktop/portal/desktop",
+        "org.freedesktop.portal.FileChooser",
+        if save { "SaveFile" } else { "OpenFile" },
+    );
+    call.signature = "ssa{sv}".to_string();
+    call.body = write_arguments(options, save);
+    let serial = connection.send(call)?;
+
+    // The `Response` may arrive before the reply if the portal is quick,
+    // so signals received before the reply are kept.
+    let mut responses = Vec::new();
+    let (handle, portal) = loop {
+        let message = connection.receive()?;
+        if message.reply_serial == Some(serial) {
+            if message.message_type != dbus::METHOD_RETURN {
+                return Err(());
+            }
+            break (message.reader().string()?, message.sender);
+        }
+        if message.message_type == dbus::SIGNAL && message.member == "Response" {
+            responses.push(message);
+        }
+    };
+
+    let mut responses = responses.into_iter();
+    let response = loop {
+        let message = match responses.next() {
+            Some(message) => message,
+            None => connection.receive()?,
+        };
+        // Only the portal that replied is trusted to respond.
+        if message.message_type == dbus::SIGNAL
+            && message.member == "Response"
+            && message.path == handle
+            && message.sender == portal
+        {
+            break message;
+        }
+    };
+
+    read_response(&response)
+}
+
+fn write_arguments(options: &FileDialogOptions, save: bool) -> Vec<u8> {
+    let mut body = Writer::new();
+
+    // No parent window is passed, so the portal can't make the dialog modal to a window.
+    body.string("");
+    body.string(&options.title);
+
+    let current_folder = options.current_folder.as_ref().map(|folder| {
+        let mut bytes = folder.clone().into_os_string().into_vec();
+        bytes.push(0);
+        bytes
+    });
+
+    let array = body.begin_array(8);
+    let mut entry = |key: &str, signature: &str, write: &dyn Fn(&mut Writer)| {
+        body.begin_struct();
+        body.string(key);
+        body.signature(signature);
+        write(&mut body);
+    };
+    // Each dialog uses its own connection, so the token doesn't need to be unique.
+    entry("handle_token", "s", &|w| w.string("kapp"));
+    if save {
+        if !options.current_name.is_empty() {
+            entry("current_name", "s", &|w| w.string(&options.current_name));
+        }
+    } else {
+        entry("multiple", "b", &|w| w.boolean(options.multiple));
+        entry("directory", "b", &|w| w.boolean(options.directory));
+    }
+    if !options.filters.is_empty() {
+        entry("filters", "a(sa(us))", &|w| {
+            let filters = w.begin_array(8);
+            for filter in &options.filters {
+                w.begin_struct();
+                w.string(&filter.name);
+                let patterns = w.begin_array(8);
+                for extension in &filter.extensions {
+                    // 0 is a glob pattern, 1 would be a MIME type.
+                    w.begin_struct();
+                    w.u32(0);
+                    w.string(&format!("*.{}", extension));
+                }
+                w.end_array(patterns);
+            }
+            w.end_array(filters);
+        });
+    }
+    if let Some(current_folder) = &current_folder {
+        entry("current_folder", "ay", &|w| {
+            let bytes = w.begin_array(1);
+            w.data.extend_from_slice(current_folder);
+            w.end_array(bytes);
+        });
+    }
+    body.end_array(array);
+    body.data
+}
+
+/// Reads the `(u response, a{sv} results)` arguments of a `Response` signal.
+fn read_response(response: &Message) -> Result<Vec<PathBuf>, ()> {
+    let mut reader = response.reader();
+
+    // 0 means success, 1 that the user cancelled and 2 that the dialog ended some other way.
+    if reader.u32()? != 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut paths = Vec::new();
+    let results_end = reader.begin_array(8)?;
+    while reader.position() < results_end {
+        reader.begin_struct()?;
+        let key = reader.string()?;
+        let signature = reader.signature()?;
+        if key == "uris" && signature == "as" {
+            let uris_end = reader.begin_array(4)?;
+            while reader.position() < uris_end {
+                if let Some(path) = path_from_uri(&reader.string()?) {
+                    paths.push(path);
+                }
+            }
+        } else {
+            reader.skip(&signature)?;
+        }
+    }
+    Ok(paths)
+}
+
+/// Only local `file://` URIs can be converted to paths.
+fn path_from_uri(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    // Skips an authority like "localhost".
+    let path = &path[path.find('/')?..];
+    Some(PathBuf::from(std::ffi::OsString::from_vec(
+        dbus::percent_decode(path),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kapp_platform_common::FileFilter;
+    use std::io::BufRead;
+    use std::process::{Child, Command, Stdio};
+    use std::sync::mpsc::Sender;
+
+    const URIS: &[&str] = &[
+        "file:///tmp/a%20b.png",
+        "file://localhost/home/user/%C3%A9.txt",
+        "https://example.com/c.png",
+    ];
+
+    fn expected_paths() -> Vec<PathBuf> {
+        vec![
+            PathBuf::from("/tmp/a b.png"),
+            PathBuf::from("/home/user/é.txt"),
+        ]
+    }
+
+    // The `(u response, a{sv} results)` body of a `Response` signal.
+    fn response_body(code: u32, uris: &[&str]) -> Vec<u8> {
+        let mut body = Writer::new();
+        body.u32(code);
+        let results = body.begin_array(8);
+        // Entries before `uris` have to be skipped.
+        body.begin_struct();
+        body.string("current_filter");
+        body.signature("(sa(us))");
+        body.begin_struct();
+        body.string("Images");
+        let patterns = body.begin_array(8);
+        body.begin_struct();
+        body.u32(0);
+        body.string("*.png");
+        body.end_array(patterns);
+        body.begin_struct();
+        body.string("uris");
+        body.signature("as");
+        let array = body.begin_array(4);
+        for uri in uris {
+            body.string(uri);
+        }
+        body.end_array(array);
+        body.end_array(results);
+        body.data
+    }
+
+    fn response(code: u32, uris: &[&str]) -> Message {
+        let mut message = Message::default();
+        message.message_type = dbus::SIGNAL;
+        message.member = "Response".to_string();
+        message.signature = "ua{sv}".to_string();
+        message.body = response_body(code, uris);
+        message
+    }
+
+    #[test]
+    fn paths_from_uris() {
+        assert_eq!(
+            path_from_uri("file:///tmp/a%20b.png"),
+            Some(PathBuf::from("/tmp/a b.png"))
+        );
+        assert_eq!(
+            path_from_uri("file://localhost/home/user/%C3%A9.txt"),
+            Some(PathBuf::from("/home/user/é.txt"))
+        );
+        assert_eq!(path_from_uri("file://localhost"), None);
+        assert_eq!(path_from_uri("https://example.com/c.png"), None);
+        assert_eq!(path_from_uri("/tmp/a.png"), None);
+    }
+
+    #[test]
+    fn read_response_paths() {
+        assert_eq!(read_response(&response(0, URIS)), Ok(expected_paths()));
+    }
+
+    #[test]
+    fn cancelled_responses_have_no_paths() {
+        // 1 is cancelled by the user and 2 is ended some other way.
+        for code in [1, 2] {
+            assert_eq!(read_response(&response(code, URIS)), Ok(Vec::new()));
+        }
+    }
+
+    // A dbus-daemon running a private session bus, stopped when dropped.
+    struct Bus(Child);
+
+    impl Drop for Bus {
+        fn drop(&mut self) {
+            let _ = self.0.kill();
+            let _ = self.0.wait();
+        }
+    }
+
+    fn start_bus() -> Option<(Bus, String)> {
+        let child = Command::new("dbus-daemon")
+            .args(["--session", "--nofork", "--print-address"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+        let mut bus = Bus(child);
+        let mut address = String::new();
+        std::io::BufReader::new(bus.0.stdout.take()?)
+            .read_line(&mut address)
+            .ok()?;
+        Some((bus, address.trim().to_string()))
+    }
+
+    /// Stands in for the FileChooser portal. For each call it replies with a request handle
+    /// and emits `Response` from it, before the reply if `respond_early` is set.
+    /// Returns the methods that were called.
+    fn serve_file_chooser(ready: Sender<()>, respond_early: &[bool]) -> Vec<String> {
+        let mut connection = Connection::session().unwrap();
+        let mut request_name = Message::method_call(
+            "org.freedesktop.DBus",
+            "/org/freedesktop/DBus",
+            "org.freedesktop.DBus",
+            "RequestName",
+        );
+        request_name.signature = "su".to_string();
+        let mut body = Writer::new();
+        body.string("org.freedesktop.portal.Desktop");
+        body.u32(4); // DBUS_NAME_FLAG_DO_NOT_QUEUE
+        request_name.body = body.data;
+        let reply = connection.call(request_name).unwrap();
+        assert_eq!(reply.reader().u32(), Ok(1)); // DBUS_REQUEST_NAME_REPLY_PRIMARY_OWNER
+        ready.send(()).unwrap();
+
+        let mut methods = Vec::new();
+        for &respond_early in respond_early {
+            let call = loop {
+                let message = connection.receive().unwrap();
+                if message.message_type == dbus::METHOD_CALL
+                    && message.interface == "org.freedesktop.portal.FileChooser"
+                {
+                    break message;
+                }
+            };
+
+            let mut reader = call.reader();
+            let _parent_window = reader.string().unwrap();
+            assert_eq!(reader.string(), Ok("Pick".to_string()));
+            let mut token = String::new();
+            let options_end = reader.begin_array(8).unwrap();
+            while reader.position() < options_end {
+                reader.begin_struct().unwrap();
+                let key = reader.string().unwrap();
+                let signature = reader.signature().unwrap();
+                if key == "handle_token" {
+                    token = reader.string().unwrap();
+                } else {
+                    reader.skip(&signature).unwrap();
+                }
+            }
+
+            let sender = call.sender.trim_start_matches(':').replace('.', "_");
+            let handle = format!(
+                "/org/freedesktop/portal/desktop/request/{}/{}",
+                sender, token
+            );
+
+            let mut reply = Message::default();
+            reply.message_type = dbus::METHOD_RETURN;
+            reply.reply_serial = Some(call.serial);
+            reply.destination = call.sender.clone();
+            reply.signature = "o".to_string();
+            let mut body = Writer::new();
+            body.string(&handle);
+            reply.body = body.data;
+
+            let mut signal = response(0, URIS);
+            signal.path = handle;
+            signal.interface = "org.freedesktop.portal.Request".to_string();
+
+            if respond_early {
+                connection.send(signal).unwrap();
+                connection.send(reply).unwrap();
+            } else {
+                connection.send(reply).unwrap();
+                connection.send(signal).unwrap();
+            }
+            methods.push(call.member);
+        }
+        methods
+    }
+
+    #[test]
+    fn file_dialog_through_a_private_bus() {
+        let (_bus, address) = match start_bus() {
+            Some(bus) => bus,
+            None => {
+                eprintln!("dbus-daemon isn't available, skipping");
+                return;
+            }
+        };
+        std::env::set_var("DBUS_SESSION_BUS_ADDRESS", &address);
+
+        let (ready_sender, ready) = std::sync::mpsc::channel();
+        let portal = std::thread::spawn(move || serve_file_chooser(ready_sender, &[false, true]));
+        ready.recv().unwrap();
+
+        let options = FileDialogOptions {
+            title: "Pick".to_string(),
+            filters: vec![FileFilter {
+                name: "Images".to_string(),
+                extensions: vec!["png".to_string(), "jpg".to_string()],
+            }],
+            current_folder: Some(PathBuf::from("/tmp")),
+            current_name: "a.png".to_string(),
+            multiple: true,
+            ..Default::default()
+        };
+        assert_eq!(file_dialog(&options, false), expected_paths());
+        // The second response arrives before the reply to the call.
+        assert_eq!(file_dialog(&options, true), expected_paths());
+        assert_eq!(portal.join().unwrap(), vec!["OpenFile", "SaveFile"]);
+    }
+}
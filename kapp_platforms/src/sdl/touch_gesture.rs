@@ -0,0 +1,211 @@
+use kapp_platform_common::{Event, Gesture, GestureKind, WindowId};
+use std::time::Duration;
+
+// The same as the defaults of kapp's `GestureThresholds`.
+const PAN_DISTANCE: f64 = 10.;
+const PINCH_SCALE: f64 = 0.05;
+const ROTATE_ANGLE: f64 = 0.1;
+
+/// A multi-finger gesture reported by SDL_MULTIGESTURE.
+/// SDL reports changes in the fingers' spread, rotation, and center together, so each
+/// `GestureKind` only begins once it has changed enough on its own.
+/// Otherwise a two finger pan would also be reported as a pinch and a rotate.
+pub struct TouchGesture {
+    window_id: WindowId,
+    start_center: (f64, f64),
+    last_center: (f64, f64),
+    // The total change in scale and rotation since the gesture started.
+    scale: f64,
+    angle: f64,
+    active: Vec<GestureKind>,
+}
+
+impl TouchGesture {
+    /// `center` is the fingers' center in physical coordinates.
+    pub fn new(window_id: WindowId, center: (f64, f64)) -> Self {
+        Self {
+            window_id,
+            start_center: center,
+            last_center: center,
+            scale: 0.,
+            angle: 0.,
+            active: Vec::new(),
+        }
+    }
+
+    pub fn window_id(&self) -> WindowId {
+        self.window_id
+    }
+
+    /// `scale` and `angle` are the changes since the previous update.
+    pub fn update(
+        &mut self,
+        center: (f64, f64),
+        scale: f64,
+        angle: f64,
+        fingers: u32,
+        timestamp: Duration,
+    ) -> Vec<Event> {
+        self.scale = (1. + self.scale) * (1. + scale) - 1.;
+        self.angle += angle;
+
+        let mut updates = Vec::new();
+        if self.active.contains(&GestureKind::Pinch) || self.scale.abs() > PINCH_SCALE {
+            updates.push(Gesture::Pinch { scale });
+        }
+        if self.active.contains(&GestureKind::Rotate) || self.angle.abs() > ROTATE_ANGLE {
+            updates.push(Gesture::Rotate { angle });
+        }
+        if self.active.contains(&GestureKind::Pan)
+            || distance(self.start_center, center) > PAN_DISTANCE
+        {
+            updates.push(Gesture::Pan {
+                delta_x: center.0 - self.last_center.0,
+                delta_y: center.1 - self.last_center.1,
+                fingers,
+            });
+        }
+        self.last_center = center;
+
+        let mut events = Vec::new();
+        for gesture in updates {
+            if !self.active.contains(&gesture.kind()) {
+                self.active.push(gesture.kind());
+                events.push(Event::GestureBegin {
+                    kind: gesture.kind(),
+                    window_id: self.window_id,
+                    timestamp,
+                });
+            }
+            events.push(Event::GestureUpdate {
+                gesture,
+                window_id: self.window_id,
+                timestamp,
+            });
+        }
+        events
+    }
+
+    /// Ends the gesture kinds that have begun.
+    pub fn end(self, timestamp: Duration) -> Vec<Event> {
+        let window_id = self.window_id;
+        self.active
+            .into_iter()
+            .map(|kind| Event::GestureEnd {
+                kind,
+                cancelled: false,
+                window_id,
+                timestamp,
+            })
+            .collect()
+    }
+}
+
+fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn began(events: &[Event]) -> Vec<GestureKind> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                Event::GestureBegin { kind, .. } => Some(*kind),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn updated(events: &[Event]) -> Vec<Gesture> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                Event::GestureUpdate { gesture, .. } => Some(*gesture),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn gesture() -> TouchGesture {
+        TouchGesture::new(WindowId::new(std::ptr::null_mut()), (100., 100.))
+    }
+
+    #[test]
+    fn small_changes_do_not_begin() {
+        let mut gesture = gesture();
+        let events = gesture.update((103., 100.), 0.01, 0.02, 2, Duration::from_millis(10));
+        assert!(events.is_empty());
+        assert!(gesture.end(Duration::from_millis(20)).is_empty());
+    }
+
+    #[test]
+    fn pan_does_not_begin_pinch_or_rotate() {
+        let mut gesture = gesture();
+        let mut events = Vec::new();
+        for i in 1..=5 {
+            let center = (100. + 5. * i as f64, 100.);
+            events.extend(gesture.update(center, 0.005, 0.01, 2, Duration::from_millis(i)));
+        }
+        assert_eq!(began(&events), vec![GestureKind::Pan]);
+        // The first update is the one that passed the threshold.
+        assert_eq!(
+            updated(&events),
+            vec![
+                Gesture::Pan {
+                    delta_x: 5.,
+                    delta_y: 0.,
+                    fingers: 2
+                },
+                Gesture::Pan {
+                    delta_x: 5.,
+                    delta_y: 0.,
+                    fingers: 2
+                },
+                Gesture::Pan {
+                    delta_x: 5.,
+                    delta_y: 0.,
+                    fingers: 2
+                },
+            ]
+        );
+
+        let ended = gesture.end(Duration::from_millis(10));
+        assert_eq!(ended.len(), 1);
+        assert!(matches!(
+            ended[0],
+            Event::GestureEnd {
+                kind: GestureKind::Pan,
+                cancelled: false,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn pinch_begins_once_scale_accumulates() {
+        let mut gesture = gesture();
+        let first = gesture.update((100., 100.), 0.03, 0., 2, Duration::from_millis(1));
+        assert!(first.is_empty());
+
+        // 1.03 * 1.03 is more than 5% larger.
+        let second = gesture.update((100., 100.), 0.03, 0., 2, Duration::from_millis(2));
+        assert_eq!(began(&second), vec![GestureKind::Pinch]);
+        assert_eq!(updated(&second), vec![Gesture::Pinch { scale: 0.03 }]);
+
+        // Once begun, small changes are still reported.
+        let third = gesture.update((100., 100.), -0.001, 0., 2, Duration::from_millis(3));
+        assert!(began(&third).is_empty());
+        assert_eq!(updated(&third), vec![Gesture::Pinch { scale: -0.001 }]);
+    }
+
+    #[test]
+    fn rotate_begins_on_its_own() {
+        let mut gesture = gesture();
+        let events = gesture.update((100., 100.), 0., 0.2, 2, Duration::from_millis(1));
+        assert_eq!(began(&events), vec![GestureKind::Rotate]);
+        assert_eq!(updated(&events), vec![Gesture::Rotate { angle: 0.2 }]);
+    }
+}
@@ -0,0 +1,433 @@
+/// Window manager hints and requests that SDL 2.0.14 doesn't provide, made with Xlib.
+/// SDL loads libX11 at runtime, so it's loaded the same way here instead of being linked.
+/// These do nothing for windows that aren't X11 windows, like on Wayland.
+use fermium::{loadso::*, mouse::*, syswm::*, video::*, *};
+use kapp_platform_common::{ResizeEdge, WindowId};
+use std::ffi::{c_void, CStr};
+use std::os::raw::{c_char, c_int, c_long, c_ulong};
+
+type Display = c_void;
+type Window = c_ulong;
+type Atom = c_ulong;
+
+// From X.h
+const CLIENT_MESSAGE: c_int = 33;
+const SUBSTRUCTURE_NOTIFY_MASK: c_long = 1 << 19;
+const SUBSTRUCTURE_REDIRECT_MASK: c_long = 1 << 20;
+const PROPERTY_CHANGE_MASK: c_long = 1 << 22;
+const TRUE_COLOR: c_int = 4;
+
+// From Xutil.h
+const P_RESIZE_INC: c_long = 1 << 6;
+const P_ASPECT: c_long = 1 << 7;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct AspectRatio {
+    x: c_int,
+    y: c_int,
+}
+
+#[repr(C)]
+struct XSizeHints {
+    flags: c_long,
+    x: c_int,
+    y: c_int,
+    width: c_int,
+    height: c_int,
+    min_width: c_int,
+    min_height: c_int,
+    max_width: c_int,
+    max_height: c_int,
+    width_inc: c_int,
+    height_inc: c_int,
+    min_aspect: AspectRatio,
+    max_aspect: AspectRatio,
+    base_width: c_int,
+    base_height: c_int,
+    win_gravity: c_int,
+}
+
+#[repr(C)]
+struct XVisualInfo {
+    visual: *mut c_void,
+    visualid: c_ulong,
+    screen: c_int,
+    depth: c_int,
+    class: c_int,
+    red_mask: c_ulong,
+    green_mask: c_ulong,
+    blue_mask: c_ulong,
+    colormap_size: c_int,
+    bits_per_rgb: c_int,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct XClientMessageEvent {
+    type_: c_int,
+    serial: c_ulong,
+    send_event: c_int,
+    display: *mut Display,
+    window: Window,
+    message_type: Atom,
+    format: c_int,
+    data: [c_long; 5],
+}
+
+// Every event is sent as the `XEvent` union, which is padded to 24 longs.
+#[repr(C)]
+union XEvent {
+    client_message: XClientMessageEvent,
+    pad: [c_long; 24],
+}
+
+type XOpenDisplay = unsafe extern "C" fn(*const c_char) -> *mut Display;
+type XCloseDisplay = unsafe extern "C" fn(*mut Display) -> c_int;
+type XDefaultScreen = unsafe extern "C" fn(*mut Display) -> c_int;
+type XMatchVisualInfo =
+    unsafe extern "C" fn(*mut Display, c_int, c_int, c_int, *mut XVisualInfo) -> c_int;
+type XInternAtom = unsafe extern "C" fn(*mut Display, *const c_char, c_int) -> Atom;
+type XDefaultRootWindow = unsafe extern "C" fn(*mut Display) -> Window;
+type XSendEvent = unsafe extern "C" fn(*mut Display, Window, c_int, c_long, *mut XEvent) -> c_int;
+type XUngrabPointer = unsafe extern "C" fn(*mut Display, c_ulong) -> c_int;
+type XFlush = unsafe extern "C" fn(*mut Display) -> c_int;
+type XSync = unsafe extern "C" fn(*mut Display, c_int) -> c_int;
+type XFree = unsafe extern "C" fn(*mut c_void) -> c_int;
+type XAllocSizeHints = unsafe extern "C" fn() -> *mut XSizeHints;
+type XGetWMNormalHints =
+    unsafe extern "C" fn(*mut Display, Window, *mut XSizeHints, *mut c_long) -> c_int;
+type XSetWMNormalHints = unsafe extern "C" fn(*mut Display, Window, *mut XSizeHints);
+
+struct Xlib {
+    open_display: XOpenDisplay,
+    close_display: XCloseDisplay,
+    default_screen: XDefaultScreen,
+    match_visual_info: XMatchVisualInfo,
+    intern_atom: XInternAtom,
+    default_root_window: XDefaultRootWindow,
+    send_event: XSendEvent,
+    ungrab_pointer: XUngrabPointer,
+    flush: XFlush,
+    sync: XSync,
+    free: XFree,
+    alloc_size_hints: XAllocSizeHints,
+    get_wm_normal_hints: XGetWMNormalHints,
+    set_wm_normal_hints: XSetWMNormalHints,
+}
+
+impl Xlib {
+    unsafe fn load() -> Option<Self> {
+        let library = SDL_LoadObject(b"libX11.so.6\0".as_ptr().cast());
+        if library.is_null() {
+            return None;
+        }
+        macro_rules! function {
+            ($name:ident) => {{
+                let name = concat!(stringify!($name), "\0");
+                let function = SDL_LoadFunction(library, name.as_ptr().cast());
+                if function.is_null() {
+                    return None;
+                }
+                std::mem::transmute::<*mut c_void, $name>(function)
+            }};
+        }
+        Some(Self {
+            open_display: function!(XOpenDisplay),
+            close_display: function!(XCloseDisplay),
+            default_screen: function!(XDefaultScreen),
+            match_visual_info: function!(XMatchVisualInfo),
+            intern_atom: function!(XInternAtom),
+            default_root_window: function!(XDefaultRootWindow),
+            send_event: function!(XSendEvent),
+            ungrab_pointer: function!(XUngrabPointer),
+            flush: function!(XFlush),
+            sync: function!(XSync),
+            free: function!(XFree),
+            alloc_size_hints: function!(XAllocSizeHints),
+            get_wm_normal_hints: function!(XGetWMNormalHints),
+            set_wm_normal_hints: function!(XSetWMNormalHints),
+        })
+    }
+
+    unsafe fn atom(&self, display: *mut Display, name: &str) -> Atom {
+        let name = std::ffi::CString::new(name).unwrap();
+        (self.intern_atom)(display, name.as_ptr(), 0)
+    }
+
+    /// Sends a client message about `window` to the root window, where the window manager
+    /// receives it.
+    unsafe fn send_to_root(
+        &self,
+        display: *mut Display,
+        window: Window,
+        message_type: &str,
+        data: [c_long; 5],
+    ) {
+        self.send_client_message(
+            display,
+            window,
+            message_type,
+            32,
+            data,
+            SUBSTRUCTURE_REDIRECT_MASK | SUBSTRUCTURE_NOTIFY_MASK,
+        );
+    }
+
+    unsafe fn send_client_message(
+        &self,
+        display: *mut Display,
+        window: Window,
+        message_type: &str,
+        format: c_int,
+        data: [c_long; 5],
+        event_mask: c_long,
+    ) {
+        let mut event = XEvent { pad: [0; 24] };
+        event.client_message = XClientMessageEvent {
+            type_: CLIENT_MESSAGE,
+            serial: 0,
+            send_event: 1,
+            display,
+            window,
+            message_type: self.atom(display, message_type),
+            format,
+            data,
+        };
+        (self.send_event)(
+            display,
+            (self.default_root_window)(display),
+            0,
+            event_mask,
+            &mut event,
+        );
+        (self.flush)(display);
+    }
+}
+
+thread_local! {
+    // Loaded the first time an X11 window needs it and never unloaded.
+    static XLIB: Option<Xlib> = unsafe { Xlib::load() };
+}
+
+/// Calls `f` with Xlib and the window's display and X11 window, if it's an X11 window.
+fn with_x11_window(window_id: WindowId, f: impl FnOnce(&Xlib, *mut Display, Window)) {
+    unsafe {
+        let mut info = SDL_SysWMinfo::default();
+        version::SDL_VERSION(&mut info.version);
+        if SDL_GetWindowWMInfo(window_id.raw() as *mut SDL_Window, &mut info) != SDL_TRUE {
+            return;
+        }
+        if let SDL_SYSWM_X11 = info.subsystem {
+            let display = info.info.x11.display as *mut Display;
+            let window = info.info.x11.window;
+            XLIB.with(|xlib| {
+                if let Some(xlib) = xlib {
+                    f(xlib, display, window)
+                }
+            });
+        }
+    }
+}
+
+/// The id of a 32 bit TrueColor visual, whose alpha channel compositors use to blend
+/// the window with what's behind it. Returns `None` if SDL isn't using X11.
+/// The window doesn't exist yet, so the display is opened separately to find it.
+/// Visual ids are the same for every connection to the X server.
+pub fn transparent_visual_id() -> Option<c_ulong> {
+    unsafe {
+        let driver = SDL_GetCurrentVideoDriver();
+        if driver.is_null() || CStr::from_ptr(driver).to_bytes() != b"x11" {
+            return None;
+        }
+        XLIB.with(|xlib| {
+            let xlib = xlib.as_ref()?;
+            let display = (xlib.open_display)(std::ptr::null());
+            if display.is_null() {
+                return None;
+            }
+            let mut info: XVisualInfo = std::mem::zeroed();
+            let found = (xlib.match_visual_info)(
+                display,
+                (xlib.default_screen)(display),
+                32,
+                TRUE_COLOR,
+                &mut info,
+            );
+            (xlib.close_display)(display);
+            if found != 0 {
+                Some(info.visualid)
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// SDL sets the window's minimum and maximum size in `WM_NORMAL_HINTS` and keeps the other
+/// hints when it does, so the aspect ratio and resize increments are added to the same hints.
+fn update_normal_hints(window_id: WindowId, update: impl FnOnce(&mut XSizeHints)) {
+    with_x11_window(window_id, |xlib, display, window| unsafe {
+        let hints = (xlib.alloc_size_hints)();
+        if hints.is_null() {
+            return;
+        }
+        let mut supplied = 0;
+        (xlib.get_wm_normal_hints)(display, window, hints, &mut supplied);
+        update(&mut *hints);
+        (xlib.set_wm_normal_hints)(display, window, hints);
+        (xlib.free)(hints.cast());
+        (xlib.flush)(display);
+    });
+}
+
+pub fn set_aspect_ratio(window_id: WindowId, ratio: Option<(u32, u32)>) {
+    update_normal_hints(window_id, |hints| match ratio {
+        Some((width, height)) => {
+            let ratio = AspectRatio {
+                x: width.min(i32::MAX as u32) as c_int,
+                y: height.min(i32::MAX as u32) as c_int,
+            };
+            hints.min_aspect = ratio;
+            hints.max_aspect = ratio;
+            hints.flags |= P_ASPECT;
+        }
+        None => hints.flags &= !P_ASPECT,
+    });
+}
+
+/// X11 windows aren't scaled by SDL, so the physical increments are used as they are.
+pub fn set_resize_increments(window_id: WindowId, increments: Option<(u32, u32)>) {
+    update_normal_hints(window_id, |hints| match increments {
+        Some((width, height)) => {
+            hints.width_inc = width.clamp(1, i32::MAX as u32) as c_int;
+            hints.height_inc = height.clamp(1, i32::MAX as u32) as c_int;
+            hints.flags |= P_RESIZE_INC;
+        }
+        None => hints.flags &= !P_RESIZE_INC,
+    });
+}
+
+/// Adds `_NET_WM_STATE_DEMANDS_ATTENTION` to the window's state,
+/// which the window manager removes once the window is focused.
+/// https://specifications.freedesktop.org/wm-spec/latest/
+pub fn request_attention(window_id: WindowId) {
+    with_x11_window(window_id, |xlib, display, window| unsafe {
+        const NET_WM_STATE_ADD: c_long = 1;
+        const SOURCE_APPLICATION: c_long = 1;
+        let demands_attention = xlib.atom(display, "_NET_WM_STATE_DEMANDS_ATTENTION");
+        xlib.send_to_root(
+            display,
+            window,
+            "_NET_WM_STATE",
+            [
+                NET_WM_STATE_ADD,
+                demands_attention as c_long,
+                0,
+                SOURCE_APPLICATION,
+                0,
+            ],
+        );
+    });
+}
+
+/// Asks the window manager to move the window, or resize it from an edge, with the pointer.
+/// This is what SDL does on X11 for its own hit-test.
+/// https://specifications.freedesktop.org/wm-spec/latest/
+pub fn begin_move_resize(window_id: WindowId, edge: Option<ResizeEdge>) {
+    with_x11_window(window_id, |xlib, display, window| unsafe {
+        const BUTTON_1: c_long = 1;
+        const SOURCE_APPLICATION: c_long = 1;
+        let direction = match edge {
+            Some(ResizeEdge::NorthWest) => 0,
+            Some(ResizeEdge::North) => 1,
+            Some(ResizeEdge::NorthEast) => 2,
+            Some(ResizeEdge::East) => 3,
+            Some(ResizeEdge::SouthEast) => 4,
+            Some(ResizeEdge::South) => 5,
+            Some(ResizeEdge::SouthWest) => 6,
+            Some(ResizeEdge::West) => 7,
+            None => 8, // _NET_WM_MOVERESIZE_MOVE
+        };
+        let (mut x, mut y) = (0, 0);
+        SDL_GetGlobalMouseState(&mut x, &mut y);
+
+        // The window manager can't grab the pointer while the button press holds a grab.
+        (xlib.ungrab_pointer)(display, 0);
+        (xlib.flush)(display);
+        xlib.send_to_root(
+            display,
+            window,
+            "_NET_WM_MOVERESIZE",
+            [
+                x as c_long,
+                y as c_long,
+                direction,
+                BUTTON_1,
+                SOURCE_APPLICATION,
+            ],
+        );
+        (xlib.sync)(display, 0);
+    });
+}
+
+/// Tells the desktop the application has finished starting, so it can stop showing
+/// a busy cursor or placeholder, using the id the launcher passed in `DESKTOP_STARTUP_ID`.
+/// The variable is removed so it's only used once and isn't inherited by child processes.
+/// https://specifications.freedesktop.org/startup-notification-spec/latest/
+pub fn complete_startup(window_id: WindowId) {
+    let id = match std::env::var("DESKTOP_STARTUP_ID") {
+        Ok(id) if !id.is_empty() => id,
+        _ => return,
+    };
+    with_x11_window(window_id, |xlib, display, window| unsafe {
+        std::env::remove_var("DESKTOP_STARTUP_ID");
+
+        // The message is sent 20 bytes at a time. Receivers join the pieces by the window
+        // they're about, which the spec only requires to stay valid while it's sent.
+        for (i, chunk) in startup_remove_message(&id).chunks(20).enumerate() {
+            let mut data = [0; 5];
+            std::ptr::copy_nonoverlapping(
+                chunk.as_ptr(),
+                data.as_mut_ptr().cast::<u8>(),
+                chunk.len(),
+            );
+            let message_type = if i == 0 {
+                "_NET_STARTUP_INFO_BEGIN"
+            } else {
+                "_NET_STARTUP_INFO"
+            };
+            xlib.send_client_message(display, window, message_type, 8, data, PROPERTY_CHANGE_MASK);
+        }
+    });
+}
+
+/// The nul terminated `remove` message for a startup id, with the id quoted.
+fn startup_remove_message(id: &str) -> Vec<u8> {
+    let mut message = String::from("remove: ID=\"");
+    for character in id.chars() {
+        if character == '"' || character == '\\' {
+            message.push('\\');
+        }
+        message.push(character);
+    }
+    message.push_str("\"\0");
+    message.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn startup_remove_message_quotes_the_id() {
+        assert_eq!(
+            startup_remove_message("kapp-1234_TIME5678"),
+            b"remove: ID=\"kapp-1234_TIME5678\"\0"
+        );
+        assert_eq!(
+            startup_remove_message(r#"a "b"\c"#),
+            b"remove: ID=\"a \\\"b\\\"\\\\c\"\0"
+        );
+    }
+}
@@ -3,12 +3,16 @@ use wasm_bindgen::JsCast;
 use web_sys::HtmlElement;
 
 pub static mut CURRENT_CURSOR: Option<String> = None;
+// Set while a pointer lock is requested, so that a lock released by the browser can be reported.
+pub static mut POINTER_LOCKED: bool = false;
 
 pub struct PlatformApplication {}
 
 impl PlatformApplicationTrait for PlatformApplication {
     type EventLoop = PlatformEventLoop;
-    fn new() -> Self {
+    type EventLoopProxy = PlatformEventLoopProxy;
+    // The page controls how the application is identified.
+    fn new(_app_info: &AppInfo) -> Self {
         // Set panic hook. Should this be possible to disable?
         console_error_panic_hook::set_once();
         Self {}
@@ -18,6 +22,10 @@ impl PlatformApplicationTrait for PlatformApplication {
         PlatformEventLoop {}
     }
 
+    fn event_loop_proxy(&mut self) -> Self::EventLoopProxy {
+        PlatformEventLoopProxy {}
+    }
+
     fn set_window_position(&mut self, _window_id: WindowId, _x: u32, _y: u32) {}
     fn set_window_size(&mut self, _window_id: WindowId, _width: u32, _height: u32) {}
     fn set_window_title(&mut self, _window_id: WindowId, _title: &str) {}
@@ -45,6 +53,51 @@ impl PlatformApplicationTrait for PlatformApplication {
         todo!()
     }
     fn close_window(&mut self, _window_id: WindowId) {}
+    fn show_window(&mut self, _window_id: WindowId) {}
+    fn hide_window(&mut self, _window_id: WindowId) {}
+    fn focus_window(&mut self, _window_id: WindowId) {}
+    fn request_window_attention(&mut self, _window_id: WindowId) {}
+    // The canvas is considered visible if the page is visible.
+    fn is_window_visible(&mut self, _window_id: WindowId) -> bool {
+        let document = web_sys::window().unwrap().document().unwrap();
+        !document.hidden()
+    }
+    fn is_window_focused(&mut self, _window_id: WindowId) -> bool {
+        let document = web_sys::window().unwrap().document().unwrap();
+        document.has_focus().unwrap_or(false)
+    }
+    fn is_window_minimized(&mut self, _window_id: WindowId) -> bool {
+        false
+    }
+    fn is_window_maximized(&mut self, _window_id: WindowId) -> bool {
+        false
+    }
+    fn set_window_min_size(&mut self, _window_id: WindowId, _size: Option<(u32, u32)>) {}
+    fn set_window_max_size(&mut self, _window_id: WindowId, _size: Option<(u32, u32)>) {}
+    fn set_window_resizable(&mut self, _window_id: WindowId, _resizable: bool) {}
+    fn set_window_decorations(&mut self, _window_id: WindowId, _decorations: bool) {}
+    fn set_window_aspect_ratio(&mut self, _window_id: WindowId, _ratio: Option<(u32, u32)>) {}
+    fn set_window_resize_increments(
+        &mut self,
+        _window_id: WindowId,
+        _increments: Option<(u32, u32)>,
+    ) {
+    }
+    fn set_window_opacity(&mut self, _window_id: WindowId, opacity: f64) {
+        let document = web_sys::window().unwrap().document().unwrap();
+        let canvas = document
+            .get_element_by_id("canvas")
+            .unwrap()
+            .dyn_into::<web_sys::HtmlCanvasElement>()
+            .unwrap();
+        canvas
+            .style()
+            .set_property("opacity", &opacity.to_string())
+            .unwrap();
+    }
+    fn set_window_hit_test(&mut self, _window_id: WindowId, _hit_test: Option<HitTest>) {}
+    fn begin_window_drag_move(&mut self, _window_id: WindowId) {}
+    fn begin_window_drag_resize(&mut self, _window_id: WindowId, _edge: ResizeEdge) {}
     fn redraw_window(&mut self, _window_id: WindowId) {
         super::event_loop_web::request_frame()
     }
@@ -64,11 +117,84 @@ impl PlatformApplicationTrait for PlatformApplication {
         document.exit_pointer_lock();
     }
 
+    // Browsers do not allow moving the pointer.
+    fn set_pointer_position(&mut self, _window_id: WindowId, _x: f64, _y: f64) {}
+
+    // Browsers do not allow confining the pointer without locking it.
+    fn confine_pointer(&mut self, _window_id: WindowId, _confine: bool) {}
+
+    fn lock_pointer(&mut self, _window_id: WindowId) {
+        unsafe {
+            POINTER_LOCKED = true;
+        }
+        self.lock_mouse_position();
+    }
+
+    fn unlock_pointer(&mut self, _window_id: WindowId) {
+        unsafe {
+            POINTER_LOCKED = false;
+        }
+        self.unlock_mouse_position();
+    }
+
+    // Browsers do not reliably offer unaccelerated motion.
+    fn set_raw_mouse_motion(&mut self, _enabled: bool) {}
+
     fn new_window(&mut self, _window_parameters: &WindowParameters) -> WindowId {
         WindowId::new(0 as *mut std::ffi::c_void)
     }
 
-    fn quit(&self) {}
+    // The page can't be closed, so quitting does nothing.
+    fn quit(&self, _exit_code: i32) {}
+
+    fn set_control_flow(&mut self, control_flow: ControlFlow) {
+        super::event_loop_web::set_control_flow(control_flow);
+    }
+
+    // The browser's dialogs have no title or icon, so the title is prepended to the text.
+    // `confirm` only has two buttons, so `YesNoCancel` never returns `Cancel`.
+    fn message_box(&mut self, message_box: &MessageBox) -> Option<MessageBoxButton> {
+        let text = if message_box.title.is_empty() {
+            message_box.text.clone()
+        } else {
+            format!("{}\n\n{}", message_box.title, message_box.text)
+        };
+
+        let window = web_sys::window().unwrap();
+        match message_box.buttons {
+            MessageBoxButtons::Ok => {
+                window.alert_with_message(&text).ok()?;
+                Some(MessageBoxButton::Ok)
+            }
+            MessageBoxButtons::OkCancel => Some(if window.confirm_with_message(&text).ok()? {
+                MessageBoxButton::Ok
+            } else {
+                MessageBoxButton::Cancel
+            }),
+            MessageBoxButtons::YesNo | MessageBoxButtons::YesNoCancel => {
+                Some(if window.confirm_with_message(&text).ok()? {
+                    MessageBoxButton::Yes
+                } else {
+                    MessageBoxButton::No
+                })
+            }
+        }
+    }
+
+    // Browsers only give access to the contents of picked files, not their paths.
+    fn open_file_dialog(&mut self, id: FileDialogId, _options: &FileDialogOptions) {
+        super::event_loop_web::send_event_later(Event::FileDialogResult {
+            id,
+            paths: Vec::new(),
+        });
+    }
+
+    fn save_file_dialog(&mut self, id: FileDialogId, _options: &FileDialogOptions) {
+        super::event_loop_web::send_event_later(Event::FileDialogResult {
+            id,
+            paths: Vec::new(),
+        });
+    }
 
     fn set_cursor(&mut self, cursor: Cursor) {
         let style = web_sys::window()
@@ -122,9 +248,14 @@ impl PlatformApplicationTrait for PlatformApplication {
         RawWindowHandle::Web(raw_window_handle::web::WebHandle::empty())
     }
 
-    fn start_text_input(&mut self) {}
+    fn start_text_input(&mut self, _window_id: Option<WindowId>) {}
+
+    fn end_text_input(&mut self, _window_id: Option<WindowId>) {}
 
-    fn end_text_input(&mut self) {}
+    // The browser's keyboard layout map is only available asynchronously.
+    fn key_label(&self, _physical_key: Key) -> Option<String> {
+        None
+    }
 
     fn set_text_input_rectangle(
         &mut self,
@@ -141,14 +272,30 @@ impl PlatformApplicationTrait for PlatformApplication {
 // When the application is dropped, quit the program.
 impl Drop for PlatformApplication {
     fn drop(&mut self) {
-        self.quit();
+        self.quit(0);
+    }
+}
+
+// Without threads the proxy can only be used from the page's thread,
+// so events are sent from a timeout like other events produced outside of browser callbacks.
+#[derive(Clone)]
+pub struct PlatformEventLoopProxy {}
+
+impl PlatformEventLoopProxyTrait for PlatformEventLoopProxy {
+    fn send(&self, event: UserEvent) {
+        super::event_loop_web::send_event_later(Event::User(event));
     }
 }
 
 pub struct PlatformEventLoop {}
 
 impl PlatformEventLoopTrait for PlatformEventLoop {
-    fn run(&self, callback: Box<dyn FnMut(Event)>) {
+    // Returns immediately. Events are sent from browser callbacks afterwards.
+    fn run(&self, callback: Box<dyn FnMut(Event)>) -> i32 {
         super::event_loop_web::run(callback);
+        0
     }
 }
+
+// `PlatformEventLoopPumpTrait` isn't implemented because the browser owns the event loop
+// and events can only be received from its callbacks.
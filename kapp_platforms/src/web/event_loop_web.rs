@@ -21,7 +21,13 @@ fn window() -> web_sys::Window {
 static mut CALLBACK: Option<Box<dyn FnMut(Event)>> = None;
 static mut REQUEST_ANIMATION_FRAME_CLOSURE: Option<Closure<dyn FnMut()>> = None;
 static mut REQUEST_FULLSCREEN_CLOSURE: Option<Closure<dyn FnMut()>> = None;
+static mut CONTROL_FLOW_CLOSURE: Option<Closure<dyn FnMut()>> = None;
+static mut CONTROL_FLOW: ControlFlow = ControlFlow::Wait;
+static mut CONTROL_FLOW_FRAME_REQUESTED: bool = false;
+// The `setTimeout` handle used to wake for `WaitUntil`.
+static mut CONTROL_FLOW_TIMEOUT: Option<i32> = None;
 static mut CANVAS_HEIGHT: u32 = 0;
+static mut LAST_MODIFIERS: Modifiers = Modifiers::empty();
 
 fn send_event(event: Event) {
     unsafe {
@@ -29,6 +35,14 @@ fn send_event(event: Event) {
     }
 }
 
+/// Sends an event from a timeout so it isn't sent while the callback is running.
+pub fn send_event_later(event: Event) {
+    let closure = Closure::once_into_js(move || send_event(event));
+    window()
+        .set_timeout_with_callback(closure.unchecked_ref())
+        .unwrap();
+}
+
 pub fn run<T>(callback: T)
 where
     T: 'static + FnMut(Event),
@@ -76,6 +90,16 @@ where
                 Some(Closure::wrap(
                     Box::new(move || println!("Fullscreened?")) as Box<dyn FnMut()>
                 ));
+
+            CONTROL_FLOW_CLOSURE = Some(Closure::wrap(Box::new(move || {
+                CONTROL_FLOW_FRAME_REQUESTED = false;
+                CONTROL_FLOW_TIMEOUT = None;
+                send_event(Event::EventsCleared);
+                request_control_flow_frame();
+            }) as Box<dyn FnMut()>));
+
+            // The control flow may have been set before the event loop was run.
+            request_control_flow_frame();
         }
 
         // Pointer move event
@@ -85,12 +109,40 @@ where
                 x,
                 y,
                 source: get_pointer_type(&event),
+                pointer_id: get_pointer_id(&event),
+                window_id: WindowId::new(0 as *mut std::ffi::c_void),
+                modifiers: get_mouse_modifiers(&event),
                 timestamp: Duration::from_secs_f64(event.time_stamp() * 1000.0),
             });
         }) as Box<dyn FnMut(web_sys::PointerEvent)>);
         canvas.set_onpointermove(Some(pointer_move.as_ref().unchecked_ref()));
         pointer_move.forget();
 
+        // Pointer enter and leave events
+        // Touches enter and leave with every press, so only hovering pointers are reported.
+        let pointer_enter = Closure::wrap(Box::new(move |event: web_sys::PointerEvent| {
+            if get_pointer_type(&event) != PointerSource::Touch {
+                let (x, y) = get_pointer_position(&event);
+                send_event(Event::PointerEntered {
+                    window_id: WindowId::new(0 as *mut std::ffi::c_void),
+                    x,
+                    y,
+                });
+            }
+        }) as Box<dyn FnMut(web_sys::PointerEvent)>);
+        canvas.set_onpointerenter(Some(pointer_enter.as_ref().unchecked_ref()));
+        pointer_enter.forget();
+
+        let pointer_leave = Closure::wrap(Box::new(move |event: web_sys::PointerEvent| {
+            if get_pointer_type(&event) != PointerSource::Touch {
+                send_event(Event::PointerLeft {
+                    window_id: WindowId::new(0 as *mut std::ffi::c_void),
+                });
+            }
+        }) as Box<dyn FnMut(web_sys::PointerEvent)>);
+        canvas.set_onpointerleave(Some(pointer_leave.as_ref().unchecked_ref()));
+        pointer_leave.forget();
+
         // Pointer down event
         let pointer_down = Closure::wrap(Box::new(move |event: web_sys::PointerEvent| {
             let (x, y) = get_pointer_position(&event);
@@ -99,6 +151,9 @@ where
                 x,
                 y,
                 source: get_pointer_type(&event),
+                pointer_id: get_pointer_id(&event),
+                window_id: WindowId::new(0 as *mut std::ffi::c_void),
+                modifiers: get_mouse_modifiers(&event),
                 button: match event.button() {
                     0 => PointerButton::Primary,
                     1 => PointerButton::Auxillary,
@@ -128,6 +183,7 @@ where
                         4 => PointerButton::Extra2,
                         _ => PointerButton::Unknown,
                     },
+                    window_id: WindowId::new(0 as *mut std::ffi::c_void),
                     timestamp: Duration::from_secs_f64(event.time_stamp() * 1000.0),
                 });
             }
@@ -141,6 +197,7 @@ where
             send_event(Event::MouseMotion {
                 delta_x,
                 delta_y,
+                window_id: WindowId::new(0 as *mut std::ffi::c_void),
                 timestamp: Duration::from_secs_f64(event.time_stamp() * 1000.0),
             });
         }) as Box<dyn FnMut(web_sys::MouseEvent)>);
@@ -163,12 +220,14 @@ where
                 x,
                 y,
                 button,
+                window_id: WindowId::new(0 as *mut std::ffi::c_void),
                 timestamp,
             });
             send_event(Event::DoubleClick {
                 x,
                 y,
                 button,
+                window_id: WindowId::new(0 as *mut std::ffi::c_void),
                 timestamp,
             });
         }) as Box<dyn FnMut(web_sys::MouseEvent)>);
@@ -183,6 +242,9 @@ where
                 x,
                 y,
                 source: get_pointer_type(&event),
+                pointer_id: get_pointer_id(&event),
+                window_id: WindowId::new(0 as *mut std::ffi::c_void),
+                modifiers: get_mouse_modifiers(&event),
                 button: match event.button() {
                     0 => PointerButton::Primary,
                     1 => PointerButton::Auxillary,
@@ -199,14 +261,29 @@ where
 
         // Key down event
         let keydown = Closure::wrap(Box::new(move |event: web_sys::KeyboardEvent| {
+            let modifiers = get_key_modifiers(&event);
+            let (key, physical_key) = get_keys(&event);
+            // `key` is either a single printable character or the name of the key.
+            let text = Some(event.key())
+                .filter(|text| text.chars().count() == 1 && !text.chars().any(char::is_control));
             let key_event = if event.repeat() {
                 Event::KeyRepeat {
-                    key: keys_web::virtual_keycode_to_key(&event.code()),
+                    key,
+                    physical_key,
+                    scancode: 0,
+                    text,
+                    window_id: WindowId::new(0 as *mut std::ffi::c_void),
+                    modifiers,
                     timestamp: Duration::from_secs_f64(event.time_stamp() * 1000.0),
                 }
             } else {
                 Event::KeyDown {
-                    key: keys_web::virtual_keycode_to_key(&event.code()),
+                    key,
+                    physical_key,
+                    scancode: 0,
+                    text,
+                    window_id: WindowId::new(0 as *mut std::ffi::c_void),
+                    modifiers,
                     timestamp: Duration::from_secs_f64(event.time_stamp() * 1000.0),
                 }
             };
@@ -222,7 +299,10 @@ where
             // A more robust solution may watch a text field for changes instead.
             if !event.is_composing() && event.key().chars().count() == 1 {
                 for character in event.key().chars() {
-                    send_event(Event::CharacterReceived { character })
+                    send_event(Event::CharacterReceived {
+                        character,
+                        window_id: WindowId::new(0 as *mut std::ffi::c_void),
+                    })
                 }
             }
 
@@ -236,8 +316,13 @@ where
 
         // Key up event
         let keyup = Closure::wrap(Box::new(move |event: web_sys::KeyboardEvent| {
+            let (key, physical_key) = get_keys(&event);
             send_event(Event::KeyUp {
-                key: keys_web::virtual_keycode_to_key(&event.code()),
+                key,
+                physical_key,
+                scancode: 0,
+                window_id: WindowId::new(0 as *mut std::ffi::c_void),
+                modifiers: get_key_modifiers(&event),
                 timestamp: Duration::from_secs_f64(event.time_stamp() * 1000.0),
             });
             event
@@ -258,20 +343,40 @@ where
                 // This is a bit weird, but if a pinch gesture is performed
                 // the ctrl modifier is set.
                 // This is the simplest way to disambiguate it.
-                send_event(Event::PinchGesture {
-                    // 0.02 is a completely arbitrary number to make this value more similar
-                    // to what native MacOS produces.
-                    // Is this a good idea at all?
-                    // Should this library even make such adjustments?
-                    // Is there a way to find an actual scale factor instead of a guess?
-                    delta: -event.delta_y() * 0.02,
+                // Web provides no equivalent of `GestureBegin` or `GestureEnd`.
+                send_event(Event::GestureUpdate {
+                    gesture: Gesture::Pinch {
+                        // 0.02 is a completely arbitrary number to make this value more similar
+                        // to what native MacOS produces.
+                        // Is this a good idea at all?
+                        // Should this library even make such adjustments?
+                        // Is there a way to find an actual scale factor instead of a guess?
+                        scale: -event.delta_y() * 0.02,
+                    },
+                    window_id: WindowId::new(0 as *mut std::ffi::c_void),
                     timestamp: Duration::from_secs_f64(event.time_stamp() * 1000.0),
                 });
             } else {
+                let x = -event.delta_x();
+                let y = -event.delta_y();
+                let delta = match event.delta_mode() {
+                    web_sys::WheelEvent::DOM_DELTA_LINE => ScrollDelta::Lines { x, y },
+                    // Page scrolling is rare, so treat a page as the height of the canvas.
+                    web_sys::WheelEvent::DOM_DELTA_PAGE => {
+                        let page_height = unsafe { CANVAS_HEIGHT } as f64;
+                        ScrollDelta::Pixels {
+                            x: x * page_height,
+                            y: y * page_height,
+                        }
+                    }
+                    _ => ScrollDelta::Pixels { x, y },
+                };
+
                 send_event(Event::Scroll {
-                    delta_x: -event.delta_x(),
-                    delta_y: -event.delta_y(),
+                    delta,
+                    phase: ScrollPhase::Unknown,
                     window_id: WindowId::new(0 as *mut std::ffi::c_void),
+                    modifiers: get_mouse_modifiers(&event),
                     timestamp: Duration::from_secs_f64(event.time_stamp() * 1000.0),
                 });
             }
@@ -282,11 +387,79 @@ where
         }) as Box<dyn FnMut(web_sys::WheelEvent)>);
         canvas.set_onwheel(Some(wheel.as_ref().unchecked_ref()));
         wheel.forget();
+
+        // The browser releases the pointer lock when escape is pressed or the page loses focus.
+        let document_inner = document.clone();
+        let pointer_lock_change = Closure::wrap(Box::new(move || {
+            if document_inner.pointer_lock_element().is_none()
+                && super::application_web::POINTER_LOCKED
+            {
+                super::application_web::POINTER_LOCKED = false;
+                send_event(Event::PointerLockLost {
+                    window_id: WindowId::new(0 as *mut std::ffi::c_void),
+                });
+            }
+        }) as Box<dyn FnMut()>);
+        document.set_onpointerlockchange(Some(pointer_lock_change.as_ref().unchecked_ref()));
+        pointer_lock_change.forget();
+
         // Finally, start the draw loop.
         request_frame();
     }
 }
 
+/// Browsers report modifiers with every input event but have no event for when they change,
+/// so `ModifiersChanged` is sent when an input event's modifiers differ from the last ones seen.
+fn update_modifiers(modifiers: Modifiers) -> Modifiers {
+    unsafe {
+        if LAST_MODIFIERS != modifiers {
+            LAST_MODIFIERS = modifiers;
+            send_event(Event::ModifiersChanged { modifiers });
+        }
+    }
+    modifiers
+}
+
+fn get_mouse_modifiers(event: &web_sys::MouseEvent) -> Modifiers {
+    let mut modifiers = Modifiers::empty();
+    modifiers.set(Modifiers::SHIFT, event.shift_key());
+    modifiers.set(Modifiers::CONTROL, event.ctrl_key());
+    modifiers.set(Modifiers::ALT, event.alt_key());
+    modifiers.set(Modifiers::SUPER, event.meta_key());
+    modifiers.set(Modifiers::CAPS_LOCK, event.get_modifier_state("CapsLock"));
+    modifiers.set(Modifiers::NUM_LOCK, event.get_modifier_state("NumLock"));
+    update_modifiers(modifiers)
+}
+
+fn get_key_modifiers(event: &web_sys::KeyboardEvent) -> Modifiers {
+    let mut modifiers = Modifiers::empty();
+    modifiers.set(Modifiers::SHIFT, event.shift_key());
+    modifiers.set(Modifiers::CONTROL, event.ctrl_key());
+    modifiers.set(Modifiers::ALT, event.alt_key());
+    modifiers.set(Modifiers::SUPER, event.meta_key());
+    modifiers.set(Modifiers::CAPS_LOCK, event.get_modifier_state("CapsLock"));
+    modifiers.set(Modifiers::NUM_LOCK, event.get_modifier_state("NumLock"));
+    update_modifiers(modifiers)
+}
+
+/// Returns the logical key for the current layout and the physical key.
+/// The browser's `code` is the physical key and `key` is the character produced or the key's name.
+fn get_keys(event: &web_sys::KeyboardEvent) -> (Key, Key) {
+    let physical_key = keys_web::virtual_keycode_to_key(&event.code());
+    let key_value = event.key();
+    let mut characters = key_value.chars();
+    let key = match (characters.next(), characters.next()) {
+        (Some(character), None) => Key::from_character(character),
+        _ => keys_web::virtual_keycode_to_key(&key_value),
+    };
+    let key = if key == Key::Unknown {
+        physical_key
+    } else {
+        key
+    };
+    (key, physical_key)
+}
+
 fn get_pointer_type(event: &web_sys::PointerEvent) -> PointerSource {
     match event.pointer_type().as_str() {
         "mouse" => PointerSource::Mouse,
@@ -296,6 +469,15 @@ fn get_pointer_type(event: &web_sys::PointerEvent) -> PointerSource {
     }
 }
 
+/// The mouse is always 0, so other pointers are offset by one.
+fn get_pointer_id(event: &web_sys::PointerEvent) -> u64 {
+    if event.pointer_type() == "mouse" {
+        0
+    } else {
+        event.pointer_id() as u32 as u64 + 1
+    }
+}
+
 fn get_pointer_position(event: &web_sys::PointerEvent) -> (f64, f64) {
     // 0,0 is the upper left of the canvas on web, so no transformations need to be performed.
     (event.client_x().into(), event.client_y().into())
@@ -307,6 +489,49 @@ fn request_animation_frame(f: &Closure<dyn FnMut()>) {
         .expect("should register `requestAnimationFrame` OK");
 }
 
+pub fn set_control_flow(control_flow: ControlFlow) {
+    unsafe {
+        CONTROL_FLOW = control_flow;
+        // A wake-up scheduled for the previous control flow may be too late for the new one.
+        if let Some(handle) = CONTROL_FLOW_TIMEOUT.take() {
+            window().clear_timeout_with_handle(handle);
+            CONTROL_FLOW_FRAME_REQUESTED = false;
+        }
+        request_control_flow_frame();
+    }
+}
+
+/// The browser owns the event loop, so instead of running it again `EventsCleared` is sent
+/// every animation frame with `Poll`, or from a `setTimeout` with `WaitUntil`.
+/// Once a `WaitUntil` has passed it's sent every animation frame, like on other platforms.
+unsafe fn request_control_flow_frame() {
+    if CONTROL_FLOW_FRAME_REQUESTED {
+        return;
+    }
+    let closure = match CONTROL_FLOW_CLOSURE.as_ref() {
+        Some(closure) => closure,
+        None => return,
+    };
+    match CONTROL_FLOW.wait_duration(None) {
+        None => {}
+        Some(wait) if wait == Duration::from_secs(0) => {
+            CONTROL_FLOW_FRAME_REQUESTED = true;
+            request_animation_frame(closure);
+        }
+        Some(wait) => {
+            // Rounded up so that a `WaitUntil` isn't woken just before its instant.
+            let milliseconds = ((wait.as_micros() + 999) / 1000).min(i32::MAX as u128) as i32;
+            CONTROL_FLOW_FRAME_REQUESTED = true;
+            CONTROL_FLOW_TIMEOUT = window()
+                .set_timeout_with_callback_and_timeout_and_arguments_0(
+                    closure.as_ref().unchecked_ref(),
+                    milliseconds,
+                )
+                .ok();
+        }
+    }
+}
+
 pub fn request_frame() {
     unsafe {
         request_animation_frame(REQUEST_ANIMATION_FRAME_CLOSURE.as_ref().unwrap());
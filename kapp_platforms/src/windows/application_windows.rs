@@ -1,6 +1,11 @@
 use super::external_windows::*;
+use super::keys_windows::scancode_to_key;
 use super::utils_windows::*;
 use std::convert::TryInto;
+use std::ffi::OsString;
+use std::iter::once;
+use std::os::windows::ffi::{OsStrExt, OsStringExt};
+use std::path::PathBuf;
 use std::ptr::null_mut;
 
 use kapp_platform_common::*;
@@ -19,14 +24,29 @@ pub(crate) struct WindowData {
     pub minimum_height: u32,
     pub maximum_width: u32,
     pub maximum_height: u32,
+    pub resizable: bool,
+    pub decorations: bool,
+    pub aspect_ratio: Option<(u32, u32)>, // Enforced by WM_SIZING.
+    pub resize_increments: Option<(u32, u32)>, // Enforced by WM_SIZING.
+    pub modal_parent: Option<HWND>,       // Disabled while a Dialog is open.
+    pub hit_test: Option<HitTest>,        // Consulted by WM_NCHITTEST.
+    pub pointer_inside: bool,             // Used to send PointerEntered and request WM_MOUSELEAVE.
+    pub pointer_clipped: bool, // Set when the pointer is locked or confined to the window.
 }
 
 impl PlatformApplicationTrait for PlatformApplication {
     type EventLoop = PlatformEventLoop;
-    fn new() -> Self {
+    type EventLoopProxy = PlatformEventLoopProxy;
+    fn new(app_info: &AppInfo) -> Self {
         unsafe {
             SetProcessDpiAwareness(PROCESS_PER_MONITOR_DPI_AWARE);
 
+            // Windows with the same application user model id are grouped in the taskbar.
+            if !app_info.id.is_empty() {
+                let id = win32_string(&app_info.id);
+                SetCurrentProcessExplicitAppUserModelID(id.as_ptr());
+            }
+
             // Register the window class.
             let window_class_name = win32_string("windowing_rust");
             let h_instance = GetModuleHandleW(null_mut());
@@ -58,6 +78,12 @@ impl PlatformApplicationTrait for PlatformApplication {
         PlatformEventLoop {}
     }
 
+    fn event_loop_proxy(&mut self) -> Self::EventLoopProxy {
+        PlatformEventLoopProxy {
+            thread_id: unsafe { GetCurrentThreadId() },
+        }
+    }
+
     fn set_window_position(&mut self, window_id: WindowId, x: u32, y: u32) {
         unsafe {
             let mut rect = RECT {
@@ -139,17 +165,178 @@ impl PlatformApplicationTrait for PlatformApplication {
             );
         }
     }
+    fn show_window(&mut self, window_id: WindowId) {
+        unsafe {
+            ShowWindow(window_id.raw() as HWND, SW_SHOW);
+        }
+    }
+    fn hide_window(&mut self, window_id: WindowId) {
+        unsafe {
+            ShowWindow(window_id.raw() as HWND, SW_HIDE);
+        }
+    }
+    // Windows may flash the taskbar button instead if another application is in the foreground.
+    fn focus_window(&mut self, window_id: WindowId) {
+        unsafe {
+            SetForegroundWindow(window_id.raw() as HWND);
+        }
+    }
+    // Flashes the taskbar button until the window is focused.
+    fn request_window_attention(&mut self, window_id: WindowId) {
+        let flash_info = FLASHWINFO {
+            cbSize: std::mem::size_of::<FLASHWINFO>() as UINT,
+            hwnd: window_id.raw() as HWND,
+            dwFlags: FLASHW_ALL | FLASHW_TIMERNOFG,
+            uCount: 0,
+            dwTimeout: 0,
+        };
+        unsafe {
+            FlashWindowEx(&flash_info);
+        }
+    }
+    fn is_window_visible(&mut self, window_id: WindowId) -> bool {
+        unsafe { IsWindowVisible(window_id.raw() as HWND) != FALSE }
+    }
+    fn is_window_focused(&mut self, window_id: WindowId) -> bool {
+        unsafe { GetForegroundWindow() == window_id.raw() as HWND }
+    }
+    fn is_window_minimized(&mut self, window_id: WindowId) -> bool {
+        unsafe { IsIconic(window_id.raw() as HWND) != FALSE }
+    }
+    fn is_window_maximized(&mut self, window_id: WindowId) -> bool {
+        unsafe { IsZoomed(window_id.raw() as HWND) != FALSE }
+    }
+    fn set_window_min_size(&mut self, window_id: WindowId, size: Option<(u32, u32)>) {
+        unsafe {
+            if let Some(window_data) =
+                super::event_loop_windows::get_window_data(window_id.raw() as HWND)
+            {
+                let (minimum_width, minimum_height) = size.unwrap_or((
+                    GetSystemMetrics(SM_CXMINTRACK) as u32,
+                    GetSystemMetrics(SM_CYMINTRACK) as u32,
+                ));
+                (*window_data).minimum_width = minimum_width;
+                (*window_data).minimum_height = minimum_height;
+            }
+        }
+    }
+    fn set_window_max_size(&mut self, window_id: WindowId, size: Option<(u32, u32)>) {
+        unsafe {
+            if let Some(window_data) =
+                super::event_loop_windows::get_window_data(window_id.raw() as HWND)
+            {
+                let (maximum_width, maximum_height) = size.unwrap_or((
+                    GetSystemMetrics(SM_CXMAXTRACK) as u32,
+                    GetSystemMetrics(SM_CYMAXTRACK) as u32,
+                ));
+                (*window_data).maximum_width = maximum_width;
+                (*window_data).maximum_height = maximum_height;
+            }
+        }
+    }
+    fn set_window_resizable(&mut self, window_id: WindowId, resizable: bool) {
+        unsafe {
+            let hwnd = window_id.raw() as HWND;
+            if let Some(window_data) = super::event_loop_windows::get_window_data(hwnd) {
+                (*window_data).resizable = resizable;
+                update_window_style(hwnd, &*window_data);
+            }
+        }
+    }
+    fn set_window_decorations(&mut self, window_id: WindowId, decorations: bool) {
+        unsafe {
+            let hwnd = window_id.raw() as HWND;
+            if let Some(window_data) = super::event_loop_windows::get_window_data(hwnd) {
+                (*window_data).decorations = decorations;
+                update_window_style(hwnd, &*window_data);
+            }
+        }
+    }
+    fn set_window_aspect_ratio(&mut self, window_id: WindowId, ratio: Option<(u32, u32)>) {
+        unsafe {
+            if let Some(window_data) =
+                super::event_loop_windows::get_window_data(window_id.raw() as HWND)
+            {
+                (*window_data).aspect_ratio =
+                    ratio.filter(|(width, height)| *width > 0 && *height > 0);
+            }
+        }
+    }
+    fn set_window_resize_increments(
+        &mut self,
+        window_id: WindowId,
+        increments: Option<(u32, u32)>,
+    ) {
+        unsafe {
+            if let Some(window_data) =
+                super::event_loop_windows::get_window_data(window_id.raw() as HWND)
+            {
+                (*window_data).resize_increments = increments;
+            }
+        }
+    }
+    fn set_window_hit_test(&mut self, window_id: WindowId, hit_test: Option<HitTest>) {
+        if let Some(window_data) =
+            super::event_loop_windows::get_window_data(window_id.raw() as HWND)
+        {
+            unsafe {
+                (*window_data).hit_test = hit_test;
+            }
+        }
+    }
+    fn begin_window_drag_move(&mut self, window_id: WindowId) {
+        unsafe {
+            begin_non_client_drag(window_id.raw() as HWND, HTCAPTION);
+        }
+    }
+    fn begin_window_drag_resize(&mut self, window_id: WindowId, edge: ResizeEdge) {
+        let hit_test_result = match edge {
+            ResizeEdge::North => HTTOP,
+            ResizeEdge::South => HTBOTTOM,
+            ResizeEdge::East => HTRIGHT,
+            ResizeEdge::West => HTLEFT,
+            ResizeEdge::NorthEast => HTTOPRIGHT,
+            ResizeEdge::NorthWest => HTTOPLEFT,
+            ResizeEdge::SouthEast => HTBOTTOMRIGHT,
+            ResizeEdge::SouthWest => HTBOTTOMLEFT,
+        };
+        unsafe {
+            begin_non_client_drag(window_id.raw() as HWND, hit_test_result);
+        }
+    }
+    // Opacity requires a layered window, which is removed again for opaque windows.
+    fn set_window_opacity(&mut self, window_id: WindowId, opacity: f64) {
+        unsafe {
+            let hwnd = window_id.raw() as HWND;
+            let extended_style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE) as DWORD;
+            if opacity < 1.0 {
+                SetWindowLongPtrW(hwnd, GWL_EXSTYLE, (extended_style | WS_EX_LAYERED) as _);
+                SetLayeredWindowAttributes(hwnd, 0, (opacity * 255.0) as BYTE, LWA_ALPHA);
+            } else {
+                SetWindowLongPtrW(hwnd, GWL_EXSTYLE, (extended_style & !WS_EX_LAYERED) as _);
+            }
+        }
+    }
     fn restore_window(&mut self, window_id: WindowId) {
         unsafe {
             let hwnd = window_id.raw() as HWND;
-            let window_style = WS_OVERLAPPEDWINDOW | WS_VISIBLE | CS_OWNDC;
-            SetWindowLongPtrW(hwnd, GWL_STYLE, window_style.try_into().unwrap());
+            let window_style = match super::event_loop_windows::get_window_data(hwnd) {
+                Some(window_data) => {
+                    window_style((*window_data).resizable, (*window_data).decorations)
+                }
+                None => WS_OVERLAPPEDWINDOW | CS_OWNDC,
+            } | WS_VISIBLE;
+            SetWindowLongPtrW(hwnd, GWL_STYLE, window_style as _);
             ShowWindow(window_id.raw() as HWND, SW_RESTORE);
         }
     }
     fn close_window(&mut self, window_id: WindowId) {
         unsafe {
-            CloseWindow(window_id.raw() as HWND);
+            let hwnd = window_id.raw() as HWND;
+            // The parent must be enabled before its dialog closes or another application is activated.
+            release_modal_parent(hwnd);
+            // The window's `WindowData` is freed when it receives WM_NCDESTROY.
+            DestroyWindow(hwnd);
         }
     }
 
@@ -190,18 +377,146 @@ impl PlatformApplicationTrait for PlatformApplication {
         todo!();
     }
 
+    fn set_pointer_position(&mut self, window_id: WindowId, x: f64, y: f64) {
+        unsafe {
+            let mut position = POINT {
+                x: x as LONG,
+                y: y as LONG,
+            };
+            ClientToScreen(window_id.raw() as HWND, &mut position);
+            SetCursorPos(position.x, position.y);
+        }
+    }
+
+    fn confine_pointer(&mut self, window_id: WindowId, confine: bool) {
+        unsafe {
+            if confine {
+                let hwnd = window_id.raw() as HWND;
+                let mut rect = RECT {
+                    left: 0,
+                    top: 0,
+                    right: 0,
+                    bottom: 0,
+                };
+                GetClientRect(hwnd, &mut rect);
+                let mut top_left = POINT {
+                    x: rect.left,
+                    y: rect.top,
+                };
+                let mut bottom_right = POINT {
+                    x: rect.right,
+                    y: rect.bottom,
+                };
+                ClientToScreen(hwnd, &mut top_left);
+                ClientToScreen(hwnd, &mut bottom_right);
+                clip_pointer(
+                    hwnd,
+                    Some(RECT {
+                        left: top_left.x,
+                        top: top_left.y,
+                        right: bottom_right.x,
+                        bottom: bottom_right.y,
+                    }),
+                );
+            } else {
+                clip_pointer(window_id.raw() as HWND, None);
+            }
+        }
+    }
+
+    // The pointer is locked by clipping it to its current position.
+    fn lock_pointer(&mut self, window_id: WindowId) {
+        unsafe {
+            let mut position = POINT { x: 0, y: 0 };
+            GetCursorPos(&mut position);
+            clip_pointer(
+                window_id.raw() as HWND,
+                Some(RECT {
+                    left: position.x,
+                    top: position.y,
+                    right: position.x + 1,
+                    bottom: position.y + 1,
+                }),
+            );
+        }
+    }
+
+    fn unlock_pointer(&mut self, window_id: WindowId) {
+        unsafe {
+            clip_pointer(window_id.raw() as HWND, None);
+        }
+    }
+
+    // Raw input is sent to the focused window with WM_INPUT.
+    // https://docs.microsoft.com/en-us/windows/win32/inputdev/about-raw-input
+    fn set_raw_mouse_motion(&mut self, enabled: bool) {
+        let device = RAWINPUTDEVICE {
+            usUsagePage: HID_USAGE_PAGE_GENERIC,
+            usUsage: HID_USAGE_GENERIC_MOUSE,
+            dwFlags: if enabled { 0 } else { RIDEV_REMOVE },
+            hwndTarget: null_mut(),
+        };
+        unsafe {
+            RegisterRawInputDevices(&device, 1, std::mem::size_of::<RAWINPUTDEVICE>() as UINT);
+        }
+    }
+
     fn new_window(&mut self, window_parameters: &WindowParameters) -> WindowId {
         unsafe {
-            let extended_style = WS_EX_APPWINDOW;
-            let window_style = WS_OVERLAPPEDWINDOW | WS_VISIBLE | CS_OWNDC;
+            let kind = window_parameters.kind;
+            let parent = window_parameters.parent.map(|parent| parent.raw() as HWND);
+
+            // Tool windows don't appear in the taskbar.
+            let mut extended_style = match kind {
+                WindowKind::Normal | WindowKind::Splash => WS_EX_APPWINDOW,
+                WindowKind::Dialog => 0,
+                WindowKind::Popup | WindowKind::Utility => WS_EX_TOOLWINDOW,
+                WindowKind::Tooltip => WS_EX_TOOLWINDOW | WS_EX_NOACTIVATE | WS_EX_TOPMOST,
+            };
+            if window_parameters.always_on_top {
+                extended_style |= WS_EX_TOPMOST;
+            }
+
+            let frameless = matches!(
+                kind,
+                WindowKind::Popup | WindowKind::Tooltip | WindowKind::Splash
+            );
+            let resizable = window_parameters.resizable && !frameless;
+            let decorations = !window_parameters.without_titlebar && !frameless;
+
+            // Popups and tooltips leave focus with their parent.
+            let focused = window_parameters.focused
+                && !matches!(kind, WindowKind::Popup | WindowKind::Tooltip);
+
+            // Windows that shouldn't take focus are shown after they're created instead.
+            let mut window_style = window_style(resizable, decorations);
+            if window_parameters.visible && focused {
+                window_style |= WS_VISIBLE;
+            }
             let title = win32_string(&window_parameters.title);
 
-            let (x, y) = if let Some(position) = window_parameters.position {
-                (position.0 as i32, position.1 as i32)
-            } else {
-                (CW_USEDEFAULT, CW_USEDEFAULT)
+            let (x, y) = match (window_parameters.position, parent) {
+                // Popups and tooltips are positioned relative to their parent's client area.
+                (Some((x, y)), Some(parent))
+                    if matches!(kind, WindowKind::Popup | WindowKind::Tooltip) =>
+                {
+                    let mut position = POINT {
+                        x: x as i32,
+                        y: y as i32,
+                    };
+                    ClientToScreen(parent, &mut position);
+                    (position.x, position.y)
+                }
+                (Some((x, y)), _) => (x as i32, y as i32),
+                (None, _) => (CW_USEDEFAULT, CW_USEDEFAULT),
             };
 
+            // A dialog blocks input to its parent until it's closed.
+            let modal_parent = parent.filter(|_| kind == WindowKind::Dialog);
+            if let Some(modal_parent) = modal_parent {
+                EnableWindow(modal_parent, FALSE);
+            }
+
             let (width, height) =
                 window_parameters
                     .size
@@ -233,6 +548,14 @@ impl PlatformApplicationTrait for PlatformApplication {
                 minimum_height,
                 maximum_width,
                 maximum_height,
+                resizable,
+                decorations,
+                aspect_ratio: None,
+                resize_increments: None,
+                modal_parent,
+                hit_test: None,
+                pointer_inside: false,
+                pointer_clipped: false,
             });
 
             let data = Box::leak(window_data) as *mut WindowData as *mut std::ffi::c_void;
@@ -245,12 +568,30 @@ impl PlatformApplicationTrait for PlatformApplication {
                 y as i32,
                 width,
                 height,
-                null_mut(),
+                parent.unwrap_or(null_mut()),
                 null_mut(),
                 self.h_instance,
                 data,
             );
 
+            // Enabling blur behind an empty region makes DWM blend the window's alpha channel
+            // with what's behind it, without blurring anything.
+            if window_parameters.transparent {
+                let region = CreateRectRgn(0, 0, -1, -1);
+                let blur_behind = DWM_BLURBEHIND {
+                    dwFlags: DWM_BB_ENABLE | DWM_BB_BLURREGION,
+                    fEnable: TRUE,
+                    hRgnBlur: region,
+                    fTransitionOnMaximized: FALSE,
+                };
+                DwmEnableBlurBehindWindow(window_handle, &blur_behind);
+                DeleteObject(region as HGDIOBJ);
+            }
+
+            if window_parameters.visible && !focused {
+                ShowWindow(window_handle, SW_SHOWNOACTIVATE);
+            }
+
             let window_id = WindowId::new(window_handle as *mut std::ffi::c_void);
             // When a window is created immediately request that it should redraw
             redraw_manager::add_draw_request(window_id);
@@ -259,12 +600,65 @@ impl PlatformApplicationTrait for PlatformApplication {
         }
     }
 
-    fn quit(&self) {
+    fn quit(&self, exit_code: i32) {
         unsafe {
-            PostQuitMessage(0);
+            PostQuitMessage(exit_code);
         }
     }
 
+    fn set_control_flow(&mut self, control_flow: ControlFlow) {
+        super::event_loop_windows::set_control_flow(control_flow);
+    }
+
+    fn message_box(&mut self, message_box: &MessageBox) -> Option<MessageBoxButton> {
+        let buttons = match message_box.buttons {
+            MessageBoxButtons::Ok => MB_OK,
+            MessageBoxButtons::OkCancel => MB_OKCANCEL,
+            MessageBoxButtons::YesNo => MB_YESNO,
+            MessageBoxButtons::YesNoCancel => MB_YESNOCANCEL,
+        };
+        let icon = match message_box.kind {
+            MessageBoxKind::Info => MB_ICONINFORMATION,
+            MessageBoxKind::Warning => MB_ICONWARNING,
+            MessageBoxKind::Error => MB_ICONERROR,
+        };
+
+        let title = win32_string(&message_box.title);
+        let text = win32_string(&message_box.text);
+
+        // MB_TASKMODAL disables the thread's windows while the message box is open.
+        // The message box runs its own message loop that still dispatches to kapp's windows.
+        // If this is called from the user callback those events are queued until it returns.
+        let result = unsafe {
+            MessageBoxW(
+                null_mut(),
+                text.as_ptr(),
+                title.as_ptr(),
+                buttons | icon | MB_TASKMODAL,
+            )
+        };
+
+        match result {
+            IDOK => Some(MessageBoxButton::Ok),
+            IDCANCEL => Some(MessageBoxButton::Cancel),
+            IDYES => Some(MessageBoxButton::Yes),
+            IDNO => Some(MessageBoxButton::No),
+            _ => None,
+        }
+    }
+
+    // The dialogs are modal and the result is sent when they close.
+    // Picking folders isn't supported, so `directory` is ignored.
+    fn open_file_dialog(&mut self, id: FileDialogId, options: &FileDialogOptions) {
+        let paths = unsafe { file_dialog(options, false) };
+        event_receiver::send_event(Event::FileDialogResult { id, paths });
+    }
+
+    fn save_file_dialog(&mut self, id: FileDialogId, options: &FileDialogOptions) {
+        let paths = unsafe { file_dialog(options, true) };
+        event_receiver::send_event(Event::FileDialogResult { id, paths });
+    }
+
     fn set_cursor(&mut self, cursor: Cursor) {
         unsafe {
             // List of cursors here:
@@ -312,37 +706,301 @@ impl PlatformApplicationTrait for PlatformApplication {
         })
     }
 
-    fn start_text_input(&mut self) {
-        todo!()
+    fn start_text_input(&mut self, window_id: Option<WindowId>) {
+        text_input_manager::start_text_input(window_id);
+        unsafe {
+            update_text_input(match window_id {
+                Some(window_id) => window_id.raw() as HWND,
+                None => GetFocus(),
+            });
+        }
     }
 
-    fn end_text_input(&mut self) {
-        todo!()
+    fn end_text_input(&mut self, window_id: Option<WindowId>) {
+        text_input_manager::end_text_input(window_id);
+        unsafe {
+            update_text_input(match window_id {
+                Some(window_id) => window_id.raw() as HWND,
+                None => GetFocus(),
+            });
+        }
     }
 
+    // https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getkeynametextw
+    fn key_label(&self, physical_key: Key) -> Option<String> {
+        if physical_key == Key::Unknown {
+            return None;
+        }
+        let scancode = (0..=0xFF)
+            .chain(0xE000..=0xE0FF)
+            .find(|scancode| scancode_to_key(*scancode) == physical_key)?;
+
+        // The scancode goes in bits 16-23 and bit 24 marks an extended key.
+        let extended = if scancode & 0xE000 != 0 { 1 << 24 } else { 0 };
+        let l_param = ((scancode & 0xFF) << 16) | extended;
+
+        let mut buffer = [0u16; 64];
+        let length =
+            unsafe { GetKeyNameTextW(l_param as LONG, buffer.as_mut_ptr(), buffer.len() as c_int) };
+        if length > 0 {
+            String::from_utf16(&buffer[..length as usize]).ok()
+        } else {
+            None
+        }
+    }
+
+    // The composition is drawn at the rectangle's position and candidates are placed to not cover it.
     fn set_text_input_rectangle(
         &mut self,
-        _window_id: WindowId,
-        _x: f64,
-        _y: f64,
-        _width: f64,
-        _height: f64,
+        window_id: WindowId,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
     ) {
-        todo!()
+        unsafe {
+            let hwnd = window_id.raw() as HWND;
+            let himc = ImmGetContext(hwnd);
+            if himc == null_mut() {
+                return;
+            }
+            let position = POINT {
+                x: x as LONG,
+                y: y as LONG,
+            };
+            let area = RECT {
+                left: x as LONG,
+                top: y as LONG,
+                right: (x + width) as LONG,
+                bottom: (y + height) as LONG,
+            };
+            ImmSetCompositionWindow(
+                himc,
+                &COMPOSITIONFORM {
+                    dwStyle: CFS_POINT,
+                    ptCurrentPos: position,
+                    rcArea: area,
+                },
+            );
+            ImmSetCandidateWindow(
+                himc,
+                &CANDIDATEFORM {
+                    dwIndex: 0,
+                    dwStyle: CFS_EXCLUDE,
+                    ptCurrentPos: position,
+                    rcArea: area,
+                },
+            );
+            ImmReleaseContext(hwnd, himc);
+        }
     }
 }
 
 // When the application is dropped, quit the program.
 impl Drop for PlatformApplication {
     fn drop(&mut self) {
-        self.quit();
+        self.quit(0);
+    }
+}
+
+#[derive(Clone)]
+pub struct PlatformEventLoopProxy {
+    thread_id: DWORD, // The thread that runs the event loop.
+}
+
+impl PlatformEventLoopProxyTrait for PlatformEventLoopProxy {
+    fn send(&self, event: UserEvent) {
+        super::event_loop_windows::send_user_event(self.thread_id, event);
     }
 }
 
 pub struct PlatformEventLoop {}
 
 impl PlatformEventLoopTrait for PlatformEventLoop {
-    fn run(&self, callback: Box<dyn FnMut(kapp_platform_common::Event)>) {
-        super::event_loop_windows::run(callback);
+    fn run(&self, callback: Box<dyn FnMut(kapp_platform_common::Event)>) -> i32 {
+        super::event_loop_windows::run(callback)
+    }
+}
+
+impl PlatformEventLoopPumpTrait for PlatformEventLoop {
+    fn pump_events(
+        &self,
+        timeout: Option<std::time::Duration>,
+        callback: Box<dyn FnMut(kapp_platform_common::Event)>,
+    ) {
+        super::event_loop_windows::pump_events(timeout, callback);
+    }
+}
+
+/// Each window has its own input context, which is removed to disable the IME.
+/// Windows are also updated as they gain focus, which handles text input enabled for every window.
+pub(crate) unsafe fn update_text_input(hwnd: HWND) {
+    if hwnd == null_mut() {
+        return;
+    }
+    let window_id = WindowId::new(hwnd as *mut std::ffi::c_void);
+    if text_input_manager::text_input_enabled(window_id) {
+        ImmAssociateContextEx(hwnd, null_mut(), IACE_DEFAULT);
+    } else {
+        ImmAssociateContextEx(hwnd, null_mut(), 0);
+    }
+}
+
+/// Re-enables the parent of a dialog so it can receive input again.
+pub(crate) unsafe fn release_modal_parent(hwnd: HWND) {
+    if let Some(window_data) = super::event_loop_windows::get_window_data(hwnd) {
+        if let Some(parent) = (*window_data).modal_parent.take() {
+            EnableWindow(parent, TRUE);
+        }
+    }
+}
+
+/// Shows a modal open or save dialog and returns the picked paths.
+unsafe fn file_dialog(options: &FileDialogOptions, save: bool) -> Vec<PathBuf> {
+    // Filters are pairs of null terminated strings, like "Images\0*.png;*.jpg\0",
+    // with an extra null at the end.
+    let mut filter = String::new();
+    for f in &options.filters {
+        let patterns: Vec<String> = f.extensions.iter().map(|e| format!("*.{}", e)).collect();
+        filter += &format!("{}\0{}\0", f.name, patterns.join(";"));
+    }
+    let filter = win32_string(&filter);
+    let title = win32_string(&options.title);
+    let initial_dir = options.current_folder.as_ref().map(|folder| {
+        folder
+            .as_os_str()
+            .encode_wide()
+            .chain(once(0))
+            .collect::<Vec<u16>>()
+    });
+
+    // With multiple selection the buffer can hold many paths.
+    let mut file = vec![0u16; 32 * 1024];
+    if save {
+        let name: Vec<u16> = options.current_name.encode_utf16().collect();
+        let length = name.len().min(file.len() - 1);
+        file[..length].copy_from_slice(&name[..length]);
+    }
+
+    let mut flags = OFN_EXPLORER | OFN_PATHMUSTEXIST | OFN_NOCHANGEDIR;
+    if save {
+        flags |= OFN_OVERWRITEPROMPT;
+    } else {
+        flags |= OFN_FILEMUSTEXIST;
+        if options.multiple {
+            flags |= OFN_ALLOWMULTISELECT;
+        }
+    }
+
+    let mut open_file_name = OPENFILENAMEW {
+        lStructSize: std::mem::size_of::<OPENFILENAMEW>() as DWORD,
+        hwndOwner: null_mut(),
+        hInstance: null_mut(),
+        lpstrFilter: if options.filters.is_empty() {
+            std::ptr::null()
+        } else {
+            filter.as_ptr()
+        },
+        lpstrCustomFilter: null_mut(),
+        nMaxCustFilter: 0,
+        nFilterIndex: 0,
+        lpstrFile: file.as_mut_ptr(),
+        nMaxFile: file.len() as DWORD,
+        lpstrFileTitle: null_mut(),
+        nMaxFileTitle: 0,
+        lpstrInitialDir: initial_dir
+            .as_ref()
+            .map_or(std::ptr::null(), |d| d.as_ptr()),
+        lpstrTitle: if options.title.is_empty() {
+            std::ptr::null()
+        } else {
+            title.as_ptr()
+        },
+        Flags: flags,
+        nFileOffset: 0,
+        nFileExtension: 0,
+        lpstrDefExt: std::ptr::null(),
+        lCustData: 0,
+        lpfnHook: null_mut(),
+        lpTemplateName: std::ptr::null(),
+        pvReserved: null_mut(),
+        dwReserved: 0,
+        FlagsEx: 0,
+    };
+
+    let result = if save {
+        GetSaveFileNameW(&mut open_file_name)
+    } else {
+        GetOpenFileNameW(&mut open_file_name)
+    };
+    if result == FALSE {
+        return Vec::new();
+    }
+
+    // A single path is returned as is. Multiple files are returned as the folder followed by
+    // each file name, separated by nulls and ending with two nulls.
+    let mut parts = file
+        .split(|c| *c == 0)
+        .take_while(|part| !part.is_empty())
+        .map(|part| PathBuf::from(OsString::from_wide(part)));
+    let first = parts.next().unwrap_or_default();
+    let names: Vec<PathBuf> = parts.map(|name| first.join(name)).collect();
+    if names.is_empty() {
+        vec![first]
+    } else {
+        names
+    }
+}
+
+/// Returns the style for a window with or without a resizable frame and decorations.
+fn window_style(resizable: bool, decorations: bool) -> DWORD {
+    let mut window_style = WS_OVERLAPPEDWINDOW | CS_OWNDC;
+    if !resizable {
+        window_style &= !(WS_THICKFRAME | WS_MAXIMIZEBOX);
+    }
+    if !decorations {
+        window_style = (window_style & !(WS_CAPTION | WS_SYSMENU)) | WS_POPUP;
+    }
+    window_style
+}
+
+/// Applies a window's resizability and decorations to its style.
+/// `SWP_FRAMECHANGED` lets Windows know the frame needs to be recalculated.
+unsafe fn update_window_style(hwnd: HWND, window_data: &WindowData) {
+    let mut style = window_style(window_data.resizable, window_data.decorations);
+    if IsWindowVisible(hwnd) != FALSE {
+        style |= WS_VISIBLE;
+    }
+    SetWindowLongPtrW(hwnd, GWL_STYLE, style as _);
+    SetWindowPos(
+        hwnd,
+        null_mut(),
+        0,
+        0,
+        0,
+        0,
+        SWP_NOMOVE | SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE | SWP_FRAMECHANGED,
+    );
+}
+
+/// Starts the OS's move or resize loop as if the pointer was pressed on part of the window's frame.
+/// The message is posted so the loop doesn't start while the event callback is still running.
+unsafe fn begin_non_client_drag(hwnd: HWND, hit_test_result: LRESULT) {
+    let mut position = POINT { x: 0, y: 0 };
+    GetCursorPos(&mut position);
+    ReleaseCapture();
+    let l_param = ((position.y as LPARAM) << 16) | (position.x as LPARAM & 0xffff);
+    PostMessageW(hwnd, WM_NCLBUTTONDOWN, hit_test_result as WPARAM, l_param);
+}
+
+/// Clips the pointer to a rectangle in screen coordinates, or releases it if `None`.
+/// The clip is released by `WM_KILLFOCUS` when the window loses focus.
+unsafe fn clip_pointer(hwnd: HWND, rect: Option<RECT>) {
+    match &rect {
+        Some(rect) => ClipCursor(rect),
+        None => ClipCursor(null_mut()),
+    };
+    if let Some(window_data) = super::event_loop_windows::get_window_data(hwnd) {
+        (*window_data).pointer_clipped = rect.is_some();
     }
 }
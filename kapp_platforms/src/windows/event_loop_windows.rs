@@ -1,10 +1,16 @@
 use super::application_windows::WindowData;
-use super::{external_windows::*, keys_windows::virtual_keycode_to_key};
+use super::{
+    external_windows::*,
+    keys_windows::{scancode_to_key, virtual_keycode_to_key},
+};
 use kapp_platform_common::*;
 
+use std::cell::Cell;
 use std::ptr::null_mut;
 use std::convert::TryInto;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
 
 pub static mut DBLCLICK_L: AtomicBool = AtomicBool::new(false);
 pub static mut DBLCLICK_M: AtomicBool = AtomicBool::new(false);
@@ -12,6 +18,17 @@ pub static mut DBLCLICK_R: AtomicBool = AtomicBool::new(false);
 pub static mut DBLCLICK_X1: AtomicBool = AtomicBool::new(false);
 pub static mut DBLCLICK_X2: AtomicBool = AtomicBool::new(false);
 
+// Pushed by `PlatformEventLoopProxy` from any thread and sent from the event loop.
+static USER_EVENTS: Mutex<Vec<UserEvent>> = Mutex::new(Vec::new());
+
+// Posted to the event loop's thread to wake it when a user event is sent.
+const WM_USER_EVENT: UINT = WM_APP;
+
+thread_local! {
+    static LAST_MODIFIERS: Cell<Modifiers> = Cell::new(Modifiers::empty());
+    static CONTROL_FLOW: Cell<ControlFlow> = Cell::new(ControlFlow::Wait);
+}
+
 pub unsafe extern "system" fn window_callback(
     hwnd: HWND,
     u_msg: UINT,
@@ -34,21 +51,55 @@ pub unsafe extern "system" fn window_callback(
             // Return 0 to reject the close because the user application must approve the close.
             return 0;
         }
-        WM_KEYDOWN | WM_SYSKEYDOWN => produce_event(process_key_down(w_param, l_param)),
-        WM_KEYUP | WM_SYSKEYUP => produce_event(process_key_up(w_param, l_param)),
-        WM_CHAR => {
+        WM_KEYDOWN | WM_SYSKEYDOWN => {
+            update_modifiers();
+            produce_event(process_key_down(hwnd, w_param, l_param))
+        }
+        WM_KEYUP | WM_SYSKEYUP => {
+            update_modifiers();
+            produce_event(process_key_up(hwnd, w_param, l_param))
+        }
+        WM_INPUTLANGCHANGE => produce_event(Event::KeyboardLayoutChanged),
+        WM_SETFOCUS => {
+            // Modifiers may have changed while another application had focus.
+            update_modifiers();
+            super::application_windows::update_text_input(hwnd);
+        }
+        WM_KILLFOCUS => {
+            // The clip rectangle is shared by all windows so it must be released.
+            if let Some(window_data) = get_window_data(hwnd) {
+                if (*window_data).pointer_clipped {
+                    (*window_data).pointer_clipped = false;
+                    ClipCursor(null_mut());
+                    produce_event(Event::PointerLockLost {
+                        window_id: WindowId::new(hwnd as *mut std::ffi::c_void),
+                    });
+                }
+            }
+        }
+        // Characters are only sent for windows with text input enabled.
+        WM_CHAR
+            if text_input_manager::text_input_enabled(WindowId::new(
+                hwnd as *mut std::ffi::c_void,
+            )) =>
+        {
             let character = String::from_utf16(&[w_param as u16])
                 .unwrap()
                 .chars()
                 .next()
                 .unwrap();
-            produce_event(Event::CharacterReceived { character });
+            produce_event(Event::CharacterReceived {
+                character,
+                window_id: WindowId::new(hwnd as *mut std::ffi::c_void),
+            });
         }
         WM_IME_STARTCOMPOSITION => {
             return 0;
         }
         WM_IME_ENDCOMPOSITION => {
-            produce_event(Event::IMEEndComposition);
+            produce_event(Event::IMEEndComposition {
+                window_id: WindowId::new(hwnd as *mut std::ffi::c_void),
+            });
             return 0;
         }
         WM_IME_COMPOSITION => {
@@ -57,19 +108,67 @@ pub unsafe extern "system" fn window_callback(
                 if himc == null_mut() {
                     return 0;
                 }
-                let size_bytes = ImmGetCompositionStringW(himc, GCS_COMPSTR, null_mut(), 0);
-                if size_bytes != 0 {
-                    let mut buffer = Vec::<u16>::with_capacity(size_bytes as usize / std::mem::size_of::<u16>());
-                    ImmGetCompositionStringW(himc, GCS_COMPSTR, buffer.as_mut_ptr().cast(), size_bytes as u32);
-                    buffer.set_len(size_bytes as usize / std::mem::size_of::<u16>());
+                let buffer = get_composition_data::<u16>(himc, GCS_COMPSTR);
+                if !buffer.is_empty() {
                     let composition = String::from_utf16(&buffer).unwrap();
-                    produce_event(Event::IMEComposition { composition });
+
+                    // The IME measures offsets in UTF-16 code units.
+                    let byte_offset = |offset: usize| {
+                        String::from_utf16_lossy(&buffer[..offset.min(buffer.len())]).len()
+                    };
+
+                    let cursor = if l_param as u32 & GCS_CURSORPOS != 0 {
+                        let cursor = ImmGetCompositionStringW(himc, GCS_CURSORPOS, null_mut(), 0);
+                        Some(byte_offset(cursor.max(0) as usize))
+                    } else {
+                        None
+                    };
+
+                    // Each UTF-16 code unit has an attribute and runs of the same attribute become a segment.
+                    let attributes = if l_param as u32 & GCS_COMPATTR != 0 {
+                        get_composition_data::<BYTE>(himc, GCS_COMPATTR)
+                    } else {
+                        Vec::new()
+                    };
+                    let mut segments = Vec::new();
+                    let mut start = 0;
+                    for i in 1..=attributes.len() {
+                        if i == attributes.len() || attributes[i] != attributes[start] {
+                            let kind = match attributes[start] {
+                                ATTR_TARGET_CONVERTED | ATTR_TARGET_NOTCONVERTED => CompositionSegmentKind::Target,
+                                ATTR_CONVERTED | ATTR_FIXEDCONVERTED => CompositionSegmentKind::Converted,
+                                _ => CompositionSegmentKind::Unconverted,
+                            };
+                            segments.push(CompositionSegment {
+                                range: byte_offset(start)..byte_offset(i),
+                                kind,
+                            });
+                            start = i;
+                        }
+                    }
+                    let selection = segments
+                        .iter()
+                        .find(|segment| segment.kind == CompositionSegmentKind::Target)
+                        .map(|segment| segment.range.clone());
+
+                    produce_event(Event::IMEComposition {
+                        composition,
+                        cursor,
+                        selection,
+                        segments,
+                        window_id: WindowId::new(hwnd as *mut std::ffi::c_void),
+                    });
                 }
                 ImmReleaseContext(hwnd, himc);
                 return 0;
             }
         }
-        WM_SIZING => return TRUE as isize,
+        WM_SIZING => {
+            if let Some(window_data) = get_window_data(hwnd) {
+                constrain_sizing_rect(hwnd, &*window_data, w_param, &mut *(l_param as *mut RECT));
+            }
+            return TRUE as isize;
+        }
         WM_SETCURSOR => {
             // Give the OS a chance to set the cursor first, and don't override it if it sets it.
             // The OS will not set the cursor within a window as the default cursor
@@ -107,6 +206,9 @@ pub unsafe extern "system" fn window_callback(
                 x: x as f64,
                 y: y as f64,
                 source: PointerSource::Mouse,
+                pointer_id: 0,
+                window_id: WindowId::new(hwnd as *mut std::ffi::c_void),
+                modifiers: get_modifiers(),
                 button: PointerButton::Primary,
                 timestamp: get_message_time(),
             });
@@ -119,6 +221,9 @@ pub unsafe extern "system" fn window_callback(
                 x: x as f64,
                 y: y as f64,
                 source: PointerSource::Mouse,
+                pointer_id: 0,
+                window_id: WindowId::new(hwnd as *mut std::ffi::c_void),
+                modifiers: get_modifiers(),
                 button: PointerButton::Auxillary,
                 timestamp: get_message_time(),
             });
@@ -131,6 +236,9 @@ pub unsafe extern "system" fn window_callback(
                 x: x as f64,
                 y: y as f64,
                 source: PointerSource::Mouse,
+                pointer_id: 0,
+                window_id: WindowId::new(hwnd as *mut std::ffi::c_void),
+                modifiers: get_modifiers(),
                 button: PointerButton::Secondary,
                 timestamp: get_message_time(),
             });
@@ -154,6 +262,9 @@ pub unsafe extern "system" fn window_callback(
                 x: x as f64,
                 y: y as f64,
                 source: PointerSource::Mouse,
+                pointer_id: 0,
+                window_id: WindowId::new(hwnd as *mut std::ffi::c_void),
+                modifiers: get_modifiers(),
                 button,
                 timestamp: get_message_time(),
             });
@@ -166,6 +277,9 @@ pub unsafe extern "system" fn window_callback(
                 x: x as f64,
                 y: y as f64,
                 source: PointerSource::Mouse,
+                pointer_id: 0,
+                window_id: WindowId::new(hwnd as *mut std::ffi::c_void),
+                modifiers: get_modifiers(),
                 button: PointerButton::Primary,
                 timestamp: get_message_time(),
             });
@@ -175,6 +289,7 @@ pub unsafe extern "system" fn window_callback(
                     x: x as f64,
                     y: y as f64,
                     button: PointerButton::Primary,
+                    window_id: WindowId::new(hwnd as *mut std::ffi::c_void),
                     timestamp: get_message_time(),
                 });
             }
@@ -187,6 +302,9 @@ pub unsafe extern "system" fn window_callback(
                 x: x as f64,
                 y: y as f64,
                 source: PointerSource::Mouse,
+                pointer_id: 0,
+                window_id: WindowId::new(hwnd as *mut std::ffi::c_void),
+                modifiers: get_modifiers(),
                 button: PointerButton::Auxillary,
                 timestamp: get_message_time(),
             });
@@ -196,6 +314,7 @@ pub unsafe extern "system" fn window_callback(
                     x: x as f64,
                     y: y as f64,
                     button: PointerButton::Auxillary,
+                    window_id: WindowId::new(hwnd as *mut std::ffi::c_void),
                     timestamp: get_message_time(),
                 });
             }
@@ -208,6 +327,9 @@ pub unsafe extern "system" fn window_callback(
                 x: x as f64,
                 y: y as f64,
                 source: PointerSource::Mouse,
+                pointer_id: 0,
+                window_id: WindowId::new(hwnd as *mut std::ffi::c_void),
+                modifiers: get_modifiers(),
                 button: PointerButton::Secondary,
                 timestamp: get_message_time(),
             });
@@ -217,6 +339,7 @@ pub unsafe extern "system" fn window_callback(
                     x: x as f64,
                     y: y as f64,
                     button: PointerButton::Secondary,
+                    window_id: WindowId::new(hwnd as *mut std::ffi::c_void),
                     timestamp: get_message_time(),
                 });
             }
@@ -233,6 +356,9 @@ pub unsafe extern "system" fn window_callback(
                 x: x as f64,
                 y: y as f64,
                 source: PointerSource::Mouse,
+                pointer_id: 0,
+                window_id: WindowId::new(hwnd as *mut std::ffi::c_void),
+                modifiers: get_modifiers(),
                 button,
                 timestamp: get_message_time(),
             });
@@ -246,11 +372,84 @@ pub unsafe extern "system" fn window_callback(
                     x: x as f64,
                     y: y as f64,
                     button,
+                    window_id: WindowId::new(hwnd as *mut std::ffi::c_void),
                     timestamp: get_message_time(),
                 });
             }
         }
-        WM_MOUSEMOVE => produce_event(process_mouse_move_event(hwnd, l_param)),
+        WM_MOUSEMOVE => {
+            if let Some(window_data) = get_window_data(hwnd) {
+                // Windows only sends WM_MOUSELEAVE if it's requested each time the pointer enters.
+                // https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-trackmouseevent
+                if !(*window_data).pointer_inside {
+                    (*window_data).pointer_inside = true;
+                    let mut track_mouse_event = TRACKMOUSEEVENT {
+                        cbSize: std::mem::size_of::<TRACKMOUSEEVENT>() as DWORD,
+                        dwFlags: TME_LEAVE,
+                        hwndTrack: hwnd,
+                        dwHoverTime: 0,
+                    };
+                    TrackMouseEvent(&mut track_mouse_event);
+                    produce_event(Event::PointerEntered {
+                        window_id: WindowId::new(hwnd as *mut std::ffi::c_void),
+                        x: GET_X_LPARAM(l_param) as f64,
+                        y: GET_Y_LPARAM(l_param) as f64,
+                    });
+                }
+            }
+            produce_event(process_mouse_move_event(hwnd, l_param))
+        }
+        WM_MOUSELEAVE => {
+            if let Some(window_data) = get_window_data(hwnd) {
+                (*window_data).pointer_inside = false;
+            }
+            produce_event(Event::PointerLeft {
+                window_id: WindowId::new(hwnd as *mut std::ffi::c_void),
+            });
+        }
+        WM_INPUT => {
+            let mut raw_input: RAWINPUT = std::mem::zeroed();
+            let mut size = std::mem::size_of::<RAWINPUT>() as UINT;
+            let result = GetRawInputData(
+                l_param as HRAWINPUT,
+                RID_INPUT,
+                &mut raw_input as *mut RAWINPUT as LPVOID,
+                &mut size,
+                std::mem::size_of::<RAWINPUTHEADER>() as UINT,
+            );
+
+            // Devices like pen tablets report absolute positions instead of motion.
+            if result != UINT::MAX
+                && raw_input.header.dwType == RIM_TYPEMOUSE
+                && raw_input.mouse.usFlags & MOUSE_MOVE_ABSOLUTE == 0
+                && (raw_input.mouse.lLastX != 0 || raw_input.mouse.lLastY != 0)
+            {
+                produce_event(Event::RawMouseMotion {
+                    delta_x: raw_input.mouse.lLastX as f64,
+                    delta_y: raw_input.mouse.lLastY as f64,
+                    window_id: WindowId::new(hwnd as *mut std::ffi::c_void),
+                    timestamp: get_message_time(),
+                });
+            }
+        }
+        WM_MOUSEWHEEL | WM_MOUSEHWHEEL => {
+            // High resolution wheels report less than WHEEL_DELTA per message,
+            // which becomes a fraction of a line.
+            // https://docs.microsoft.com/en-us/windows/win32/inputdev/wm-mousewheel
+            let lines = HIWORD(w_param as u32) as c_short as f64 / WHEEL_DELTA as f64;
+            let delta = if u_msg == WM_MOUSEWHEEL {
+                ScrollDelta::Lines { x: 0., y: lines }
+            } else {
+                ScrollDelta::Lines { x: lines, y: 0. }
+            };
+            produce_event(Event::Scroll {
+                delta,
+                phase: ScrollPhase::Unknown,
+                window_id: WindowId::new(hwnd as *mut std::ffi::c_void),
+                modifiers: get_modifiers(),
+                timestamp: get_message_time(),
+            });
+        }
         WM_LBUTTONDBLCLK => {
             // When double click is enabled on a window Windows will consume the second down event
             // so send a synthetic one here to ensure that no clicks are missed.
@@ -260,6 +459,9 @@ pub unsafe extern "system" fn window_callback(
                 x: x as f64,
                 y: y as f64,
                 source: PointerSource::Mouse,
+                pointer_id: 0,
+                window_id: WindowId::new(hwnd as *mut std::ffi::c_void),
+                modifiers: get_modifiers(),
                 button: PointerButton::Primary,
                 timestamp: get_message_time(),
             });
@@ -267,6 +469,7 @@ pub unsafe extern "system" fn window_callback(
                 x: x as f64,
                 y: y as f64,
                 button: PointerButton::Primary,
+                window_id: WindowId::new(hwnd as *mut std::ffi::c_void),
                 timestamp: get_message_time(),
             });
             *DBLCLICK_L.get_mut() = true;
@@ -280,6 +483,9 @@ pub unsafe extern "system" fn window_callback(
                 x: x as f64,
                 y: y as f64,
                 source: PointerSource::Mouse,
+                pointer_id: 0,
+                window_id: WindowId::new(hwnd as *mut std::ffi::c_void),
+                modifiers: get_modifiers(),
                 button: PointerButton::Auxillary,
                 timestamp: get_message_time(),
             });
@@ -287,6 +493,7 @@ pub unsafe extern "system" fn window_callback(
                 x: x as f64,
                 y: y as f64,
                 button: PointerButton::Auxillary,
+                window_id: WindowId::new(hwnd as *mut std::ffi::c_void),
                 timestamp: get_message_time(),
             });
             *DBLCLICK_M.get_mut() = true;
@@ -300,6 +507,9 @@ pub unsafe extern "system" fn window_callback(
                 x: x as f64,
                 y: y as f64,
                 source: PointerSource::Mouse,
+                pointer_id: 0,
+                window_id: WindowId::new(hwnd as *mut std::ffi::c_void),
+                modifiers: get_modifiers(),
                 button: PointerButton::Secondary,
                 timestamp: get_message_time(),
             });
@@ -307,6 +517,7 @@ pub unsafe extern "system" fn window_callback(
                 x: x as f64,
                 y: y as f64,
                 button: PointerButton::Secondary,
+                window_id: WindowId::new(hwnd as *mut std::ffi::c_void),
                 timestamp: get_message_time(),
             });
             *DBLCLICK_R.get_mut() = true;
@@ -332,6 +543,9 @@ pub unsafe extern "system" fn window_callback(
                 x: x as f64,
                 y: y as f64,
                 source: PointerSource::Mouse,
+                pointer_id: 0,
+                window_id: WindowId::new(hwnd as *mut std::ffi::c_void),
+                modifiers: get_modifiers(),
                 button,
                 timestamp: get_message_time(),
             });
@@ -339,6 +553,7 @@ pub unsafe extern "system" fn window_callback(
                 x: x as f64,
                 y: y as f64,
                 button,
+                window_id: WindowId::new(hwnd as *mut std::ffi::c_void),
                 timestamp: get_message_time(),
             });
         }
@@ -366,9 +581,42 @@ pub unsafe extern "system" fn window_callback(
                 (*min_max_info).ptMaxTrackSize.y = (*window_data).maximum_height as i32;
             }
         }
+        WM_NCHITTEST => {
+            // Windows handles the window's own frame, the hit-test handles the client area.
+            let result = DefWindowProcW(hwnd, u_msg, w_param, l_param);
+            if result == HTCLIENT {
+                if let Some(window_data) = get_window_data(hwnd) {
+                    if let Some(hit_test) = &(*window_data).hit_test {
+                        let mut position = POINT {
+                            x: GET_X_LPARAM(l_param),
+                            y: GET_Y_LPARAM(l_param),
+                        };
+                        ScreenToClient(hwnd, &mut position);
+                        return match hit_test(position.x as f64, position.y as f64) {
+                            HitTestResult::Client => HTCLIENT,
+                            HitTestResult::Caption => HTCAPTION,
+                            HitTestResult::ResizeNorth => HTTOP,
+                            HitTestResult::ResizeSouth => HTBOTTOM,
+                            HitTestResult::ResizeEast => HTRIGHT,
+                            HitTestResult::ResizeWest => HTLEFT,
+                            HitTestResult::ResizeNorthEast => HTTOPRIGHT,
+                            HitTestResult::ResizeNorthWest => HTTOPLEFT,
+                            HitTestResult::ResizeSouthEast => HTBOTTOMRIGHT,
+                            HitTestResult::ResizeSouthWest => HTBOTTOMLEFT,
+                        };
+                    }
+                }
+            }
+            return result;
+        }
         WM_NCDESTROY => {
+            super::application_windows::release_modal_parent(hwnd);
             // Deallocate data associated with this window.
-            let _ = Box::from_raw(GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowData);
+            // The pointer is cleared so `get_window_data` can't return it afterwards.
+            if let Some(window_data) = get_window_data(hwnd) {
+                SetWindowLongPtrW(hwnd, GWLP_USERDATA, 0);
+                let _ = Box::from_raw(window_data);
+            }
         }
         _ => {}
     }
@@ -376,7 +624,7 @@ pub unsafe extern "system" fn window_callback(
     DefWindowProcW(hwnd, u_msg, w_param, l_param)
 }
 
-fn get_window_data(hwnd: HWND) -> Option<*mut WindowData> {
+pub(crate) fn get_window_data(hwnd: HWND) -> Option<*mut WindowData> {
     let data = unsafe { GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut WindowData };
     if data == std::ptr::null_mut() {
         None
@@ -384,6 +632,69 @@ fn get_window_data(hwnd: HWND) -> Option<*mut WindowData> {
         Some(data)
     }
 }
+/// Reads composition data from the IME, like the composition string or its attributes.
+/// Applies a window's aspect ratio and resize increments to the window rectangle
+/// the user is dragging, keeping the edges opposite the dragged edge in place.
+unsafe fn constrain_sizing_rect(
+    hwnd: HWND,
+    window_data: &WindowData,
+    edge: WPARAM,
+    rect: &mut RECT,
+) {
+    if window_data.aspect_ratio.is_none() && window_data.resize_increments.is_none() {
+        return;
+    }
+
+    // The constraints apply to the client area so the border size is excluded.
+    let mut window_rect = RECT {
+        left: 0,
+        top: 0,
+        right: 0,
+        bottom: 0,
+    };
+    let mut client_rect = window_rect;
+    GetWindowRect(hwnd, &mut window_rect);
+    GetClientRect(hwnd, &mut client_rect);
+    let border_width = (window_rect.right - window_rect.left) - client_rect.right;
+    let border_height = (window_rect.bottom - window_rect.top) - client_rect.bottom;
+
+    let mut width = rect.right - rect.left - border_width;
+    let mut height = rect.bottom - rect.top - border_height;
+
+    if let Some((x_increment, y_increment)) = window_data.resize_increments {
+        width -= width % (x_increment.max(1) as LONG);
+        height -= height % (y_increment.max(1) as LONG);
+    }
+
+    // Dragging the top or bottom edge resizes the height, otherwise the width leads.
+    if let Some((ratio_width, ratio_height)) = window_data.aspect_ratio {
+        if edge == WMSZ_TOP || edge == WMSZ_BOTTOM {
+            width = height * ratio_width as LONG / ratio_height as LONG;
+        } else {
+            height = width * ratio_height as LONG / ratio_width as LONG;
+        }
+    }
+
+    match edge {
+        WMSZ_LEFT | WMSZ_TOPLEFT | WMSZ_BOTTOMLEFT => rect.left = rect.right - width - border_width,
+        _ => rect.right = rect.left + width + border_width,
+    }
+    match edge {
+        WMSZ_TOP | WMSZ_TOPLEFT | WMSZ_TOPRIGHT => rect.top = rect.bottom - height - border_height,
+        _ => rect.bottom = rect.top + height + border_height,
+    }
+}
+
+unsafe fn get_composition_data<T: Clone + Default>(himc: HIMC, index: UINT) -> Vec<T> {
+    let size_bytes = ImmGetCompositionStringW(himc, index, null_mut(), 0);
+    if size_bytes <= 0 {
+        return Vec::new();
+    }
+    let mut buffer = vec![T::default(); size_bytes as usize / std::mem::size_of::<T>()];
+    ImmGetCompositionStringW(himc, index, buffer.as_mut_ptr().cast(), size_bytes as u32);
+    buffer
+}
+
 /// Gets the message time with millisecond precision
 /// https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getmessagetime
 fn get_message_time() -> std::time::Duration {
@@ -424,12 +735,40 @@ fn resize_event(hwnd: HWND, l_param: LPARAM, w_param: WPARAM) {
     }
 }
 
+// https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getkeystate
+// GetKeyState reflects the keyboard state as of the message currently being processed.
+fn get_modifiers() -> Modifiers {
+    fn is_down(virtual_key: c_int) -> bool {
+        unsafe { GetKeyState(virtual_key) as u16 & 0x8000 != 0 }
+    }
+    fn is_toggled(virtual_key: c_int) -> bool {
+        unsafe { GetKeyState(virtual_key) & 1 != 0 }
+    }
+
+    let mut modifiers = Modifiers::empty();
+    modifiers.set(Modifiers::SHIFT, is_down(VK_SHIFT));
+    modifiers.set(Modifiers::CONTROL, is_down(VK_CONTROL));
+    modifiers.set(Modifiers::ALT, is_down(VK_MENU));
+    modifiers.set(Modifiers::SUPER, is_down(VK_LWIN) || is_down(VK_RWIN));
+    modifiers.set(Modifiers::CAPS_LOCK, is_toggled(VK_CAPITAL));
+    modifiers.set(Modifiers::NUM_LOCK, is_toggled(VK_NUMLOCK));
+    modifiers
+}
+
+/// Sends `ModifiersChanged` if the modifiers differ from the last ones seen.
+fn update_modifiers() {
+    let modifiers = get_modifiers();
+    if LAST_MODIFIERS.with(|m| m.replace(modifiers)) != modifiers {
+        produce_event(Event::ModifiersChanged { modifiers });
+    }
+}
+
 fn produce_event(event: Event) {
     event_receiver::send_event(event);
 }
 
 // https://docs.microsoft.com/en-us/windows/win32/inputdev/wm-mousemove
-fn process_mouse_move_event(_hwnd: HWND, l_param: LPARAM) -> Event {
+fn process_mouse_move_event(hwnd: HWND, l_param: LPARAM) -> Event {
     let x = GET_X_LPARAM(l_param);
     let y = GET_Y_LPARAM(l_param);
 
@@ -437,6 +776,9 @@ fn process_mouse_move_event(_hwnd: HWND, l_param: LPARAM) -> Event {
         x: x as f64,
         y: y as f64,
         source: PointerSource::Mouse,
+        pointer_id: 0,
+        window_id: WindowId::new(hwnd as *mut std::ffi::c_void),
+        modifiers: get_modifiers(),
         timestamp: get_message_time(),
     }
 }
@@ -447,70 +789,184 @@ fn get_width_height(l_param: LPARAM) -> (u32, u32) {
     (width, height)
 }
 
-fn process_key_down(w_param: WPARAM, l_param: LPARAM) -> Event {
-    let (_scancode, key, repeat) = process_key_event(w_param, l_param);
+fn process_key_down(hwnd: HWND, w_param: WPARAM, l_param: LPARAM) -> Event {
+    let (scancode, key, physical_key, repeat) = process_key_event(w_param, l_param);
+    let text = get_key_text(w_param, scancode);
 
     if repeat {
         Event::KeyRepeat {
             key,
+            physical_key,
+            scancode,
+            text,
+            window_id: WindowId::new(hwnd as *mut std::ffi::c_void),
+            modifiers: get_modifiers(),
             timestamp: get_message_time(),
         }
     } else {
         Event::KeyDown {
             key,
+            physical_key,
+            scancode,
+            text,
+            window_id: WindowId::new(hwnd as *mut std::ffi::c_void),
+            modifiers: get_modifiers(),
             timestamp: get_message_time(),
         }
     }
 }
 
-fn process_key_up(w_param: WPARAM, l_param: LPARAM) -> Event {
-    let (_scancode, key, _repeat) = process_key_event(w_param, l_param);
+fn process_key_up(hwnd: HWND, w_param: WPARAM, l_param: LPARAM) -> Event {
+    let (scancode, key, physical_key, _repeat) = process_key_event(w_param, l_param);
     Event::KeyUp {
         key,
+        physical_key,
+        scancode,
+        window_id: WindowId::new(hwnd as *mut std::ffi::c_void),
+        modifiers: get_modifiers(),
         timestamp: get_message_time(),
     }
 }
 
-fn process_key_event(w_param: WPARAM, l_param: LPARAM) -> (UINT, Key, bool) {
-    let scancode = ((l_param >> 16) & 16) as UINT; // bits 16-23 represent the scancode
-    let _extended = (l_param >> 24) & 1 != 0; // bit 24 represents if its an extended key
+// https://docs.microsoft.com/en-us/windows/win32/inputdev/wm-keydown
+fn process_key_event(w_param: WPARAM, l_param: LPARAM) -> (UINT, Key, Key, bool) {
+    let scancode = ((l_param >> 16) & 0xFF) as UINT; // bits 16-23 represent the scancode
+    let extended = (l_param >> 24) & 1 != 0; // bit 24 represents if its an extended key
     let repeat = (l_param >> 30) & 1 == 1;
-    let key = virtual_keycode_to_key(w_param as _);
-    (scancode, key, repeat)
+    let scancode = if extended { scancode | 0xE000 } else { scancode };
+
+    // Virtual key codes depend on the keyboard layout, scancodes do not.
+    let physical_key = scancode_to_key(scancode);
+    let key = match virtual_keycode_to_key(w_param as _) {
+        Key::Unknown => physical_key,
+        key => key,
+    };
+    (scancode, key, physical_key, repeat)
 }
 
-pub fn run(callback: Box<dyn FnMut(kapp_platform_common::Event)>) {
+// https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-tounicode
+fn get_key_text(w_param: WPARAM, scancode: UINT) -> Option<String> {
     unsafe {
-        event_receiver::set_callback(callback);
+        let mut key_state = [0 as BYTE; 256];
+        GetKeyboardState(key_state.as_mut_ptr());
 
-        let mut message: MSG = std::mem::zeroed();
+        // Flag bit 2 keeps ToUnicode from changing the keyboard state, which would break dead keys.
+        let mut buffer = [0u16; 8];
+        let length = ToUnicode(
+            w_param as UINT,
+            scancode & 0xFF,
+            key_state.as_ptr(),
+            buffer.as_mut_ptr(),
+            buffer.len() as c_int,
+            1 << 2,
+        );
 
-        while message.message != WM_QUIT {
-            // Block and wait for messages unless there is a redraw request.
-            // GetMessageW will return 0 if WM_QUIT is encountered
-            while redraw_manager::draw_requests_count() == 0
-                && GetMessageW(&mut message, null_mut(), 0, 0) > 0
-            {
-                TranslateMessage(&message as *const MSG);
-                DispatchMessageW(&message as *const MSG);
-            }
+        // A negative length is a dead key, which produces no text until the next key.
+        if length <= 0 {
+            return None;
+        }
+        let text = String::from_utf16(&buffer[..length as usize]).ok()?;
+        if text.chars().any(char::is_control) {
+            None
+        } else {
+            Some(text)
+        }
+    }
+}
 
-            if message.message == WM_QUIT {
-                break;
-            }
+pub fn run(callback: Box<dyn FnMut(kapp_platform_common::Event)>) -> i32 {
+    unsafe {
+        event_receiver::set_callback(callback);
 
-            // We only reach here if there are redraw requests.
-            // Iterate through all messages without blocking.
-            while PeekMessageW(&mut message, null_mut(), 0, 0, PM_REMOVE) > 0 {
-                TranslateMessage(&message as *const MSG);
-                DispatchMessageW(&message as *const MSG);
+        let exit_code = loop {
+            if let Some(exit_code) = process_messages(None) {
+                break exit_code;
             }
+        };
 
-            redraw_manager::begin_draw_flush();
-            while let Some(window_id) = redraw_manager::get_draw_request() {
-                produce_event(Event::Draw { window_id });
-            }
-            // Need to rerun event loop here if there are any redraw requests.
+        produce_event(Event::Quit);
+
+        // Dropping the callback drops the user's `Window`s, which destroys them.
+        event_receiver::clear_callback();
+
+        exit_code
+    }
+}
+
+/// Runs a single iteration of the event loop.
+/// The callback is kept afterwards because Windows produces events outside of the event loop,
+/// like when a window is resized.
+pub fn pump_events(
+    timeout: Option<Duration>,
+    callback: Box<dyn FnMut(kapp_platform_common::Event)>,
+) {
+    unsafe {
+        event_receiver::set_callback(callback);
+
+        if quit_manager::pump_events(|| process_messages(timeout).is_some()) {
+            produce_event(Event::Quit);
         }
     }
 }
+
+/// May be called from any thread.
+pub fn send_user_event(thread_id: DWORD, event: UserEvent) {
+    USER_EVENTS.lock().unwrap().push(event);
+    unsafe {
+        PostThreadMessageW(thread_id, WM_USER_EVENT, 0, 0);
+    }
+}
+
+pub fn set_control_flow(control_flow: ControlFlow) {
+    CONTROL_FLOW.with(|c| c.set(control_flow));
+}
+
+/// Runs one iteration of the event loop.
+/// Waits up to `timeout` for a message unless there are redraw requests or the control flow
+/// asks for a shorter wait.
+/// Returns the exit code passed to PostQuitMessage if WM_QUIT is received,
+/// in which case draws are skipped.
+unsafe fn process_messages(timeout: Option<Duration>) -> Option<i32> {
+    // Wait for a message unless there is a redraw request.
+    // MWMO_INPUTAVAILABLE also returns for messages that were already in the queue.
+    if redraw_manager::draw_requests_count() == 0 {
+        let wait = CONTROL_FLOW.with(|c| c.get()).wait_duration(timeout);
+        // Rounded up so that a `WaitUntil` isn't woken just before its instant.
+        let milliseconds = wait.map_or(INFINITE, |wait| {
+            ((wait.as_micros() + 999) / 1000).min((INFINITE - 1) as u128) as DWORD
+        });
+        MsgWaitForMultipleObjectsEx(
+            0,
+            std::ptr::null(),
+            milliseconds,
+            QS_ALLINPUT,
+            MWMO_INPUTAVAILABLE,
+        );
+    }
+
+    let mut message: MSG = std::mem::zeroed();
+    while PeekMessageW(&mut message, null_mut(), 0, 0, PM_REMOVE) > 0 {
+        // Quitting happens before any requested draws.
+        if message.message == WM_QUIT {
+            return Some(message.wParam as i32);
+        }
+        TranslateMessage(&message as *const MSG);
+        DispatchMessageW(&message as *const MSG);
+    }
+
+    // Thread messages are discarded by modal loops, like while a window is being resized,
+    // so user events are checked for every iteration instead of only after WM_USER_EVENT.
+    let user_events = std::mem::take(&mut *USER_EVENTS.lock().unwrap());
+    for event in user_events {
+        produce_event(Event::User(event));
+    }
+
+    // When there are no messages remaining, we're at the end of the event loop
+    produce_event(Event::EventsCleared);
+
+    redraw_manager::begin_draw_flush();
+    while let Some(window_id) = redraw_manager::get_draw_request() {
+        produce_event(Event::Draw { window_id });
+    }
+    None
+}
@@ -73,6 +73,7 @@ macro_rules! FN {
 pub type LONG = c_long;
 pub type WCHAR = wchar_t;
 pub type LPCWSTR = *const WCHAR;
+pub type LPWSTR = *mut WCHAR;
 
 // Copied from https://github.com/retep998/winapi-rs/blob/0.3/src/lib.rs
 pub mod ctypes {
@@ -81,6 +82,7 @@ pub mod ctypes {
    // #[cfg(not(feature = "std"))]
    // pub enum c_void {}
     pub type c_short = i16;
+    pub type c_uchar = u8;
     pub type c_ushort = u16;
     pub type c_int = i32;
     pub type c_uint = u32;
@@ -115,6 +117,12 @@ pub type WPARAM = UINT_PTR;
 pub type LPARAM = LONG_PTR;
 pub type LRESULT = LONG_PTR;
 pub type ATOM = WORD;
+pub type BYTE = c_uchar;
+pub type USHORT = c_ushort;
+pub type ULONG = c_ulong;
+pub type HANDLE = *mut std::ffi::c_void;
+pub type HGDIOBJ = *mut std::ffi::c_void;
+pub type COLORREF = DWORD;
 
 #[inline]
 pub fn LOWORD(l: DWORD) -> WORD {
@@ -135,6 +143,8 @@ DECLARE_HANDLE! {HMENU, HMENU__}
 DECLARE_HANDLE! {HBRUSH, HBRUSH__}
 
 DECLARE_HANDLE! {HIMC, HIMC__}
+DECLARE_HANDLE! {HRAWINPUT, HRAWINPUT__}
+DECLARE_HANDLE! {HRGN, HRGN__}
 
 pub type HCURSOR = HICON;
 type LPRECT = *mut RECT;
@@ -184,6 +194,11 @@ extern "system" {
     pub fn GetModuleHandleW(lpModuleName: LPCWSTR) -> HMODULE;
 }
 
+// Copied from https://github.com/retep998/winapi-rs/blob/0.3/src/um/processthreadsapi.rs
+extern "system" {
+    pub fn GetCurrentThreadId() -> DWORD;
+}
+
 // Copied from https://github.com/retep998/winapi-rs/blob/0.3/src/um/winuser.rs
 FN! {stdcall WNDPROC(
     HWND,
@@ -202,7 +217,7 @@ extern "system" {
         bMenu: BOOL,
         dwExStyle: DWORD,
     ) -> BOOL;
-    pub fn CloseWindow(hWnd: HWND) -> BOOL;
+    pub fn DestroyWindow(hWnd: HWND) -> BOOL;
     pub fn MoveWindow(
         hWnd: HWND,
         X: c_int,
@@ -228,9 +243,46 @@ extern "system" {
     pub fn ShowWindow(hWnd: HWND, nCmdShow: c_int) -> BOOL;
     pub fn DefWindowProcW(hWnd: HWND, Msg: UINT, wParam: WPARAM, lParam: LPARAM) -> LRESULT;
     pub fn PostQuitMessage(nExitCode: c_int);
+    pub fn PostThreadMessageW(
+        idThread: DWORD,
+        Msg: UINT,
+        wParam: WPARAM,
+        lParam: LPARAM,
+    ) -> BOOL;
+    pub fn MsgWaitForMultipleObjectsEx(
+        nCount: DWORD,
+        pHandles: *const HANDLE,
+        dwMilliseconds: DWORD,
+        dwWakeMask: DWORD,
+        dwFlags: DWORD,
+    ) -> DWORD;
     pub fn DispatchMessageW(lpmsg: *const MSG) -> LRESULT;
     pub fn GetCursorPos(lpPoint: LPPOINT) -> BOOL;
     pub fn GetMessageTime() -> LONG;
+    pub fn GetKeyState(nVirtKey: c_int) -> c_short;
+    pub fn TrackMouseEvent(lpEventTrack: *mut TRACKMOUSEEVENT) -> BOOL;
+    pub fn RegisterRawInputDevices(
+        pRawInputDevices: *const RAWINPUTDEVICE,
+        uiNumDevices: UINT,
+        cbSize: UINT,
+    ) -> BOOL;
+    pub fn GetRawInputData(
+        hRawInput: HRAWINPUT,
+        uiCommand: UINT,
+        pData: LPVOID,
+        pcbSize: *mut UINT,
+        cbSizeHeader: UINT,
+    ) -> UINT;
+    pub fn GetKeyboardState(lpKeyState: *mut BYTE) -> BOOL;
+    pub fn GetKeyNameTextW(lParam: LONG, lpString: LPWSTR, cchSize: c_int) -> c_int;
+    pub fn ToUnicode(
+        wVirtKey: UINT,
+        wScanCode: UINT,
+        lpKeyState: *const BYTE,
+        pwszBuff: LPWSTR,
+        cchBuff: c_int,
+        wFlags: UINT,
+    ) -> c_int;
     pub fn GetSystemMetrics(nIndex: c_int) -> c_int;
     pub fn GetWindowRect(hWnd: HWND, lpRect: LPRECT) -> BOOL;
     pub fn GetClientRect(hWnd: HWND, lpRect: LPRECT) -> BOOL;
@@ -246,6 +298,34 @@ extern "system" {
     pub fn RegisterClassW(lpWndClass: *const WNDCLASSW) -> ATOM;
     pub fn SetCursor(hCursor: HCURSOR) -> HCURSOR;
     pub fn SetCursorPos(X: c_int, Y: c_int) -> BOOL;
+    pub fn GetFocus() -> HWND;
+    pub fn GetForegroundWindow() -> HWND;
+    pub fn SetForegroundWindow(hWnd: HWND) -> BOOL;
+    pub fn FlashWindowEx(pfwi: *const FLASHWINFO) -> BOOL;
+    pub fn IsWindowVisible(hWnd: HWND) -> BOOL;
+    pub fn IsIconic(hWnd: HWND) -> BOOL;
+    pub fn IsZoomed(hWnd: HWND) -> BOOL;
+    pub fn SetLayeredWindowAttributes(
+        hwnd: HWND,
+        crKey: COLORREF,
+        bAlpha: BYTE,
+        dwFlags: DWORD,
+    ) -> BOOL;
+    pub fn SetWindowPos(
+        hWnd: HWND,
+        hWndInsertAfter: HWND,
+        X: c_int,
+        Y: c_int,
+        cx: c_int,
+        cy: c_int,
+        uFlags: UINT,
+    ) -> BOOL;
+    pub fn ClipCursor(lpRect: *const RECT) -> BOOL;
+    pub fn ClientToScreen(hWnd: HWND, lpPoint: LPPOINT) -> BOOL;
+    pub fn ScreenToClient(hWnd: HWND, lpPoint: LPPOINT) -> BOOL;
+    pub fn PostMessageW(hWnd: HWND, Msg: UINT, wParam: WPARAM, lParam: LPARAM) -> BOOL;
+    pub fn ReleaseCapture() -> BOOL;
+    pub fn EnableWindow(hWnd: HWND, bEnable: BOOL) -> BOOL;
     pub fn ShowCursor(bShow: BOOL) -> c_int;
     pub fn SetWindowLongW(
         hWnd: HWND,
@@ -266,6 +346,7 @@ extern "system" {
     ) -> LONG_PTR;
   
     pub fn SetWindowTextW(hWnd: HWND, lpString: LPCWSTR) -> BOOL;
+    pub fn MessageBoxW(hWnd: HWND, lpText: LPCWSTR, lpCaption: LPCWSTR, uType: UINT) -> c_int;
     pub fn TranslateMessage(lpmsg: *const MSG) -> BOOL;
     pub fn GetDpiForWindow(hwnd: HWND) -> UINT;
 }
@@ -280,6 +361,9 @@ extern "system" {
         lpBug: LPVOID,
         dwBufLen: DWORD,
     ) -> LONG;
+    pub fn ImmAssociateContextEx(hWnd: HWND, hIMC: HIMC, dwFlags: DWORD) -> BOOL;
+    pub fn ImmSetCompositionWindow(hIMC: HIMC, lpCompForm: *const COMPOSITIONFORM) -> BOOL;
+    pub fn ImmSetCandidateWindow(hIMC: HIMC, lpCandidate: *const CANDIDATEFORM) -> BOOL;
 }
 
 #[cfg(target_pointer_width = "32")]
@@ -288,6 +372,55 @@ pub use self::SetWindowLongW as SetWindowLongPtrW;
 #[cfg(target_pointer_width = "32")]
 pub use self::GetWindowLongW as GetWindowLongPtrW;
 
+#[link(name = "gdi32")]
+extern "system" {
+    pub fn CreateRectRgn(x1: c_int, y1: c_int, x2: c_int, y2: c_int) -> HRGN;
+    pub fn DeleteObject(ho: HGDIOBJ) -> BOOL;
+}
+
+#[link(name = "dwmapi")]
+extern "system" {
+    pub fn DwmEnableBlurBehindWindow(hWnd: HWND, pBlurBehind: *const DWM_BLURBEHIND) -> HRESULT;
+}
+
+#[link(name = "shell32")]
+extern "system" {
+    pub fn SetCurrentProcessExplicitAppUserModelID(AppID: LPCWSTR) -> HRESULT;
+}
+
+// Copied from https://github.com/retep998/winapi-rs/blob/0.3/src/um/commdlg.rs
+STRUCT! {struct OPENFILENAMEW {
+    lStructSize: DWORD,
+    hwndOwner: HWND,
+    hInstance: HINSTANCE,
+    lpstrFilter: LPCWSTR,
+    lpstrCustomFilter: LPWSTR,
+    nMaxCustFilter: DWORD,
+    nFilterIndex: DWORD,
+    lpstrFile: LPWSTR,
+    nMaxFile: DWORD,
+    lpstrFileTitle: LPWSTR,
+    nMaxFileTitle: DWORD,
+    lpstrInitialDir: LPCWSTR,
+    lpstrTitle: LPCWSTR,
+    Flags: DWORD,
+    nFileOffset: WORD,
+    nFileExtension: WORD,
+    lpstrDefExt: LPCWSTR,
+    lCustData: LPARAM,
+    lpfnHook: LPVOID,
+    lpTemplateName: LPCWSTR,
+    pvReserved: LPVOID,
+    dwReserved: DWORD,
+    FlagsEx: DWORD,
+}}
+
+#[link(name = "comdlg32")]
+extern "system" {
+    pub fn GetOpenFileNameW(lpofn: *mut OPENFILENAMEW) -> BOOL;
+    pub fn GetSaveFileNameW(lpofn: *mut OPENFILENAMEW) -> BOOL;
+}
+
 #[link(name = "Shcore")]
 extern "system" {
     pub fn SetProcessDpiAwareness(value: PROCESS_DPI_AWARENESS) -> HRESULT;
@@ -320,6 +453,71 @@ STRUCT! {struct WNDCLASSW {
     lpszClassName: LPCWSTR,
 }}
 
+STRUCT! {struct TRACKMOUSEEVENT {
+    cbSize: DWORD,
+    dwFlags: DWORD,
+    hwndTrack: HWND,
+    dwHoverTime: DWORD,
+}}
+
+STRUCT! {struct FLASHWINFO {
+    cbSize: UINT,
+    hwnd: HWND,
+    dwFlags: DWORD,
+    uCount: UINT,
+    dwTimeout: DWORD,
+}}
+
+STRUCT! {struct DWM_BLURBEHIND {
+    dwFlags: DWORD,
+    fEnable: BOOL,
+    hRgnBlur: HRGN,
+    fTransitionOnMaximized: BOOL,
+}}
+
+STRUCT! {struct COMPOSITIONFORM {
+    dwStyle: DWORD,
+    ptCurrentPos: POINT,
+    rcArea: RECT,
+}}
+
+STRUCT! {struct CANDIDATEFORM {
+    dwIndex: DWORD,
+    dwStyle: DWORD,
+    ptCurrentPos: POINT,
+    rcArea: RECT,
+}}
+
+STRUCT! {struct RAWINPUTDEVICE {
+    usUsagePage: USHORT,
+    usUsage: USHORT,
+    dwFlags: DWORD,
+    hwndTarget: HWND,
+}}
+
+STRUCT! {struct RAWINPUTHEADER {
+    dwType: DWORD,
+    dwSize: DWORD,
+    hDevice: HANDLE,
+    wParam: WPARAM,
+}}
+
+// ulButtons is a union with the usButtonFlags and usButtonData fields.
+STRUCT! {struct RAWMOUSE {
+    usFlags: USHORT,
+    ulButtons: ULONG,
+    ulRawButtons: ULONG,
+    lLastX: LONG,
+    lLastY: LONG,
+    ulExtraInformation: ULONG,
+}}
+
+// The data is a union of RAWMOUSE, RAWKEYBOARD, and RAWHID, but only mice are registered.
+STRUCT! {struct RAWINPUT {
+    header: RAWINPUTHEADER,
+    mouse: RAWMOUSE,
+}}
+
 STRUCT! {struct MINMAXINFO {
     ptReserved: POINT,
     ptMaxSize: POINT,
@@ -344,12 +542,17 @@ pub const WS_OVERLAPPEDWINDOW: DWORD =
     WS_OVERLAPPED | WS_CAPTION | WS_SYSMENU | WS_THICKFRAME | WS_MINIMIZEBOX | WS_MAXIMIZEBOX;
 
 pub const WS_EX_APPWINDOW: DWORD = 0x00040000;
+pub const WS_EX_TOPMOST: DWORD = 0x00000008;
+pub const WS_EX_LAYERED: DWORD = 0x00080000;
+pub const WS_EX_TOOLWINDOW: DWORD = 0x00000080;
+pub const WS_EX_NOACTIVATE: DWORD = 0x08000000;
 pub const CS_OWNDC: UINT = 0x0020;
 pub const CS_DBLCLKS: UINT = 0x0008;
 
 #[allow(overflowing_literals)]
 pub const CW_USEDEFAULT: c_int = 0x80000000;
 pub const GWL_STYLE: c_int = -16;
+pub const GWL_EXSTYLE: c_int = -20;
 pub const GWLP_USERDATA: c_int = -21;
 
 pub const IDC_ARROW: LPCWSTR = 32512 as LPCWSTR;
@@ -357,6 +560,9 @@ pub const IDC_IBEAM: LPCWSTR = 32513 as LPCWSTR;
 pub const IDC_HAND: LPCWSTR = 32649 as LPCWSTR;
 
 pub const PM_REMOVE: UINT = 0x0001;
+pub const INFINITE: DWORD = 0xFFFFFFFF;
+pub const QS_ALLINPUT: DWORD = 0x04FF;
+pub const MWMO_INPUTAVAILABLE: DWORD = 0x0004;
 
 pub const SM_CXSCREEN: c_int = 0;
 pub const SM_CYSCREEN: c_int = 1;
@@ -369,9 +575,31 @@ pub const SIZE_RESTORED: WPARAM = 0;
 pub const SIZE_MINIMIZED: WPARAM = 1;
 pub const SIZE_MAXIMIZED: WPARAM = 2;
 
+pub const SW_HIDE: c_int = 0;
 pub const SW_MAXIMIZE: c_int = 3;
+pub const SW_SHOWNOACTIVATE: c_int = 4;
+pub const SW_SHOW: c_int = 5;
 pub const SW_MINIMIZE: c_int = 6;
 pub const SW_RESTORE: c_int = 9;
+
+pub const LWA_ALPHA: DWORD = 0x00000002;
+
+pub const DWM_BB_ENABLE: DWORD = 0x00000001;
+pub const DWM_BB_BLURREGION: DWORD = 0x00000002;
+
+pub const SWP_NOSIZE: UINT = 0x0001;
+pub const SWP_NOMOVE: UINT = 0x0002;
+pub const SWP_NOZORDER: UINT = 0x0004;
+pub const SWP_NOACTIVATE: UINT = 0x0010;
+pub const SWP_FRAMECHANGED: UINT = 0x0020;
+
+pub const WMSZ_LEFT: WPARAM = 1;
+pub const WMSZ_TOP: WPARAM = 3;
+pub const WMSZ_TOPLEFT: WPARAM = 4;
+pub const WMSZ_TOPRIGHT: WPARAM = 5;
+pub const WMSZ_BOTTOM: WPARAM = 6;
+pub const WMSZ_BOTTOMLEFT: WPARAM = 7;
+
 pub const VK_CANCEL: c_int = 0x03;
 pub const VK_BACK: c_int = 0x08;
 pub const VK_TAB: c_int = 0x09;
@@ -507,7 +735,11 @@ pub const WM_SIZE: UINT = 0x0005;
 pub const WM_PAINT: UINT = 0x000F;
 pub const WM_CLOSE: UINT = 0x0010;
 pub const WM_QUIT: UINT = 0x0012;
+pub const WM_APP: UINT = 0x8000;
 pub const WM_SETCURSOR: UINT = 0x0020;
+pub const WM_SETFOCUS: UINT = 0x0007;
+pub const WM_KILLFOCUS: UINT = 0x0008;
+pub const WM_INPUTLANGCHANGE: UINT = 0x0051;
 
 pub const WM_ENTERSIZEMOVE: UINT = 0x0231;
 pub const WM_EXITSIZEMOVE: UINT = 0x0232;
@@ -525,6 +757,10 @@ pub const WM_LBUTTONUP: UINT = 0x0202;
 pub const WM_MBUTTONDOWN: UINT = 0x0207;
 pub const WM_MBUTTONUP: UINT = 0x0208;
 pub const WM_MOUSEMOVE: UINT = 0x0200;
+pub const WM_MOUSELEAVE: UINT = 0x02A3;
+pub const WM_MOUSEWHEEL: UINT = 0x020A;
+pub const WM_MOUSEHWHEEL: UINT = 0x020E;
+pub const WHEEL_DELTA: c_short = 120;
 pub const WM_RBUTTONDOWN: UINT = 0x0204;
 pub const WM_RBUTTONUP: UINT = 0x0205;
 pub const WM_SIZING: UINT = 0x0214;
@@ -536,11 +772,69 @@ pub const WM_RBUTTONDBLCLK: UINT = 0x0206;
 pub const WM_XBUTTONDBLCLK: UINT = 0x020D;
 pub const WM_DPICHANGED: UINT = 0x02E0;
 pub const WM_NCDESTROY: UINT = 0x0082;
+pub const WM_NCHITTEST: UINT = 0x0084;
+pub const WM_NCLBUTTONDOWN: UINT = 0x00A1;
 pub const WM_GETMINMAXINFO: UINT = 0x0024;
 //pub const WM_NCCREATE: UINT = 0x0081;
 pub const WM_CREATE: UINT = 0x0001;
+pub const WM_INPUT: UINT = 0x00FF;
+
+pub const HTCLIENT: LRESULT = 1;
+pub const HTCAPTION: LRESULT = 2;
+pub const HTLEFT: LRESULT = 10;
+pub const HTRIGHT: LRESULT = 11;
+pub const HTTOP: LRESULT = 12;
+pub const HTTOPLEFT: LRESULT = 13;
+pub const HTTOPRIGHT: LRESULT = 14;
+pub const HTBOTTOM: LRESULT = 15;
+pub const HTBOTTOMLEFT: LRESULT = 16;
+pub const HTBOTTOMRIGHT: LRESULT = 17;
+
+pub const HID_USAGE_PAGE_GENERIC: USHORT = 0x01;
+pub const HID_USAGE_GENERIC_MOUSE: USHORT = 0x02;
+pub const RIDEV_REMOVE: DWORD = 0x00000001;
+pub const RID_INPUT: UINT = 0x10000003;
+pub const RIM_TYPEMOUSE: DWORD = 0;
+pub const MOUSE_MOVE_ABSOLUTE: USHORT = 0x01;
+
+pub const TME_LEAVE: DWORD = 0x00000002;
+
+pub const FLASHW_ALL: DWORD = 0x00000003;
+pub const FLASHW_TIMERNOFG: DWORD = 0x0000000C;
 
 pub const XBUTTON1: WORD = 0x0001;
 pub const XBUTTON2: WORD = 0x0002;
 
-pub const GCS_COMPSTR: UINT = 8;
+pub const GCS_COMPSTR: UINT = 0x0008;
+pub const GCS_COMPATTR: UINT = 0x0010;
+pub const GCS_CURSORPOS: UINT = 0x0080;
+
+pub const IACE_DEFAULT: DWORD = 0x0010;
+pub const CFS_POINT: DWORD = 0x0002;
+pub const CFS_EXCLUDE: DWORD = 0x0080;
+
+pub const ATTR_TARGET_CONVERTED: BYTE = 0x01;
+pub const ATTR_CONVERTED: BYTE = 0x02;
+pub const ATTR_TARGET_NOTCONVERTED: BYTE = 0x03;
+pub const ATTR_FIXEDCONVERTED: BYTE = 0x05;
+
+pub const MB_OK: UINT = 0x00000000;
+pub const MB_OKCANCEL: UINT = 0x00000001;
+pub const MB_YESNOCANCEL: UINT = 0x00000003;
+pub const MB_YESNO: UINT = 0x00000004;
+pub const MB_ICONERROR: UINT = 0x00000010;
+pub const MB_ICONWARNING: UINT = 0x00000030;
+pub const MB_ICONINFORMATION: UINT = 0x00000040;
+pub const MB_TASKMODAL: UINT = 0x00002000;
+
+pub const OFN_OVERWRITEPROMPT: DWORD = 0x00000002;
+pub const OFN_NOCHANGEDIR: DWORD = 0x00000008;
+pub const OFN_ALLOWMULTISELECT: DWORD = 0x00000200;
+pub const OFN_PATHMUSTEXIST: DWORD = 0x00000800;
+pub const OFN_FILEMUSTEXIST: DWORD = 0x00001000;
+pub const OFN_EXPLORER: DWORD = 0x00080000;
+
+pub const IDOK: c_int = 1;
+pub const IDCANCEL: c_int = 2;
+pub const IDYES: c_int = 6;
+pub const IDNO: c_int = 7;
@@ -173,3 +173,128 @@ pub fn virtual_keycode_to_key(key_in: std::os::raw::c_int) -> Key {
         _ => Unknown,
     }
 }
+
+/// Converts a scancode to the key in that position on a US keyboard.
+/// Extended scancodes are prefixed with 0xE0.
+pub fn scancode_to_key(scancode: u32) -> Key {
+    match scancode {
+        0x01 => Escape,
+        0x02 => Digit1,
+        0x03 => Digit2,
+        0x04 => Digit3,
+        0x05 => Digit4,
+        0x06 => Digit5,
+        0x07 => Digit6,
+        0x08 => Digit7,
+        0x09 => Digit8,
+        0x0A => Digit9,
+        0x0B => Digit0,
+        0x0C => Minus,
+        0x0D => Equal,
+        0x0E => Backspace,
+        0x0F => Tab,
+        0x10 => Q,
+        0x11 => W,
+        0x12 => E,
+        0x13 => R,
+        0x14 => T,
+        0x15 => Y,
+        0x16 => U,
+        0x17 => I,
+        0x18 => O,
+        0x19 => P,
+        0x1A => BracketLeft,
+        0x1B => BracketRight,
+        0x1C => Return,
+        0x1D => LeftControl,
+        0x1E => A,
+        0x1F => S,
+        0x20 => D,
+        0x21 => F,
+        0x22 => G,
+        0x23 => H,
+        0x24 => J,
+        0x25 => K,
+        0x26 => L,
+        0x27 => Semicolon,
+        0x28 => Quote,
+        0x29 => Backquote,
+        0x2A => LeftShift,
+        0x2B => Backslash,
+        0x2C => Z,
+        0x2D => X,
+        0x2E => C,
+        0x2F => V,
+        0x30 => B,
+        0x31 => N,
+        0x32 => M,
+        0x33 => Comma,
+        0x34 => Period,
+        0x35 => Slash,
+        0x36 => RightShift,
+        0x37 => NumPadMultiply,
+        0x38 => LeftAlt,
+        0x39 => Space,
+        0x3A => CapsLock,
+        0x3B => F1,
+        0x3C => F2,
+        0x3D => F3,
+        0x3E => F4,
+        0x3F => F5,
+        0x40 => F6,
+        0x41 => F7,
+        0x42 => F8,
+        0x43 => F9,
+        0x44 => F10,
+        0x45 => Pause,
+        0x46 => ScrollLock,
+        0x47 => NumPad7,
+        0x48 => NumPad8,
+        0x49 => NumPad9,
+        0x4A => NumPadSubtract,
+        0x4B => NumPad4,
+        0x4C => NumPad5,
+        0x4D => NumPad6,
+        0x4E => NumPadAdd,
+        0x4F => NumPad1,
+        0x50 => NumPad2,
+        0x51 => NumPad3,
+        0x52 => NumPad0,
+        0x53 => NumPadDecimal,
+        0x56 => Oem102,
+        0x57 => F11,
+        0x58 => F12,
+        0x64 => F13,
+        0x65 => F14,
+        0x66 => F15,
+        0x67 => F16,
+        0x68 => F17,
+        0x69 => F18,
+        0x6A => F19,
+        0x6B => F20,
+        0x6C => F21,
+        0x6D => F22,
+        0x6E => F23,
+        0x76 => F24,
+        0xE01C => NumPadEnter,
+        0xE01D => RightControl,
+        0xE035 => NumPadDivide,
+        0xE037 => PrintScreen,
+        0xE038 => RightAlt,
+        0xE045 => NumLock,
+        0xE047 => Home,
+        0xE048 => Up,
+        0xE049 => PageUp,
+        0xE04B => Left,
+        0xE04D => Right,
+        0xE04F => End,
+        0xE050 => Down,
+        0xE051 => PageDown,
+        0xE052 => Insert,
+        0xE053 => Delete,
+        0xE05B => LeftMeta,
+        0xE05C => RightMeta,
+        0xE05D => ContextMenu,
+        _ => Unknown,
+    }
+}
@@ -9,12 +9,34 @@ use std::rc::Rc;
 #[derive(Clone)]
 pub struct Application {
     pub(crate) platform_application: Rc<RefCell<PlatformApplication>>,
-    state_tracker: Rc<RefCell<StateTracker>>,
+    pub(crate) state_tracker: Rc<RefCell<StateTracker>>,
 }
 
 /// Create an Application and EventLoop.
 pub fn initialize() -> (Application, EventLoop) {
-    let platform_application = Rc::new(RefCell::new(PlatformApplication::new()));
+    initialize_with(AppInfo::default())
+}
+
+/// Create an Application and EventLoop with an identity the OS uses
+/// to group the application's windows and find its icon.
+/// On Linux `app_info.id` sets the X11 `WM_CLASS` and Wayland app id.
+/// On Windows it sets the application user model id used to group taskbar buttons.
+/// `app_info.name` is only used on MacOS, where it sets the process name.
+///
+/// On X11 the launcher's startup notification is completed when the first window is shown.
+/// Wayland's XDG activation isn't supported, so there the launcher may keep showing
+/// that the application is starting until it times out.
+/// ```no_run
+/// use kapp::*;
+///
+/// let (app, event_loop) = initialize_with(AppInfo {
+///     id: "com.example.Viewer".to_string(),
+///     name: "Viewer".to_string(),
+///     ..Default::default()
+/// });
+/// ```
+pub fn initialize_with(app_info: AppInfo) -> (Application, EventLoop) {
+    let platform_application = Rc::new(RefCell::new(PlatformApplication::new(&app_info)));
     let platform_event_loop = platform_application.borrow_mut().event_loop();
     let state_tracker = Rc::new(RefCell::new(StateTracker::new()));
     (
@@ -25,6 +47,8 @@ pub fn initialize() -> (Application, EventLoop) {
         EventLoop {
             platform_event_loop,
             state_tracker: state_tracker.clone(),
+            #[cfg(any(feature = "SDL", not(any(target_os = "macos", target_arch = "wasm32"))))]
+            pumped_events: Rc::new(RefCell::new(Vec::new())),
         },
     )
 }
@@ -37,9 +61,53 @@ impl Application {
         crate::window_builder::WindowBuilder::new(self)
     }
 
-    /// Immediately quits the application.
+    /// Quits the application at the end of the current iteration of the event loop.
+    /// `Event::Quit` is sent, then `EventLoop::run` returns 0.
     pub fn quit(&self) {
-        self.platform_application.borrow().quit();
+        self.quit_with_exit_code(0);
+    }
+
+    /// Like `quit`, but `EventLoop::run` returns `exit_code`.
+    pub fn quit_with_exit_code(&self, exit_code: i32) {
+        self.platform_application.borrow().quit(exit_code);
+    }
+
+    /// Returns a proxy that can send `Event::User` to the event loop from other threads.
+    /// See [`crate::EventLoopProxy`].
+    pub fn event_loop_proxy<T: std::any::Any + Send>(&self) -> crate::EventLoopProxy<T> {
+        crate::EventLoopProxy::new(self.platform_application.borrow_mut().event_loop_proxy())
+    }
+
+    /// Sets how the event loop waits for events once it has processed the pending ones.
+    /// The default is `ControlFlow::Wait`.
+    ///
+    /// `ControlFlow::WaitUntil` can be used to do periodic work in `Event::EventsCleared`
+    /// without redrawing or busy-looping:
+    /// ```no_run
+    /// use kapp::*;
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let (app, event_loop) = initialize();
+    /// let tick_rate = Duration::from_millis(50);
+    /// let mut next_tick = Instant::now() + tick_rate;
+    /// app.set_control_flow(ControlFlow::WaitUntil(next_tick));
+    ///
+    /// event_loop.run(move |event| {
+    ///     if let Event::EventsCleared = event {
+    ///         if Instant::now() >= next_tick {
+    ///             // Step the simulation here.
+    ///             next_tick += tick_rate;
+    ///             app.set_control_flow(ControlFlow::WaitUntil(next_tick));
+    ///         }
+    ///     }
+    /// });
+    /// ```
+    /// On Web `Poll` sends `EventsCleared` every animation frame
+    /// and `WaitUntil` wakes the event loop with `setTimeout`.
+    pub fn set_control_flow(&self, control_flow: ControlFlow) {
+        self.platform_application
+            .borrow_mut()
+            .set_control_flow(control_flow);
     }
 
     /// Prevents the mouse from moving until a call to `unlock_mouse_position`
@@ -55,6 +123,51 @@ impl Application {
             .unlock_mouse_position();
     }
 
+    /// Enables `RawMouseMotion` events, which report unaccelerated mouse movement.
+    /// With the SDL backend, used on Linux, events are only sent while the pointer is locked
+    /// with `Window::lock_pointer`.
+    /// Not supported on MacOS or web.
+    pub fn set_raw_mouse_motion(&self, enabled: bool) {
+        self.platform_application
+            .borrow_mut()
+            .set_raw_mouse_motion(enabled);
+    }
+
+    /// Shows a message box and waits for it to be closed, returning the button that was pressed.
+    /// Returns `None` if the message box couldn't be shown.
+    /// This can be used before the event loop is run, like to report that a GL context
+    /// couldn't be created, or from within the event loop.
+    /// On Web `alert` and `confirm` are used, so `YesNoCancel` never returns `Cancel`.
+    pub fn message_box(&self, message_box: MessageBox) -> Option<MessageBoxButton> {
+        self.platform_application
+            .borrow_mut()
+            .message_box(&message_box)
+    }
+
+    /// Shows a dialog for picking files or folders to open.
+    /// The picked paths are sent in an `Event::FileDialogResult` with the returned id.
+    /// On Linux this uses the XDG desktop portal, which must be available on the session bus.
+    /// On MacOS and Windows the dialog is modal, but the result is still sent as an event.
+    /// On Windows `directory` is not supported.
+    /// On Web file dialogs are not supported and an empty result is sent.
+    pub fn open_file_dialog(&self, options: FileDialogOptions) -> FileDialogId {
+        let id = FileDialogId::generate();
+        self.platform_application
+            .borrow_mut()
+            .open_file_dialog(id, &options);
+        id
+    }
+
+    /// Shows a dialog for picking where to save a file.
+    /// Behaves like `open_file_dialog`, but `multiple` and `directory` are ignored.
+    pub fn save_file_dialog(&self, options: FileDialogOptions) -> FileDialogId {
+        let id = FileDialogId::generate();
+        self.platform_application
+            .borrow_mut()
+            .save_file_dialog(id, &options);
+        id
+    }
+
     pub fn set_cursor(&self, cursor: Cursor) {
         self.platform_application.borrow_mut().set_cursor(cursor);
     }
@@ -67,26 +180,35 @@ impl Application {
         }
     }
 
-    /// Enable text input.
-    /// `kapp` will send `CharacterReceived` events until `stop_text_input` is called.
+    /// Enable text input for every window.
+    /// `kapp` will send `CharacterReceived` events until `end_text_input` is called.
     /// Operating system UI related to text input may appear.
-    /// ONLY SUPPORTED ON MAC (for now)
+    /// Use `Window::start_text_input` to enable text input for a single window.
     pub fn start_text_input(&self) {
-        self.platform_application.borrow_mut().start_text_input()
+        self.platform_application
+            .borrow_mut()
+            .start_text_input(None)
     }
 
-    /// Disable text input.
+    /// Disable text input for every window.
     /// Used to end text input after a call to `start_text_input`.
     pub fn end_text_input(&self) {
-        self.platform_application.borrow_mut().end_text_input()
+        self.platform_application.borrow_mut().end_text_input(None)
+    }
+
+    /// Returns the label of the key in the given physical position with the current keyboard layout.
+    /// For example on a French AZERTY keyboard `Key::W` is labeled "Z".
+    /// Returns `None` if the label is unknown.
+    pub fn key_label(&self, physical_key: Key) -> Option<String> {
+        self.platform_application.borrow().key_label(physical_key)
     }
 
-    /// Returns if the key is currently pressed
+    /// Returns if the physical key is currently pressed
     pub fn key(&self, key: Key) -> bool {
         self.state_tracker.borrow().key(key)
     }
 
-    /// Returns true if the key has been pressed since the last draw
+    /// Returns true if the physical key has been pressed since the last draw
     pub fn key_down(&self, key: Key) -> bool {
         self.state_tracker.borrow().key_down(key)
     }
@@ -114,17 +236,71 @@ impl Application {
     pub fn pointer_position(&self) -> (f64, f64) {
         self.state_tracker.borrow().pointer_position()
     }
+
+    /// Returns if the physical key is currently pressed and was pressed in the window.
+    pub fn window_key(&self, window_id: WindowId, key: Key) -> bool {
+        self.state_tracker.borrow().window_key(window_id, key)
+    }
+
+    /// Returns true if the physical key has been pressed in the window since the last draw.
+    pub fn window_key_down(&self, window_id: WindowId, key: Key) -> bool {
+        self.state_tracker.borrow().window_key_down(window_id, key)
+    }
+
+    /// Returns true if the pointer button is pressed and was pressed in the window.
+    pub fn window_pointer_button(&self, window_id: WindowId, button: PointerButton) -> bool {
+        self.state_tracker
+            .borrow()
+            .window_pointer_button(window_id, button)
+    }
+
+    /// Returns true if the pointer button has been pressed in the window since the last draw.
+    pub fn window_pointer_button_down(&self, window_id: WindowId, button: PointerButton) -> bool {
+        self.state_tracker
+            .borrow()
+            .window_pointer_button_down(window_id, button)
+    }
+
+    /// Returns the last pointer position within the window, or `None` if the pointer is not over it.
+    pub fn window_pointer_position(&self, window_id: WindowId) -> Option<(f64, f64)> {
+        self.state_tracker
+            .borrow()
+            .window_pointer_position(window_id)
+    }
+
+    /// Returns the unaccelerated mouse motion since the last draw.
+    /// `set_raw_mouse_motion` must be enabled for this to be nonzero.
+    pub fn raw_mouse_motion(&self) -> (f64, f64) {
+        self.state_tracker.borrow().raw_mouse_motion()
+    }
+
+    /// Returns the window the pointer is over, if any.
+    pub fn hovered_window(&self) -> Option<WindowId> {
+        self.state_tracker.borrow().hovered_window()
+    }
+
+    /// Returns the keyboard modifiers that are currently held.
+    pub fn modifiers(&self) -> Modifiers {
+        self.state_tracker.borrow().modifiers()
+    }
 }
 
 /// Call the 'run' or 'run_async' function on an EventLoop instance to start your program.
 pub struct EventLoop {
     platform_event_loop: PlatformEventLoop,
     state_tracker: Rc<RefCell<StateTracker>>,
+    // Events received by `pump_events`, including those produced between calls.
+    #[cfg(any(feature = "SDL", not(any(target_os = "macos", target_arch = "wasm32"))))]
+    pumped_events: Rc<RefCell<Vec<Event>>>,
 }
 
 impl EventLoop {
     /// Run the application. The callback is called for each new event.
-    pub fn run<T>(&self, mut callback: T)
+    ///
+    /// After `Application::quit` is called `Event::Quit` is sent, the callback is dropped,
+    /// closing any windows it owns, and the exit code passed to quit is returned.
+    /// On Web this returns immediately and events continue to be sent.
+    pub fn run<T>(&self, mut callback: T) -> i32
     where
         T: 'static + FnMut(Event),
     {
@@ -140,6 +316,76 @@ impl EventLoop {
                 _ => {}
             };
         };
-        self.platform_event_loop.run(Box::new(callback_wrapper));
+        self.platform_event_loop.run(Box::new(callback_wrapper))
+    }
+
+    /// Processes pending events and redraw requests and returns the events, for programs
+    /// that run their own loop instead of calling `run`.
+    /// If there are no redraw requests this first waits up to `timeout` for an event,
+    /// or until there is one if `timeout` is `None`. `Some(Duration::ZERO)` never waits.
+    ///
+    /// If `Application::quit` has been called `Event::Quit` is returned instead of draw events
+    /// and the program should stop pumping events. Later calls return no events.
+    ///
+    /// This doesn't exist on MacOS or Web, unless the `SDL` feature is enabled,
+    /// because their event loops can't return control between iterations.
+    /// ```no_run
+    /// use kapp::*;
+    /// use std::time::Duration;
+    ///
+    /// let (app, event_loop) = initialize();
+    /// let _window = app.new_window().build().unwrap();
+    ///
+    /// 'running: loop {
+    ///     for event in event_loop.pump_events(Some(Duration::ZERO)) {
+    ///         match event {
+    ///             Event::WindowCloseRequested { .. } => app.quit(),
+    ///             Event::Quit => break 'running,
+    ///             _ => {}
+    ///         }
+    ///     }
+    ///     // Update and render the game here.
+    /// }
+    /// ```
+    #[cfg(any(feature = "SDL", not(any(target_os = "macos", target_arch = "wasm32"))))]
+    pub fn pump_events(&self, timeout: Option<std::time::Duration>) -> impl Iterator<Item = Event> {
+        let pumped_events = self.pumped_events.clone();
+        self.platform_event_loop.pump_events(
+            timeout,
+            Box::new(move |event| pumped_events.borrow_mut().push(event)),
+        );
+
+        let events = std::mem::take(&mut *self.pumped_events.borrow_mut());
+        PumpedEvents {
+            events: events.into_iter(),
+            state_tracker: self.state_tracker.clone(),
+            clear_state: false,
+        }
+    }
+}
+
+/// Updates the `StateTracker` as events are taken, like `EventLoop::run` does.
+#[cfg(any(feature = "SDL", not(any(target_os = "macos", target_arch = "wasm32"))))]
+struct PumpedEvents {
+    events: std::vec::IntoIter<Event>,
+    state_tracker: Rc<RefCell<StateTracker>>,
+    clear_state: bool,
+}
+
+#[cfg(any(feature = "SDL", not(any(target_os = "macos", target_arch = "wasm32"))))]
+impl Iterator for PumpedEvents {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        // The state is cleared after a `Draw` has been handled.
+        if self.clear_state {
+            self.state_tracker.borrow_mut().clear();
+            self.clear_state = false;
+        }
+
+        let event = self.events.next()?;
+        self.state_tracker.borrow_mut().handle_event(&event);
+        self.clear_state = matches!(event, Event::Draw { .. });
+        Some(event)
     }
 }
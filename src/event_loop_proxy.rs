@@ -0,0 +1,60 @@
+use crate::platform::*;
+use std::any::Any;
+use std::marker::PhantomData;
+
+/// Sends `Event::User` to the event loop from any thread, waking it if it's waiting.
+/// Created with [`crate::Application::event_loop_proxy`].
+///
+/// `Event` isn't generic, so instead of an `Event::User(T)` the value is received as a
+/// type-erased [`UserEvent`] and moved out with `UserEvent::take::<T>()`.
+/// `T` only needs to be `Send`.
+/// ```no_run
+/// use kapp::*;
+///
+/// struct AssetLoaded(String);
+///
+/// let (app, event_loop) = initialize();
+/// let proxy = app.event_loop_proxy::<AssetLoaded>();
+/// std::thread::spawn(move || {
+///     // Load the asset here.
+///     proxy.send(AssetLoaded("texture.png".to_string()));
+/// });
+///
+/// event_loop.run(move |event| {
+///     if let Event::User(user_event) = event {
+///         if let Some(AssetLoaded(name)) = user_event.take::<AssetLoaded>() {
+///             println!("Loaded {}", name);
+///         }
+///     }
+/// });
+/// ```
+pub struct EventLoopProxy<T> {
+    platform_proxy: PlatformEventLoopProxy,
+    // `fn(T)` keeps the proxy `Send` and `Sync` without requiring the same of `T`.
+    phantom: PhantomData<fn(T)>,
+}
+
+impl<T: Any + Send> EventLoopProxy<T> {
+    pub(crate) fn new(platform_proxy: PlatformEventLoopProxy) -> Self {
+        Self {
+            platform_proxy,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Events are sent in order during the next iteration of the event loop.
+    /// Events sent after the event loop has quit are dropped.
+    pub fn send(&self, value: T) {
+        self.platform_proxy.send(UserEvent::new(value));
+    }
+}
+
+// Derived `Clone` would require `T: Clone`.
+impl<T> Clone for EventLoopProxy<T> {
+    fn clone(&self) -> Self {
+        Self {
+            platform_proxy: self.platform_proxy.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
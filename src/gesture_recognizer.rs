@@ -0,0 +1,908 @@
+use crate::{Event, Gesture, GestureKind, PointerButton, PointerSource, WindowId};
+use std::time::Duration;
+
+/// Thresholds used by the [`GestureRecognizer`] to decide which gesture is occurring.
+/// Distances are in physical coordinates.
+#[derive(Debug, Clone)]
+pub struct GestureThresholds {
+    /// How far a pointer can move and still be considered a tap or long press.
+    pub tap_slop: f64,
+    /// The longest a pointer can be held down and still produce a tap.
+    pub tap_duration: Duration,
+    /// The longest time between two taps for them to be a double tap.
+    pub double_tap_interval: Duration,
+    /// The furthest apart two taps can be for them to be a double tap.
+    pub double_tap_slop: f64,
+    /// How long a pointer must be held in place to produce a long press.
+    pub long_press_duration: Duration,
+    /// How far pointers must move together before a pan begins.
+    pub pan_distance: f64,
+    /// How much the distance between two pointers must change, as a fraction
+    /// of their starting distance, before a pinch begins.
+    pub pinch_scale: f64,
+    /// How far two pointers must rotate around each other, in radians, before a rotate begins.
+    pub rotate_angle: f64,
+}
+
+impl Default for GestureThresholds {
+    fn default() -> Self {
+        Self {
+            tap_slop: 10.,
+            tap_duration: Duration::from_millis(300),
+            double_tap_interval: Duration::from_millis(300),
+            double_tap_slop: 20.,
+            long_press_duration: Duration::from_millis(500),
+            pan_distance: 10.,
+            pinch_scale: 0.05,
+            rotate_angle: 0.1,
+        }
+    }
+}
+
+/// A gesture produced by the [`GestureRecognizer`].
+/// Pan, pinch, and rotate gestures report changes since their previous `Update`.
+/// Each window's pointers are recognized separately, so gestures never combine
+/// pointers from different windows.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecognizedGesture {
+    Tap {
+        x: f64,
+        y: f64,
+        window_id: WindowId,
+        timestamp: Duration,
+    },
+    /// Sent after the `Tap` that completes the double tap.
+    DoubleTap {
+        x: f64,
+        y: f64,
+        window_id: WindowId,
+        timestamp: Duration,
+    },
+    LongPress {
+        x: f64,
+        y: f64,
+        window_id: WindowId,
+        timestamp: Duration,
+    },
+    Begin {
+        kind: GestureKind,
+        window_id: WindowId,
+        timestamp: Duration,
+    },
+    Update {
+        gesture: Gesture,
+        window_id: WindowId,
+        timestamp: Duration,
+    },
+    End {
+        kind: GestureKind,
+        window_id: WindowId,
+        timestamp: Duration,
+    },
+}
+
+struct TrackedPointer {
+    pointer_id: u64,
+    position: (f64, f64),
+}
+
+// A single pointer press that may become a tap or long press.
+struct Press {
+    start_position: (f64, f64),
+    start_time: Duration,
+    long_press_sent: bool,
+}
+
+// Tracks the pointers' center, spread, and angle since the number of pointers last changed.
+struct Tracking {
+    start_center: (f64, f64),
+    last_center: (f64, f64),
+    start_distance: f64,
+    last_distance: f64,
+    start_angle: f64,
+    last_angle: f64,
+}
+
+// The pointers and gestures of a single window.
+// Positions are relative to the window, so pointers in different windows can't be combined.
+struct WindowGestures {
+    window_id: WindowId,
+    pointers: Vec<TrackedPointer>,
+    press: Option<Press>,
+    last_tap: Option<((f64, f64), Duration)>,
+    tracking: Option<Tracking>,
+    active: Vec<GestureKind>,
+}
+
+/// Produces taps, double taps, long presses, pans, pinches, and rotations from
+/// `PointerDown`, `PointerMoved`, and `PointerUp` events.
+/// This is intended for platforms that do not send native gesture events.
+///
+/// Mouse input only produces gestures while the primary button is held.
+///
+/// Pass each event to `handle_event` and then take the recognized gestures with `drain_gestures`.
+/// Because a long press can occur without any new events, `update` should be called
+/// periodically with a timestamp in the same time base as event timestamps.
+pub struct GestureRecognizer {
+    pub thresholds: GestureThresholds,
+    windows: Vec<WindowGestures>,
+    gestures: Vec<RecognizedGesture>,
+}
+
+impl Default for GestureRecognizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GestureRecognizer {
+    pub fn new() -> Self {
+        Self::with_thresholds(GestureThresholds::default())
+    }
+
+    pub fn with_thresholds(thresholds: GestureThresholds) -> Self {
+        Self {
+            thresholds,
+            windows: Vec::new(),
+            gestures: Vec::new(),
+        }
+    }
+
+    /// Returns the gestures recognized since the last call to `drain_gestures`.
+    pub fn drain_gestures(&mut self) -> std::vec::Drain<'_, RecognizedGesture> {
+        self.gestures.drain(..)
+    }
+
+    pub fn handle_event(&mut self, event: &Event) {
+        match event {
+            Event::PointerDown {
+                x,
+                y,
+                source,
+                button,
+                pointer_id,
+                window_id,
+                timestamp,
+                ..
+            } if is_gesture_button(*source, *button) => {
+                self.update(*timestamp);
+                window_gestures(&mut self.windows, *window_id).pointer_down(
+                    &mut self.gestures,
+                    *pointer_id,
+                    (*x, *y),
+                    *timestamp,
+                )
+            }
+            Event::PointerMoved {
+                x,
+                y,
+                pointer_id,
+                window_id,
+                timestamp,
+                ..
+            } => {
+                self.update(*timestamp);
+                window_gestures(&mut self.windows, *window_id).pointer_moved(
+                    &self.thresholds,
+                    &mut self.gestures,
+                    *pointer_id,
+                    (*x, *y),
+                    *timestamp,
+                )
+            }
+            Event::PointerUp {
+                x,
+                y,
+                source,
+                button,
+                pointer_id,
+                window_id,
+                timestamp,
+                ..
+            } if is_gesture_button(*source, *button) => {
+                self.update(*timestamp);
+                window_gestures(&mut self.windows, *window_id).pointer_up(
+                    &self.thresholds,
+                    &mut self.gestures,
+                    *pointer_id,
+                    (*x, *y),
+                    *timestamp,
+                )
+            }
+            _ => {}
+        }
+
+        // Windows with nothing in progress don't need to be remembered.
+        self.windows
+            .retain(|window| !window.pointers.is_empty() || window.last_tap.is_some());
+    }
+
+    /// Checks if a pointer has been held long enough to be a long press.
+    pub fn update(&mut self, timestamp: Duration) {
+        for window in &mut self.windows {
+            if let Some(press) = &mut window.press {
+                if !press.long_press_sent
+                    && timestamp >= press.start_time + self.thresholds.long_press_duration
+                {
+                    press.long_press_sent = true;
+                    let (x, y) = press.start_position;
+                    self.gestures.push(RecognizedGesture::LongPress {
+                        x,
+                        y,
+                        window_id: window.window_id,
+                        timestamp,
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn window_gestures(windows: &mut Vec<WindowGestures>, window_id: WindowId) -> &mut WindowGestures {
+    let index = match windows.iter().position(|w| w.window_id == window_id) {
+        Some(index) => index,
+        None => {
+            windows.push(WindowGestures {
+                window_id,
+                pointers: Vec::new(),
+                press: None,
+                last_tap: None,
+                tracking: None,
+                active: Vec::new(),
+            });
+            windows.len() - 1
+        }
+    };
+    &mut windows[index]
+}
+
+// Other mouse buttons are usually used for context menus or other actions, not gestures.
+fn is_gesture_button(source: PointerSource, button: PointerButton) -> bool {
+    source != PointerSource::Mouse || button == PointerButton::Primary
+}
+
+impl WindowGestures {
+    fn pointer_down(
+        &mut self,
+        gestures: &mut Vec<RecognizedGesture>,
+        pointer_id: u64,
+        position: (f64, f64),
+        timestamp: Duration,
+    ) {
+        if self.pointers.iter().any(|p| p.pointer_id == pointer_id) {
+            return;
+        }
+        self.pointers.push(TrackedPointer {
+            pointer_id,
+            position,
+        });
+
+        // Taps and long presses only occur with a single pointer.
+        self.press = if self.pointers.len() == 1 {
+            Some(Press {
+                start_position: position,
+                start_time: timestamp,
+                long_press_sent: false,
+            })
+        } else {
+            None
+        };
+        self.pointer_count_changed(gestures, timestamp);
+    }
+
+    fn pointer_moved(
+        &mut self,
+        thresholds: &GestureThresholds,
+        gestures: &mut Vec<RecognizedGesture>,
+        pointer_id: u64,
+        position: (f64, f64),
+        timestamp: Duration,
+    ) {
+        match self
+            .pointers
+            .iter_mut()
+            .find(|p| p.pointer_id == pointer_id)
+        {
+            Some(pointer) => pointer.position = position,
+            None => return,
+        }
+
+        if let Some(press) = &self.press {
+            if distance(press.start_position, position) > thresholds.tap_slop {
+                self.press = None;
+            }
+        }
+        self.track(thresholds, gestures, timestamp);
+    }
+
+    fn pointer_up(
+        &mut self,
+        thresholds: &GestureThresholds,
+        gestures: &mut Vec<RecognizedGesture>,
+        pointer_id: u64,
+        position: (f64, f64),
+        timestamp: Duration,
+    ) {
+        let index = match self
+            .pointers
+            .iter()
+            .position(|p| p.pointer_id == pointer_id)
+        {
+            Some(index) => index,
+            None => return,
+        };
+        self.pointers.remove(index);
+
+        if let Some(press) = self.press.take() {
+            let is_tap = !press.long_press_sent
+                && timestamp <= press.start_time + thresholds.tap_duration
+                && distance(press.start_position, position) <= thresholds.tap_slop;
+            if is_tap {
+                self.tap(thresholds, gestures, position, timestamp);
+            }
+        }
+        self.pointer_count_changed(gestures, timestamp);
+    }
+
+    fn tap(
+        &mut self,
+        thresholds: &GestureThresholds,
+        gestures: &mut Vec<RecognizedGesture>,
+        position: (f64, f64),
+        timestamp: Duration,
+    ) {
+        let (x, y) = position;
+        let window_id = self.window_id;
+        gestures.push(RecognizedGesture::Tap {
+            x,
+            y,
+            window_id,
+            timestamp,
+        });
+
+        let is_double_tap = match self.last_tap {
+            Some((last_position, last_timestamp)) => {
+                timestamp <= last_timestamp + thresholds.double_tap_interval
+                    && distance(last_position, position) <= thresholds.double_tap_slop
+            }
+            None => false,
+        };
+
+        if is_double_tap {
+            gestures.push(RecognizedGesture::DoubleTap {
+                x,
+                y,
+                window_id,
+                timestamp,
+            });
+            self.last_tap = None;
+        } else {
+            self.last_tap = Some((position, timestamp));
+        }
+    }
+
+    // When pointers are added or removed the center and spread of the pointers jumps,
+    // so tracking restarts from the new set of pointers.
+    fn pointer_count_changed(
+        &mut self,
+        gestures: &mut Vec<RecognizedGesture>,
+        timestamp: Duration,
+    ) {
+        if self.pointers.len() < 2 {
+            self.end(gestures, GestureKind::Pinch, timestamp);
+            self.end(gestures, GestureKind::Rotate, timestamp);
+        }
+        if self.pointers.is_empty() {
+            self.end(gestures, GestureKind::Pan, timestamp);
+            self.tracking = None;
+            return;
+        }
+
+        let (center, distance, angle) = self.measure();
+        self.tracking = Some(Tracking {
+            start_center: center,
+            last_center: center,
+            start_distance: distance,
+            last_distance: distance,
+            start_angle: angle,
+            last_angle: angle,
+        });
+    }
+
+    fn track(
+        &mut self,
+        thresholds: &GestureThresholds,
+        gestures: &mut Vec<RecognizedGesture>,
+        timestamp: Duration,
+    ) {
+        let (center, distance, angle) = self.measure();
+        let tracking = match &mut self.tracking {
+            Some(tracking) => tracking,
+            None => return,
+        };
+
+        let mut updates = Vec::new();
+
+        // A single pointer that may still be a tap or long press is not a pan.
+        let pan_started = self.active.contains(&GestureKind::Pan);
+        if pan_started
+            || (self.press.is_none()
+                && self::distance(tracking.start_center, center) > thresholds.pan_distance)
+        {
+            updates.push(Gesture::Pan {
+                delta_x: center.0 - tracking.last_center.0,
+                delta_y: center.1 - tracking.last_center.1,
+                fingers: self.pointers.len() as u32,
+            });
+        }
+        tracking.last_center = center;
+
+        if self.pointers.len() >= 2 {
+            let pinch_started = self.active.contains(&GestureKind::Pinch);
+            if tracking.last_distance > 0.
+                && (pinch_started
+                    || (distance / tracking.start_distance - 1.).abs() > thresholds.pinch_scale)
+            {
+                updates.push(Gesture::Pinch {
+                    scale: distance / tracking.last_distance - 1.,
+                });
+            }
+            tracking.last_distance = distance;
+
+            let rotate_started = self.active.contains(&GestureKind::Rotate);
+            if rotate_started
+                || angle_difference(angle, tracking.start_angle).abs() > thresholds.rotate_angle
+            {
+                updates.push(Gesture::Rotate {
+                    angle: angle_difference(angle, tracking.last_angle),
+                });
+            }
+            tracking.last_angle = angle;
+        }
+
+        let window_id = self.window_id;
+        for gesture in updates {
+            if !self.active.contains(&gesture.kind()) {
+                self.active.push(gesture.kind());
+                gestures.push(RecognizedGesture::Begin {
+                    kind: gesture.kind(),
+                    window_id,
+                    timestamp,
+                });
+            }
+            gestures.push(RecognizedGesture::Update {
+                gesture,
+                window_id,
+                timestamp,
+            });
+        }
+    }
+
+    fn end(
+        &mut self,
+        gestures: &mut Vec<RecognizedGesture>,
+        kind: GestureKind,
+        timestamp: Duration,
+    ) {
+        if let Some(index) = self.active.iter().position(|k| *k == kind) {
+            self.active.remove(index);
+            gestures.push(RecognizedGesture::End {
+                kind,
+                window_id: self.window_id,
+                timestamp,
+            });
+        }
+    }
+
+    // Returns the center of all pointers and the distance and angle between the first two.
+    fn measure(&self) -> ((f64, f64), f64, f64) {
+        let count = self.pointers.len().max(1) as f64;
+        let center = self.pointers.iter().fold((0., 0.), |sum, p| {
+            (sum.0 + p.position.0 / count, sum.1 + p.position.1 / count)
+        });
+
+        if let [first, second, ..] = self.pointers.as_slice() {
+            let (delta_x, delta_y) = (
+                second.position.0 - first.position.0,
+                second.position.1 - first.position.1,
+            );
+            (
+                center,
+                distance(first.position, second.position),
+                delta_y.atan2(delta_x),
+            )
+        } else {
+            (center, 0., 0.)
+        }
+    }
+}
+
+fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt()
+}
+
+// The smallest signed difference between two angles in radians.
+fn angle_difference(a: f64, b: f64) -> f64 {
+    use std::f64::consts::PI;
+    let difference = (a - b) % (2. * PI);
+    if difference > PI {
+        difference - 2. * PI
+    } else if difference < -PI {
+        difference + 2. * PI
+    } else {
+        difference
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Modifiers;
+
+    fn ms(milliseconds: u64) -> Duration {
+        Duration::from_millis(milliseconds)
+    }
+
+    fn window(id: usize) -> WindowId {
+        WindowId::new(id as *mut std::ffi::c_void)
+    }
+
+    fn down(id: u64, x: f64, y: f64, time: u64) -> Event {
+        Event::PointerDown {
+            x,
+            y,
+            source: PointerSource::Touch,
+            button: PointerButton::Primary,
+            pointer_id: id,
+            window_id: window(0),
+            modifiers: Modifiers::empty(),
+            timestamp: ms(time),
+        }
+    }
+
+    fn moved(id: u64, x: f64, y: f64, time: u64) -> Event {
+        Event::PointerMoved {
+            x,
+            y,
+            source: PointerSource::Touch,
+            pointer_id: id,
+            window_id: window(0),
+            modifiers: Modifiers::empty(),
+            timestamp: ms(time),
+        }
+    }
+
+    fn up(id: u64, x: f64, y: f64, time: u64) -> Event {
+        Event::PointerUp {
+            x,
+            y,
+            source: PointerSource::Touch,
+            button: PointerButton::Primary,
+            pointer_id: id,
+            window_id: window(0),
+            modifiers: Modifiers::empty(),
+            timestamp: ms(time),
+        }
+    }
+
+    fn in_window(mut event: Event, id: usize) -> Event {
+        match &mut event {
+            Event::PointerDown { window_id, .. }
+            | Event::PointerMoved { window_id, .. }
+            | Event::PointerUp { window_id, .. } => *window_id = window(id),
+            _ => {}
+        }
+        event
+    }
+
+    fn mouse(mut event: Event, mouse_button: PointerButton) -> Event {
+        match &mut event {
+            Event::PointerDown { source, button, .. } | Event::PointerUp { source, button, .. } => {
+                *source = PointerSource::Mouse;
+                *button = mouse_button;
+            }
+            Event::PointerMoved { source, .. } => *source = PointerSource::Mouse,
+            _ => {}
+        }
+        event
+    }
+
+    fn recognize(events: &[Event]) -> Vec<RecognizedGesture> {
+        let mut recognizer = GestureRecognizer::new();
+        for event in events {
+            recognizer.handle_event(event);
+        }
+        recognizer.drain_gestures().collect()
+    }
+
+    fn updates(gestures: &[RecognizedGesture], kind: GestureKind) -> Vec<Gesture> {
+        gestures
+            .iter()
+            .filter_map(|g| match g {
+                RecognizedGesture::Update { gesture, .. } if gesture.kind() == kind => {
+                    Some(*gesture)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn tap() {
+        let gestures = recognize(&[down(0, 10., 10., 0), up(0, 12., 11., 100)]);
+        assert_eq!(
+            gestures,
+            vec![RecognizedGesture::Tap {
+                x: 12.,
+                y: 11.,
+                window_id: window(0),
+                timestamp: ms(100)
+            }]
+        );
+    }
+
+    #[test]
+    fn slow_release_is_not_a_tap() {
+        let gestures = recognize(&[down(0, 10., 10., 0), up(0, 10., 10., 400)]);
+        assert!(gestures.is_empty());
+    }
+
+    #[test]
+    fn double_tap() {
+        let gestures = recognize(&[
+            down(0, 10., 10., 0),
+            up(0, 10., 10., 50),
+            down(1, 14., 12., 150),
+            up(1, 14., 12., 200),
+        ]);
+        assert_eq!(gestures.len(), 3);
+        assert_eq!(
+            gestures[2],
+            RecognizedGesture::DoubleTap {
+                x: 14.,
+                y: 12.,
+                window_id: window(0),
+                timestamp: ms(200)
+            }
+        );
+    }
+
+    #[test]
+    fn distant_taps_are_not_a_double_tap() {
+        let gestures = recognize(&[
+            down(0, 10., 10., 0),
+            up(0, 10., 10., 50),
+            down(1, 200., 10., 150),
+            up(1, 200., 10., 200),
+        ]);
+        assert!(gestures
+            .iter()
+            .all(|g| matches!(g, RecognizedGesture::Tap { .. })));
+    }
+
+    #[test]
+    fn long_press_from_update() {
+        let mut recognizer = GestureRecognizer::new();
+        recognizer.handle_event(&down(0, 10., 10., 0));
+        recognizer.update(ms(200));
+        assert_eq!(recognizer.drain_gestures().count(), 0);
+
+        recognizer.update(ms(600));
+        recognizer.handle_event(&up(0, 10., 10., 700));
+        let gestures: Vec<_> = recognizer.drain_gestures().collect();
+        assert_eq!(
+            gestures,
+            vec![RecognizedGesture::LongPress {
+                x: 10.,
+                y: 10.,
+                window_id: window(0),
+                timestamp: ms(600)
+            }]
+        );
+    }
+
+    #[test]
+    fn long_press_cancelled_by_movement() {
+        let mut recognizer = GestureRecognizer::new();
+        recognizer.handle_event(&down(0, 10., 10., 0));
+        recognizer.handle_event(&moved(0, 50., 10., 100));
+        recognizer.update(ms(600));
+        assert!(recognizer
+            .drain_gestures()
+            .all(|g| !matches!(g, RecognizedGesture::LongPress { .. })));
+    }
+
+    #[test]
+    fn pan() {
+        let gestures = recognize(&[
+            down(0, 10., 10., 0),
+            moved(0, 15., 10., 10),
+            moved(0, 30., 10., 20),
+            moved(0, 40., 20., 30),
+            up(0, 40., 20., 40),
+        ]);
+        assert_eq!(
+            gestures.first(),
+            Some(&RecognizedGesture::Begin {
+                kind: GestureKind::Pan,
+                window_id: window(0),
+                timestamp: ms(20)
+            })
+        );
+        assert_eq!(
+            updates(&gestures, GestureKind::Pan),
+            vec![
+                Gesture::Pan {
+                    delta_x: 15.,
+                    delta_y: 0.,
+                    fingers: 1
+                },
+                Gesture::Pan {
+                    delta_x: 10.,
+                    delta_y: 10.,
+                    fingers: 1
+                }
+            ]
+        );
+        assert_eq!(
+            gestures.last(),
+            Some(&RecognizedGesture::End {
+                kind: GestureKind::Pan,
+                window_id: window(0),
+                timestamp: ms(40)
+            })
+        );
+    }
+
+    #[test]
+    fn pinch() {
+        let gestures = recognize(&[
+            down(0, 100., 100., 0),
+            down(1, 200., 100., 0),
+            moved(1, 250., 100., 10),
+            moved(0, 50., 100., 20),
+            up(1, 250., 100., 30),
+        ]);
+        let scales: Vec<f64> = updates(&gestures, GestureKind::Pinch)
+            .iter()
+            .map(|g| match g {
+                Gesture::Pinch { scale } => *scale,
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(scales.len(), 2);
+        assert!((scales[0] - 0.5).abs() < 1e-10);
+        assert!((scales[1] - 1. / 3.).abs() < 1e-10);
+        assert!(updates(&gestures, GestureKind::Rotate).is_empty());
+        assert!(gestures.contains(&RecognizedGesture::End {
+            kind: GestureKind::Pinch,
+            window_id: window(0),
+            timestamp: ms(30)
+        }));
+    }
+
+    #[test]
+    fn rotate() {
+        let gestures = recognize(&[
+            down(0, 100., 100., 0),
+            down(1, 200., 100., 0),
+            moved(1, 100., 200., 10),
+            up(1, 100., 200., 20),
+            up(0, 100., 100., 30),
+        ]);
+        let rotations = updates(&gestures, GestureKind::Rotate);
+        assert_eq!(rotations.len(), 1);
+        match rotations[0] {
+            Gesture::Rotate { angle } => {
+                assert!((angle - std::f64::consts::FRAC_PI_2).abs() < 1e-10)
+            }
+            _ => unreachable!(),
+        }
+        assert!(gestures.contains(&RecognizedGesture::End {
+            kind: GestureKind::Rotate,
+            window_id: window(0),
+            timestamp: ms(20)
+        }));
+    }
+
+    #[test]
+    fn windows_are_recognized_separately() {
+        // Pointers in different windows share an id but are not one two-pointer gesture.
+        let gestures = recognize(&[
+            in_window(down(0, 10., 10., 0), 1),
+            in_window(down(0, 500., 10., 0), 2),
+            in_window(up(0, 10., 10., 50), 1),
+            in_window(up(0, 500., 10., 60), 2),
+        ]);
+        assert_eq!(
+            gestures,
+            vec![
+                RecognizedGesture::Tap {
+                    x: 10.,
+                    y: 10.,
+                    window_id: window(1),
+                    timestamp: ms(50)
+                },
+                RecognizedGesture::Tap {
+                    x: 500.,
+                    y: 10.,
+                    window_id: window(2),
+                    timestamp: ms(60)
+                }
+            ]
+        );
+
+        let gestures = recognize(&[
+            in_window(down(0, 100., 100., 0), 1),
+            in_window(down(0, 100., 100., 0), 2),
+            in_window(moved(0, 130., 100., 10), 1),
+            in_window(moved(0, 100., 80., 20), 2),
+        ]);
+        assert!(updates(&gestures, GestureKind::Pinch).is_empty());
+        assert_eq!(
+            gestures,
+            vec![
+                RecognizedGesture::Begin {
+                    kind: GestureKind::Pan,
+                    window_id: window(1),
+                    timestamp: ms(10)
+                },
+                RecognizedGesture::Update {
+                    gesture: Gesture::Pan {
+                        delta_x: 30.,
+                        delta_y: 0.,
+                        fingers: 1
+                    },
+                    window_id: window(1),
+                    timestamp: ms(10)
+                },
+                RecognizedGesture::Begin {
+                    kind: GestureKind::Pan,
+                    window_id: window(2),
+                    timestamp: ms(20)
+                },
+                RecognizedGesture::Update {
+                    gesture: Gesture::Pan {
+                        delta_x: 0.,
+                        delta_y: -20.,
+                        fingers: 1
+                    },
+                    window_id: window(2),
+                    timestamp: ms(20)
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn only_the_primary_mouse_button_makes_gestures() {
+        let gestures = recognize(&[
+            mouse(down(0, 10., 10., 0), PointerButton::Secondary),
+            mouse(up(0, 10., 10., 50), PointerButton::Secondary),
+            mouse(down(0, 10., 10., 100), PointerButton::Auxillary),
+            mouse(up(0, 10., 10., 150), PointerButton::Auxillary),
+        ]);
+        assert!(gestures.is_empty());
+
+        let mut recognizer = GestureRecognizer::new();
+        recognizer.handle_event(&mouse(down(0, 10., 10., 0), PointerButton::Secondary));
+        recognizer.update(ms(600));
+        assert_eq!(recognizer.drain_gestures().count(), 0);
+
+        // Releasing another button doesn't end a primary button press.
+        let gestures = recognize(&[
+            mouse(down(0, 10., 10., 0), PointerButton::Primary),
+            mouse(up(0, 10., 10., 20), PointerButton::Secondary),
+            mouse(up(0, 10., 10., 50), PointerButton::Primary),
+        ]);
+        assert_eq!(
+            gestures,
+            vec![RecognizedGesture::Tap {
+                x: 10.,
+                y: 10.,
+                window_id: window(0),
+                timestamp: ms(50)
+            }]
+        );
+    }
+}
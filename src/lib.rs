@@ -52,6 +52,8 @@
 //! See the `simple_gl.rs` example.
 mod application;
 mod async_application;
+mod event_loop_proxy;
+mod gesture_recognizer;
 mod state_tracker;
 mod window;
 mod window_builder;
@@ -61,12 +63,19 @@ use kapp_platforms::prelude as platform;
 #[cfg(feature = "gl_context")]
 pub use kapp_gl_context::prelude::*;
 
-pub use platform::{Cursor, Event, Key, PointerButton, PointerSource, WindowId};
+pub use platform::{
+    AppInfo, CompositionSegment, CompositionSegmentKind, ControlFlow, Cursor, Event, FileDialogId,
+    FileDialogOptions, FileFilter, Gesture, GestureKind, HitTestResult, Key, MessageBox,
+    MessageBoxButton, MessageBoxButtons, MessageBoxKind, Modifiers, PointerButton, PointerSource,
+    ResizeEdge, ScrollDelta, ScrollPhase, UserEvent, WindowId, WindowKind,
+};
 
-pub use application::{initialize, Application, EventLoop};
+pub use application::{initialize, initialize_with, Application, EventLoop};
 
 pub use async_application::*;
 
+pub use event_loop_proxy::EventLoopProxy;
+pub use gesture_recognizer::{GestureRecognizer, GestureThresholds, RecognizedGesture};
 pub use state_tracker::StateTracker;
 pub use window::Window;
 pub use window_builder::WindowBuilder;
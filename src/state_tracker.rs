@@ -1,4 +1,4 @@
-use crate::{Event, Key, PointerButton};
+use crate::{Event, Key, Modifiers, PointerButton, WindowId};
 use std::collections::HashMap;
 use std::time::Duration;
 
@@ -8,13 +8,19 @@ use std::time::Duration;
 // * Window color spaces
 
 /// Tracks key and pointer input state based on events.
+/// Each pressed key and button also records the window it was pressed in.
 pub struct StateTracker {
-    keys_down_since_last_frame: HashMap<Key, Duration>, // Key was pressed since the last clear for any window.
-    keys_pressed: HashMap<Key, Duration>,
-    pointer_buttons_down_since_last_frame: HashMap<PointerButton, Duration>, // pointer was pressed since the last clear for any window.
-    pointer_buttons_pressed: HashMap<PointerButton, Duration>,
+    keys_down_since_last_frame: HashMap<Key, (WindowId, Duration)>, // Key was pressed since the last clear for any window.
+    keys_pressed: HashMap<Key, (WindowId, Duration)>,
+    pointer_buttons_down_since_last_frame: HashMap<PointerButton, (WindowId, Duration)>, // pointer was pressed since the last clear for any window.
+    pointer_buttons_pressed: HashMap<PointerButton, (WindowId, Duration)>,
     pointer_position: (f64, f64),
+    window_pointer_positions: HashMap<WindowId, (f64, f64)>,
+    focused_window: Option<WindowId>,
+    hovered_window: Option<WindowId>,
     mouse_motion: (f64, f64),
+    raw_mouse_motion: (f64, f64),
+    modifiers: Modifiers,
 }
 
 impl StateTracker {
@@ -25,45 +31,116 @@ impl StateTracker {
             pointer_buttons_down_since_last_frame: HashMap::with_capacity(16),
             pointer_buttons_pressed: HashMap::with_capacity(16),
             pointer_position: (0., 0.),
+            window_pointer_positions: HashMap::new(),
+            focused_window: None,
+            hovered_window: None,
             mouse_motion: (0., 0.),
+            raw_mouse_motion: (0., 0.),
+            modifiers: Modifiers::empty(),
         }
     }
 
     pub fn handle_event(&mut self, event: &Event) {
         match event {
-            Event::KeyDown { key, timestamp } => {
-                self.keys_pressed.insert(*key, *timestamp);
-                self.keys_down_since_last_frame.insert(*key, *timestamp);
+            // Physical keys are tracked because the logical key may change between press and release.
+            Event::KeyDown {
+                physical_key,
+                window_id,
+                modifiers,
+                timestamp,
+                ..
+            } => {
+                self.keys_pressed
+                    .insert(*physical_key, (*window_id, *timestamp));
+                self.keys_down_since_last_frame
+                    .insert(*physical_key, (*window_id, *timestamp));
+                self.modifiers = *modifiers;
             }
-            Event::KeyUp { key, .. } => {
-                self.keys_pressed.remove(&key);
+            Event::KeyUp {
+                physical_key,
+                modifiers,
+                ..
+            } => {
+                self.keys_pressed.remove(&physical_key);
+                self.modifiers = *modifiers;
             }
+            Event::ModifiersChanged { modifiers } => self.modifiers = *modifiers,
             Event::PointerDown {
-                button, timestamp, ..
+                button,
+                window_id,
+                timestamp,
+                ..
             } => {
-                self.pointer_buttons_pressed.insert(*button, *timestamp);
+                self.pointer_buttons_pressed
+                    .insert(*button, (*window_id, *timestamp));
                 self.pointer_buttons_down_since_last_frame
-                    .insert(*button, *timestamp);
+                    .insert(*button, (*window_id, *timestamp));
             }
             Event::PointerUp { button, .. } => {
                 self.pointer_buttons_pressed.remove(&button);
             }
-            Event::PointerMoved { x, y, .. } => self.pointer_position = (*x, *y),
+            Event::PointerMoved {
+                x, y, window_id, ..
+            } => {
+                self.pointer_position = (*x, *y);
+                self.window_pointer_positions.insert(*window_id, (*x, *y));
+            }
+            Event::PointerEntered { window_id, x, y } => {
+                self.hovered_window = Some(*window_id);
+                self.pointer_position = (*x, *y);
+                self.window_pointer_positions.insert(*window_id, (*x, *y));
+            }
+            Event::PointerLeft { window_id } => {
+                if self.hovered_window == Some(*window_id) {
+                    self.hovered_window = None;
+                }
+                self.window_pointer_positions.remove(window_id);
+            }
+            Event::WindowGainedFocus { window_id } => self.focused_window = Some(*window_id),
+            Event::WindowLostFocus { window_id } if self.focused_window == Some(*window_id) => {
+                self.focused_window = None
+            }
             Event::MouseMotion {
                 delta_x, delta_y, ..
             } => self.mouse_motion = (self.mouse_motion.0 + delta_x, self.mouse_motion.1 + delta_y),
+            Event::RawMouseMotion {
+                delta_x, delta_y, ..
+            } => {
+                self.raw_mouse_motion = (
+                    self.raw_mouse_motion.0 + delta_x,
+                    self.raw_mouse_motion.1 + delta_y,
+                )
+            }
             _ => {}
         };
     }
 
+    /// Forgets the state of a window that has been closed.
+    /// Keys and buttons pressed in the window are released because their up events won't be sent.
+    /// This is called when a `Window` is dropped.
+    pub fn window_closed(&mut self, window_id: WindowId) {
+        self.keys_pressed.retain(|_, (id, _)| *id != window_id);
+        self.pointer_buttons_pressed
+            .retain(|_, (id, _)| *id != window_id);
+        self.window_pointer_positions.remove(&window_id);
+        if self.focused_window == Some(window_id) {
+            self.focused_window = None;
+        }
+        if self.hovered_window == Some(window_id) {
+            self.hovered_window = None;
+        }
+    }
+
     /// Reset any "button down" states
     pub fn clear(&mut self) {
         self.pointer_buttons_down_since_last_frame.clear();
         self.keys_down_since_last_frame.clear();
         self.mouse_motion = (0., 0.);
+        self.raw_mouse_motion = (0., 0.);
     }
 
     /// Returns true if the key has been pressed since the last call to clear.
+    /// Keys are physical keys, named after the key in that position on a US keyboard.
     pub fn key_down(&self, key: Key) -> bool {
         self.keys_down_since_last_frame.contains_key(&key)
     }
@@ -79,7 +156,7 @@ impl StateTracker {
         pressed
     }
 
-    /// Returns if the key is currently down
+    /// Returns if the physical key is currently down
     pub fn key(&self, key: Key) -> bool {
         self.keys_pressed.contains_key(&key)
     }
@@ -102,4 +179,52 @@ impl StateTracker {
     pub fn mouse_motion(&self) -> (f64, f64) {
         self.mouse_motion
     }
+
+    /// Returns the unaccelerated mouse motion since the last call to clear.
+    pub fn raw_mouse_motion(&self) -> (f64, f64) {
+        self.raw_mouse_motion
+    }
+
+    /// Returns if the physical key is currently down and was pressed in the window.
+    pub fn window_key(&self, window_id: WindowId, key: Key) -> bool {
+        matches!(self.keys_pressed.get(&key), Some((id, _)) if *id == window_id)
+    }
+
+    /// Returns true if the physical key has been pressed in the window since the last call to clear.
+    pub fn window_key_down(&self, window_id: WindowId, key: Key) -> bool {
+        matches!(self.keys_down_since_last_frame.get(&key), Some((id, _)) if *id == window_id)
+    }
+
+    /// Returns true if the pointer button is pressed and was pressed in the window.
+    pub fn window_pointer_button(&self, window_id: WindowId, button: PointerButton) -> bool {
+        matches!(self.pointer_buttons_pressed.get(&button), Some((id, _)) if *id == window_id)
+    }
+
+    /// Returns true if the pointer button has been pressed in the window since the last call to clear.
+    pub fn window_pointer_button_down(&self, window_id: WindowId, button: PointerButton) -> bool {
+        matches!(
+            self.pointer_buttons_down_since_last_frame.get(&button),
+            Some((id, _)) if *id == window_id
+        )
+    }
+
+    /// Returns the last pointer position within the window, or `None` if the pointer is not over it.
+    pub fn window_pointer_position(&self, window_id: WindowId) -> Option<(f64, f64)> {
+        self.window_pointer_positions.get(&window_id).copied()
+    }
+
+    /// Returns the window the pointer is over, if any.
+    pub fn hovered_window(&self) -> Option<WindowId> {
+        self.hovered_window
+    }
+
+    /// Returns the window that most recently gained focus, if it still has focus.
+    pub fn focused_window(&self) -> Option<WindowId> {
+        self.focused_window
+    }
+
+    /// Returns the keyboard modifiers that are currently held.
+    pub fn modifiers(&self) -> Modifiers {
+        self.modifiers
+    }
 }
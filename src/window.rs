@@ -1,4 +1,5 @@
 use crate::platform::*;
+use crate::state_tracker::StateTracker;
 use std::cell::RefCell;
 use std::rc::Rc;
 
@@ -7,16 +8,19 @@ use std::rc::Rc;
 pub struct Window {
     pub id: WindowId,
     platform_application: Rc<RefCell<PlatformApplication>>,
+    state_tracker: Rc<RefCell<StateTracker>>,
 }
 
 impl Window {
     pub(crate) fn new(
         id: WindowId,
         platform_application: Rc<RefCell<PlatformApplication>>,
+        state_tracker: Rc<RefCell<StateTracker>>,
     ) -> Self {
         Self {
             id,
             platform_application,
+            state_tracker,
         }
     }
 
@@ -46,6 +50,54 @@ impl Window {
             .fullscreen_window(self.id);
     }
 
+    /// Shows a window that was hidden or created with `WindowBuilder::visible(false)`.
+    pub fn show(&self) {
+        self.platform_application.borrow_mut().show_window(self.id);
+    }
+
+    pub fn hide(&self) {
+        self.platform_application.borrow_mut().hide_window(self.id);
+    }
+
+    /// Brings the window to the front and gives it keyboard focus.
+    /// Some platforms may instead request attention if another application has focus.
+    pub fn focus(&self) {
+        self.platform_application.borrow_mut().focus_window(self.id);
+    }
+
+    /// Requests the user's attention, like by flashing the window's taskbar or dock icon.
+    /// On Linux this is only supported on X11. Unsupported on Web.
+    pub fn request_attention(&self) {
+        self.platform_application
+            .borrow_mut()
+            .request_window_attention(self.id);
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.platform_application
+            .borrow_mut()
+            .is_window_visible(self.id)
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.platform_application
+            .borrow_mut()
+            .is_window_focused(self.id)
+    }
+
+    pub fn is_minimized(&self) -> bool {
+        self.platform_application
+            .borrow_mut()
+            .is_window_minimized(self.id)
+    }
+
+    /// On MacOS this is true if the window is zoomed.
+    pub fn is_maximized(&self) -> bool {
+        self.platform_application
+            .borrow_mut()
+            .is_window_maximized(self.id)
+    }
+
     /// Sets the title displayed at the top of the window
     pub fn set_title(&mut self, title: &str) {
         self.platform_application
@@ -68,10 +120,162 @@ impl Window {
             .set_window_size(self.id, width, height);
     }
 
+    /// Sets the minimum size of the window's content area (excluding the titlebar and borders)
+    /// with physical coordinates.
+    /// `None` removes the limit.
+    pub fn set_min_size(&self, size: Option<(u32, u32)>) {
+        self.platform_application
+            .borrow_mut()
+            .set_window_min_size(self.id, size);
+    }
+
+    /// Sets the maximum size of the window's content area (excluding the titlebar and borders)
+    /// with physical coordinates.
+    /// `None` removes the limit.
+    pub fn set_max_size(&self, size: Option<(u32, u32)>) {
+        self.platform_application
+            .borrow_mut()
+            .set_window_max_size(self.id, size);
+    }
+
+    /// Sets if the user can resize the window.
+    pub fn set_resizable(&self, resizable: bool) {
+        self.platform_application
+            .borrow_mut()
+            .set_window_resizable(self.id, resizable);
+    }
+
+    /// Shows or hides the window's titlebar and borders.
+    /// On MacOS the window buttons are hidden and the content extends beneath the titlebar.
+    /// Unsupported on Web.
+    pub fn set_decorations(&self, decorations: bool) {
+        self.platform_application
+            .borrow_mut()
+            .set_window_decorations(self.id, decorations);
+    }
+
+    /// Keeps the width to height ratio of the window's content area while the user resizes it.
+    /// For example `Some((16, 9))`. `None` allows any ratio.
+    /// On Linux this is only supported on X11. Unsupported on Web.
+    pub fn set_aspect_ratio(&self, ratio: Option<(u32, u32)>) {
+        self.platform_application
+            .borrow_mut()
+            .set_window_aspect_ratio(self.id, ratio);
+    }
+
+    /// Resizes the window's content area in steps of a size while the user resizes it.
+    /// Increments are specified with physical coordinates.
+    /// On MacOS an aspect ratio and resize increments can't be used together,
+    /// setting one removes the other.
+    /// On Linux this is only supported on X11. Unsupported on Web.
+    pub fn set_resize_increments(&self, increments: Option<(u32, u32)>) {
+        self.platform_application
+            .borrow_mut()
+            .set_window_resize_increments(self.id, increments);
+    }
+
+    /// Sets the opacity of the entire window including its titlebar,
+    /// from 0.0 (invisible) to 1.0 (opaque).
+    /// This is useful for fading a window in or out.
+    pub fn set_opacity(&self, opacity: f64) {
+        self.platform_application
+            .borrow_mut()
+            .set_window_opacity(self.id, opacity.clamp(0.0, 1.0));
+    }
+
+    /// Sets a function the OS uses to find which part of the window is under the pointer.
+    /// The function is passed a position relative to the window's upper left corner
+    /// in physical coordinates.
+    /// Dragging a `Caption` region moves the window and dragging a resize region resizes it,
+    /// which is useful for windows built with `without_titlebar`.
+    /// On MacOS resize regions are treated as `Client`.
+    /// Unsupported on Web.
+    pub fn set_hit_test(&self, hit_test: impl Fn(f64, f64) -> HitTestResult + 'static) {
+        self.platform_application
+            .borrow_mut()
+            .set_window_hit_test(self.id, Some(Box::new(hit_test)));
+    }
+
+    /// Removes a hit-test set with `set_hit_test`.
+    pub fn remove_hit_test(&self) {
+        self.platform_application
+            .borrow_mut()
+            .set_window_hit_test(self.id, None);
+    }
+
+    /// Starts moving the window with the pointer as if its titlebar was dragged.
+    /// Call this in response to a `PointerDown` event for the primary button.
+    /// On Linux this is only supported on X11. Unsupported on Web, use `set_hit_test` instead.
+    pub fn begin_drag_move(&self) {
+        self.platform_application
+            .borrow_mut()
+            .begin_window_drag_move(self.id);
+    }
+
+    /// Starts resizing the window from an edge or corner with the pointer.
+    /// Call this in response to a `PointerDown` event for the primary button.
+    /// On Linux this is only supported on X11.
+    /// Unsupported on MacOS and Web, use `set_hit_test` instead.
+    pub fn begin_drag_resize(&self, edge: ResizeEdge) {
+        self.platform_application
+            .borrow_mut()
+            .begin_window_drag_resize(self.id, edge);
+    }
+
+    /// Moves the pointer to a position relative to the window's upper left corner.
+    /// Position is specified with physical coordinates.
+    /// Unsupported on Web.
+    pub fn set_pointer_position(&self, x: f64, y: f64) {
+        self.platform_application
+            .borrow_mut()
+            .set_pointer_position(self.id, x, y);
+    }
+
+    /// Keeps the pointer inside this window while it is visible,
+    /// which is useful for scrolling when the pointer reaches the edge of the window.
+    /// A `PointerLockLost` event is sent if the OS releases the pointer.
+    /// Unsupported on MacOS and Web.
+    pub fn confine_pointer(&self, confine: bool) {
+        self.platform_application
+            .borrow_mut()
+            .confine_pointer(self.id, confine);
+    }
+
+    /// Prevents the pointer from moving while this window has focus.
+    /// `MouseMotion` events continue to be sent.
+    /// A `PointerLockLost` event is sent if the OS releases the pointer.
+    pub fn lock_pointer(&self) {
+        self.platform_application.borrow_mut().lock_pointer(self.id);
+    }
+
+    /// Allows the pointer to move freely after a call to `lock_pointer`.
+    pub fn unlock_pointer(&self) {
+        self.platform_application
+            .borrow_mut()
+            .unlock_pointer(self.id);
+    }
+
+    /// Enable text input while this window has focus.
+    /// `kapp` will send `CharacterReceived` events for this window until `end_text_input` is called.
+    /// Operating system UI related to text input may appear.
+    pub fn start_text_input(&self) {
+        self.platform_application
+            .borrow_mut()
+            .start_text_input(Some(self.id));
+    }
+
+    /// Disable text input for this window after a call to `start_text_input`.
+    /// This does not disable text input enabled with `Application::start_text_input`.
+    pub fn end_text_input(&self) {
+        self.platform_application
+            .borrow_mut()
+            .end_text_input(Some(self.id));
+    }
+
     /// Lets the OS know where it should place text input related popups like
     /// accent character selection.
-    /// Position is specified relative to the window's upper left corner.
-    /// ONLY SUPPORTED ON MAC (for now)
+    /// Position is specified relative to the window's upper left corner with physical coordinates.
+    /// Unsupported on Web.
     pub fn set_text_input_rectangle(&self, x: f64, y: f64, width: f64, height: f64) {
         self.platform_application
             .borrow_mut()
@@ -104,7 +308,14 @@ impl Window {
 
 impl Drop for Window {
     fn drop(&mut self) {
-        self.platform_application.borrow_mut().close_window(self.id);
+        let mut platform_application = self.platform_application.borrow_mut();
+        platform_application.end_text_input(Some(self.id));
+        platform_application.close_window(self.id);
+
+        // A closed window won't send any more events to update its state.
+        if let Ok(mut state_tracker) = self.state_tracker.try_borrow_mut() {
+            state_tracker.window_closed(self.id);
+        }
     }
 }
 
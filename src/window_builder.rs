@@ -17,7 +17,13 @@ impl<'a> WindowBuilder<'a> {
                 maximum_size: None,
                 resizable: true,
                 without_titlebar: false,
+                visible: true,
+                always_on_top: false,
+                transparent: false,
+                focused: true,
                 title: "Untitled".to_string(),
+                parent: None,
+                kind: WindowKind::Normal,
             },
         }
     }
@@ -34,6 +40,8 @@ impl<'a> WindowBuilder<'a> {
     }
 
     /// Specifies the lower left corner of the window.
+    /// `Popup` and `Tooltip` windows with a parent are positioned
+    /// relative to the upper left corner of the parent's content area.
     pub fn position(&mut self, x: u32, y: u32) -> &mut Self {
         self.window_parameters.position = Some((x, y));
         self
@@ -58,13 +66,63 @@ impl<'a> WindowBuilder<'a> {
         self
     }
 
-    /// Only available on MacOS for now.
-    /// This feature isn't well tested and may result in sizing errors.
+    /// Creates the window without a titlebar.
+    /// On MacOS the window's content extends beneath a transparent titlebar
+    /// and the window buttons remain visible.
+    /// On other platforms the window has no titlebar or borders,
+    /// the same as calling `Window::set_decorations(false)`.
     pub fn without_titlebar(&mut self) -> &mut Self {
         self.window_parameters.without_titlebar = true;
         self
     }
 
+    /// Specify if the window should be shown when it's created.
+    /// A hidden window can be shown later with `Window::show`.
+    pub fn visible(&mut self, visible: bool) -> &mut Self {
+        self.window_parameters.visible = visible;
+        self
+    }
+
+    /// Specify if the window should stay above other windows.
+    pub fn always_on_top(&mut self, always_on_top: bool) -> &mut Self {
+        self.window_parameters.always_on_top = always_on_top;
+        self
+    }
+
+    /// Specify if the window's background should be transparent.
+    /// Content drawn with an alpha below 1.0 will show what's behind the window.
+    /// Use a `GLContext` with alpha bits to draw transparent content.
+    /// On Linux with X11 the window is created with a 32 bit visual, which is transparent
+    /// when a compositor is running.
+    /// This has no effect on Web, where the GL context's alpha bits make the canvas transparent.
+    pub fn transparent(&mut self, transparent: bool) -> &mut Self {
+        self.window_parameters.transparent = transparent;
+        self
+    }
+
+    /// Specify if the window should take focus when it's shown.
+    /// Some platforms may focus new windows regardless.
+    pub fn focused(&mut self, focused: bool) -> &mut Self {
+        self.window_parameters.focused = focused;
+        self
+    }
+
+    /// Attaches the window to a parent window.
+    /// How the window behaves relative to its parent depends on its `WindowKind`.
+    pub fn parent(&mut self, parent: &Window) -> &mut Self {
+        self.window_parameters.parent = Some(parent.id);
+        self
+    }
+
+    /// Specify the window's role, like `WindowKind::Dialog` or `WindowKind::Tooltip`.
+    /// `Dialog`, `Popup`, and `Tooltip` windows should have a parent set with `parent`.
+    /// On SDL window kinds are only supported on X11.
+    /// This has no effect on Web.
+    pub fn kind(&mut self, kind: WindowKind) -> &mut Self {
+        self.window_parameters.kind = kind;
+        self
+    }
+
     pub fn build(&mut self) -> Result<Window, ()> {
         // Clamp the window size to the minimum width and height
         if let Some(size) = &mut self.window_parameters.size {
@@ -79,6 +137,7 @@ impl<'a> WindowBuilder<'a> {
                 .borrow_mut()
                 .new_window(&self.window_parameters),
             self.application.platform_application.clone(),
+            self.application.state_tracker.clone(),
         ))
     }
 }